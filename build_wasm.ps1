@@ -1,5 +1,5 @@
 # Build wasm binary
-cargo build --lib --target wasm32-unknown-unknown --release
+cargo build --lib --target wasm32-unknown-unknown --release --no-default-features
 
 # Copy to web folder
 Copy-Item -Path "./target/wasm32-unknown-unknown/release/customasm.wasm" -Destination "./web/customasm.wasm"
@@ -0,0 +1,184 @@
+use crate::*;
+
+
+/// Converts a parsed file to JSON, to be kept in the cache directory.
+///
+/// The AST types derive `serde` traits, so new fields are
+/// persisted without changes here. Every span must belong to
+/// the given file, since file handles change between runs and
+/// are restored by `decode_ast` instead.
+/// Returns `None` for anything that can't be persisted, like
+/// spans from other files or literals other than integers,
+/// booleans, and strings, in which case the file is only
+/// cached in memory.
+pub fn encode_ast(
+    ast: &asm::AstTopLevel,
+    file_handle: util::FileServerHandle)
+    -> Option<serde_json::Value>
+{
+    with_file_handle(
+        file_handle,
+        || serde_json::to_value(ast).ok())
+}
+
+
+pub fn decode_ast(
+    json: &serde_json::Value,
+    file_handle: util::FileServerHandle)
+    -> Option<asm::AstTopLevel>
+{
+    with_file_handle(
+        file_handle,
+        || serde::Deserialize::deserialize(json).ok())
+}
+
+
+thread_local!
+{
+    /// The file whose spans are being encoded or decoded,
+    /// as `serde` has no way to pass it down to each span
+    static FILE_HANDLE: std::cell::Cell<Option<util::FileServerHandle>> =
+        const { std::cell::Cell::new(None) };
+}
+
+
+fn with_file_handle<T>(
+    file_handle: util::FileServerHandle,
+    f: impl FnOnce() -> T)
+    -> T
+{
+    let prev = FILE_HANDLE.replace(Some(file_handle));
+    let result = f();
+    FILE_HANDLE.set(prev);
+    result
+}
+
+
+/// Only the location is kept, as an empty array for
+/// dummy spans, since the file handle is given again
+/// when decoding
+impl serde::Serialize for diagn::Span
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        use serde::ser::Error;
+
+        match self.location()
+        {
+            None => [0usize; 0].serialize(serializer),
+
+            Some(_) if Some(self.file_handle) != FILE_HANDLE.get() =>
+                Err(S::Error::custom("span belongs to another file")),
+
+            Some((start, end)) => [start, end].serialize(serializer),
+        }
+    }
+}
+
+
+impl<'de> serde::Deserialize<'de> for diagn::Span
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        use serde::de::Error;
+
+        let location = Vec::<usize>::deserialize(deserializer)?;
+
+        let file_handle = FILE_HANDLE
+            .get()
+            .ok_or_else(|| D::Error::custom("no file to decode spans into"))?;
+
+        match location[..]
+        {
+            [] => Ok(diagn::Span::new_dummy()),
+            [start, end] => Ok(diagn::Span::new(file_handle, start, end)),
+            _ => Err(D::Error::custom("invalid span")),
+        }
+    }
+}
+
+
+/// Only the kinds of values the parser produces as literals
+#[derive(serde::Serialize, serde::Deserialize)]
+enum LiteralValue
+{
+    Integer(bool, String, Option<usize>),
+    Bool(bool, bool),
+    String(bool, String),
+}
+
+
+impl serde::Serialize for expr::Value
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer
+    {
+        use serde::ser::Error;
+
+        let metadata = self.get_metadata();
+
+        if metadata.symbol_ref.is_some() ||
+            metadata.bank_ref.is_some()
+        {
+            return Err(S::Error::custom("value refers to a definition"));
+        }
+
+        let literal = match self
+        {
+            expr::Value::Integer(_, bigint) =>
+                LiteralValue::Integer(
+                    metadata.is_guess,
+                    bigint.to_str_radix(16),
+                    bigint.size),
+
+            expr::Value::Bool(_, value) =>
+                LiteralValue::Bool(metadata.is_guess, *value),
+
+            expr::Value::String(_, value) =>
+                LiteralValue::String(metadata.is_guess, value.clone()),
+
+            _ => return Err(S::Error::custom("value is not a literal")),
+        };
+
+        literal.serialize(serializer)
+    }
+}
+
+
+impl<'de> serde::Deserialize<'de> for expr::Value
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        use serde::de::Error;
+
+        let make_metadata = |is_guess| expr::ValueMetadata {
+            is_guess,
+            symbol_ref: None,
+            bank_ref: None,
+        };
+
+        match LiteralValue::deserialize(deserializer)?
+        {
+            LiteralValue::Integer(is_guess, digits, size) =>
+            {
+                let bigint = num_bigint::BigInt::parse_bytes(
+                    digits.as_bytes(),
+                    16)
+                    .ok_or_else(|| D::Error::custom("invalid integer"))?;
+
+                Ok(expr::Value::Integer(
+                    make_metadata(is_guess),
+                    util::BigInt::new(bigint, size)))
+            }
+
+            LiteralValue::Bool(is_guess, value) =>
+                Ok(expr::Value::Bool(make_metadata(is_guess), value)),
+
+            LiteralValue::String(is_guess, value) =>
+                Ok(expr::Value::String(make_metadata(is_guess), value)),
+        }
+    }
+}
@@ -0,0 +1,605 @@
+use crate::*;
+
+
+mod ast_codec;
+pub use ast_codec::{
+    encode_ast,
+    decode_ast,
+};
+
+
+const CACHE_FORMAT_VERSION: usize = 3;
+
+
+/// How many files to keep in the cache directory,
+/// removing the least recently used ones past that
+pub const CACHE_FILES_MAX: usize = 256;
+
+
+/// How many instructions to keep matcher results for,
+/// dropping the ones not used recently past that
+const CACHED_INSTRUCTIONS_MAX: usize = 1 << 16;
+
+
+/// Keeps work that can be reused when assembling sources
+/// that have mostly not changed since the last time.
+///
+/// Parsed files are keyed by a hash of their contents.
+/// The rules matched by each instruction are keyed by a hash
+/// of all ruledef patterns and by the instruction's text,
+/// and each entry also keeps a hash of the rule it points to,
+/// so stale entries are matched again from scratch.
+/// Both can be persisted to a directory with `save`,
+/// to be loaded again by later runs of the same build,
+/// as every file name starts with a hash of the version.
+///
+/// Set it in `AssemblyOptions::cache` to use it, and keep
+/// the same options around to reuse it across assemblies.
+pub struct AssemblyCache
+{
+    dir: Option<std::path::PathBuf>,
+    parsed_files: std::collections::HashMap<ParsedFileKey, asm::AstTopLevel>,
+    parsed_files_unsaved: Vec<ParsedFileKey>,
+    ruledefs_hash: Option<u64>,
+    instr_rules: std::collections::HashMap<String, Vec<CachedRule>>,
+    instr_rules_used: std::collections::HashSet<String>,
+    instr_rules_changed: bool,
+}
+
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ParsedFileKey
+{
+    filename: String,
+    file_handle: util::FileServerHandle,
+    src_hash: u64,
+    src_len: usize,
+    use_legacy_behavior: bool,
+}
+
+
+/// The ruledef and rule indices, and the hash of the rule's pattern
+type CachedRule = (usize, usize, u64);
+
+
+impl AssemblyCache
+{
+    pub fn new() -> AssemblyCache
+    {
+        AssemblyCache {
+            dir: None,
+            parsed_files: std::collections::HashMap::new(),
+            parsed_files_unsaved: Vec::new(),
+            ruledefs_hash: None,
+            instr_rules: std::collections::HashMap::new(),
+            instr_rules_used: std::collections::HashSet::new(),
+            instr_rules_changed: false,
+        }
+    }
+
+
+    /// Creates a cache that persists parsed files and
+    /// matcher results to files in the given directory.
+    pub fn new_with_dir<P>(dir: P) -> AssemblyCache
+        where P: Into<std::path::PathBuf>
+    {
+        AssemblyCache {
+            dir: Some(dir.into()),
+            ..AssemblyCache::new()
+        }
+    }
+
+
+    /// Gets the AST of a file with the same contents,
+    /// loading it from the cache directory if needed.
+    pub fn get_parsed_file(
+        &mut self,
+        opts: &asm::AssemblyOptions,
+        filename: &str,
+        file_handle: util::FileServerHandle,
+        src: &str)
+        -> Option<asm::AstTopLevel>
+    {
+        let key = make_parsed_file_key(opts, filename, file_handle, src);
+
+        if let Some(ast) = self.parsed_files.get(&key)
+        {
+            return Some(ast.clone());
+        }
+
+        let ast = self.load_parsed_file(&key)?;
+
+        // Drop older versions of the same file
+        self.parsed_files.retain(|k, _| k.filename != filename);
+        self.parsed_files.insert(key, ast.clone());
+
+        Some(ast)
+    }
+
+
+    pub fn insert_parsed_file(
+        &mut self,
+        opts: &asm::AssemblyOptions,
+        filename: &str,
+        file_handle: util::FileServerHandle,
+        src: &str,
+        ast: &asm::AstTopLevel)
+    {
+        let key = make_parsed_file_key(opts, filename, file_handle, src);
+
+        // Drop older versions of the same file
+        self.parsed_files.retain(|k, _| k.filename != filename);
+        self.parsed_files.insert(key.clone(), ast.clone());
+
+        if self.dir.is_some()
+        {
+            self.parsed_files_unsaved.push(key);
+        }
+    }
+
+
+    /// Switches to the matcher results for the given ruledefs,
+    /// loading them from the cache directory if needed.
+    pub fn set_ruledefs(
+        &mut self,
+        defs: &asm::ItemDefs)
+    {
+        let hash = hash_ruledefs(defs);
+
+        if self.ruledefs_hash == Some(hash)
+        {
+            return;
+        }
+
+        self.ruledefs_hash = Some(hash);
+        self.instr_rules_used.clear();
+        self.instr_rules_changed = false;
+        self.instr_rules = self.load_instr_rules(hash)
+            .unwrap_or_default();
+    }
+
+
+    /// Gets the rules that matched the same instruction text
+    /// before, or `None` if any of them has been removed
+    /// or changed since.
+    pub fn get_instr_rules(
+        &mut self,
+        defs: &asm::ItemDefs,
+        src: &str)
+        -> Option<Vec<(util::ItemRef<asm::Ruledef>, util::ItemRef<asm::Rule>)>>
+    {
+        self.ruledefs_hash?;
+
+        let rules = self.instr_rules.get(src)?;
+
+        let result = rules
+            .iter()
+            .map(|&(ruledef_index, rule_index, rule_hash)|
+            {
+                let ruledef_ref = util::ItemRef::new(ruledef_index);
+                let rule_ref = util::ItemRef::new(rule_index);
+
+                match hash_rule(defs, ruledef_ref, rule_ref)
+                {
+                    Some(hash) if hash == rule_hash =>
+                        Some((ruledef_ref, rule_ref)),
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        self.instr_rules_used.insert(src.to_string());
+        Some(result)
+    }
+
+
+    pub fn insert_instr_rules(
+        &mut self,
+        defs: &asm::ItemDefs,
+        src: &str,
+        matches: &asm::InstructionMatches)
+    {
+        if self.ruledefs_hash.is_none()
+        {
+            return;
+        }
+
+        let rules = matches
+            .iter()
+            .map(|m| (
+                m.ruledef_ref.0,
+                m.rule_ref.0,
+                hash_rule(defs, m.ruledef_ref, m.rule_ref).unwrap()))
+            .collect();
+
+        if self.instr_rules.len() >= CACHED_INSTRUCTIONS_MAX
+        {
+            self.drop_unused_instr_rules();
+        }
+
+        self.instr_rules.insert(src.to_string(), rules);
+        self.instr_rules_used.insert(src.to_string());
+        self.instr_rules_changed = true;
+    }
+
+
+    /// Keeps only the matcher results used since they were
+    /// loaded or last dropped, or none if that's still too many.
+    fn drop_unused_instr_rules(&mut self)
+    {
+        let used = std::mem::take(&mut self.instr_rules_used);
+
+        if used.len() >= CACHED_INSTRUCTIONS_MAX
+        {
+            self.instr_rules.clear();
+        }
+        else
+        {
+            self.instr_rules.retain(|src, _| used.contains(src));
+        }
+
+        self.instr_rules_changed = true;
+    }
+
+
+    /// Writes new parsed files and matcher results
+    /// to the cache directory, if any, and then removes
+    /// files from other builds and the least recently used
+    /// ones over `CACHE_FILES_MAX`.
+    pub fn save(
+        &mut self,
+        report: &mut diagn::Report)
+        -> Result<(), ()>
+    {
+        let Some(dir) = &self.dir
+            else { return Ok(()) };
+
+        let mut files = Vec::new();
+
+        for key in self.parsed_files_unsaved.drain(..)
+        {
+            let Some(ast) = self.parsed_files.get(&key)
+                else { continue };
+
+            // Left to be parsed again if it can't be persisted
+            let Some(json_ast) = encode_ast(ast, key.file_handle)
+                else { continue };
+
+            let json = serde_json::json!({
+                "version": CACHE_FORMAT_VERSION,
+                "src_len": key.src_len,
+                "ast": json_ast,
+            });
+
+            files.push((get_parsed_file_filename(dir, &key), json));
+        }
+
+        if let (Some(hash), true) = (self.ruledefs_hash, self.instr_rules_changed)
+        {
+            let entries = self.instr_rules
+                .iter()
+                .map(|(src, rules)| (
+                    src.clone(),
+                    serde_json::json!(rules)))
+                .collect::<serde_json::Map<_, _>>();
+
+            let json = serde_json::json!({
+                "version": CACHE_FORMAT_VERSION,
+                "instructions": entries,
+            });
+
+            files.push((get_instr_rules_filename(dir, hash), json));
+        }
+
+        for (filename, json) in files
+        {
+            let result = std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&filename, json.to_string()));
+
+            if let Err(err) = result
+            {
+                report.error(
+                    format!(
+                        "could not write cache file `{}`: {}",
+                        filename.to_string_lossy(),
+                        err));
+
+                return Err(());
+            }
+        }
+
+        self.instr_rules_changed = false;
+
+        evict_cache_files(dir);
+        Ok(())
+    }
+
+
+    /// Loads a parsed file from the cache directory.
+    /// Missing or unreadable files are just parsed again.
+    fn load_parsed_file(
+        &self,
+        key: &ParsedFileKey)
+        -> Option<asm::AstTopLevel>
+    {
+        let dir = self.dir.as_ref()?;
+
+        let json = read_cache_file(
+            &get_parsed_file_filename(dir, key))?;
+
+        if json["src_len"].as_u64()? != key.src_len as u64
+        {
+            return None;
+        }
+
+        decode_ast(&json["ast"], key.file_handle)
+    }
+
+
+    /// Loads matcher results from the cache directory.
+    /// Missing or unreadable files just start a fresh cache.
+    fn load_instr_rules(
+        &self,
+        hash: u64)
+        -> Option<std::collections::HashMap<String, Vec<CachedRule>>>
+    {
+        let dir = self.dir.as_ref()?;
+
+        let json = read_cache_file(
+            &get_instr_rules_filename(dir, hash))?;
+
+        let mut result = std::collections::HashMap::new();
+
+        for (src, rules) in json["instructions"].as_object()?
+        {
+            let rules = rules
+                .as_array()?
+                .iter()
+                .map(|r| Some((
+                    r[0].as_u64()? as usize,
+                    r[1].as_u64()? as usize,
+                    r[2].as_u64()?)))
+                .collect::<Option<Vec<_>>>()?;
+
+            result.insert(src.clone(), rules);
+        }
+
+        Some(result)
+    }
+}
+
+
+/// Reads a JSON file from the cache directory,
+/// if it was written with the current format.
+fn read_cache_file(
+    filename: &std::path::Path)
+    -> Option<serde_json::Value>
+{
+    let contents = std::fs::read_to_string(filename).ok()?;
+
+    let json: serde_json::Value = serde_json::from_str(&contents).ok()?;
+
+    if json["version"].as_u64()? != CACHE_FORMAT_VERSION as u64
+    {
+        return None;
+    }
+
+    // Mark as recently used, to be kept by `evict_cache_files`
+    let _ = std::fs::File::options()
+        .append(true)
+        .open(filename)
+        .and_then(|f| f.set_modified(std::time::SystemTime::now()));
+
+    Some(json)
+}
+
+
+/// Removes the files written by other builds, which are never
+/// loaded, and then the least recently used files past
+/// `CACHE_FILES_MAX`. Files that can't be removed are left
+/// alone, since they only take up space.
+fn evict_cache_files(dir: &std::path::Path)
+{
+    let Ok(entries) = std::fs::read_dir(dir)
+        else { return };
+
+    let build_tag = format!("-{:016x}-", get_build_hash());
+
+    let mut files = Vec::new();
+
+    for entry in entries.flatten()
+    {
+        let filename = entry.file_name().to_string_lossy().into_owned();
+
+        let is_cache_file =
+            (filename.starts_with("ast-") || filename.starts_with("matches-")) &&
+            filename.ends_with(".json");
+
+        if !is_cache_file
+        {
+            continue;
+        }
+
+        if !filename.contains(&build_tag)
+        {
+            let _ = std::fs::remove_file(entry.path());
+            continue;
+        }
+
+        let modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .unwrap_or(std::time::UNIX_EPOCH);
+
+        files.push((modified, entry.path()));
+    }
+
+    if files.len() <= CACHE_FILES_MAX
+    {
+        return;
+    }
+
+    files.sort();
+
+    for (_, path) in &files[..files.len() - CACHE_FILES_MAX]
+    {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+
+fn make_parsed_file_key(
+    opts: &asm::AssemblyOptions,
+    filename: &str,
+    file_handle: util::FileServerHandle,
+    src: &str)
+    -> ParsedFileKey
+{
+    ParsedFileKey {
+        filename: filename.to_string(),
+        file_handle,
+        src_hash: hash_str(src),
+        src_len: src.len(),
+        use_legacy_behavior: opts.use_legacy_behavior,
+    }
+}
+
+
+fn get_parsed_file_filename(
+    dir: &std::path::Path,
+    key: &ParsedFileKey)
+    -> std::path::PathBuf
+{
+    dir.join(format!(
+        "ast-{:016x}-{:016x}{}.json",
+        get_build_hash(),
+        key.src_hash,
+        if key.use_legacy_behavior { "-legacy" } else { "" }))
+}
+
+
+fn get_instr_rules_filename(
+    dir: &std::path::Path,
+    hash: u64)
+    -> std::path::PathBuf
+{
+    dir.join(format!(
+        "matches-{:016x}-{:016x}.json",
+        get_build_hash(),
+        hash))
+}
+
+
+/// Identifies the build of customasm that wrote a cache file,
+/// since both the AST and the matcher can change between builds
+/// without `CACHE_FORMAT_VERSION` being bumped.
+fn get_build_hash() -> u64
+{
+    hash_str(&format!(
+        "{} {} {}",
+        CACHE_FORMAT_VERSION,
+        env!("CUSTOMASM_VERSION"),
+        option_env!("CUSTOMASM_COMMIT_HASH").unwrap_or("")))
+}
+
+
+/// Hashes with 64-bit FNV-1a, which, unlike the standard
+/// library's hashers, is guaranteed to give the same results
+/// across Rust versions, as needed for the persisted keys.
+fn hash_str(s: &str) -> u64
+{
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in s.bytes()
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+
+/// Hashes the patterns of every ruledef, in declaration order,
+/// which is all that instruction matching depends on.
+/// Positions in the source files are left out, so that
+/// edits elsewhere don't invalidate the cache.
+fn hash_ruledefs(defs: &asm::ItemDefs) -> u64
+{
+    let mut text = String::new();
+
+    for index in 0..defs.ruledefs.len()
+    {
+        let ruledef_ref = util::ItemRef::new(index);
+
+        if !defs.ruledefs.is_defined(ruledef_ref)
+        {
+            continue;
+        }
+
+        let ruledef = defs.ruledefs.get(ruledef_ref);
+
+        text.push_str(&format!(
+            "#{}ruledef {}\n",
+            if ruledef.is_subruledef { "sub" } else { "" },
+            index));
+
+        for rule in &ruledef.rules
+        {
+            text.push_str(&format_rule_pattern(rule));
+            text.push('\n');
+        }
+    }
+
+    hash_str(&text)
+}
+
+
+/// Hashes the pattern of a single rule, or gives `None`
+/// if there's no such rule anymore.
+fn hash_rule(
+    defs: &asm::ItemDefs,
+    ruledef_ref: util::ItemRef<asm::Ruledef>,
+    rule_ref: util::ItemRef<asm::Rule>)
+    -> Option<u64>
+{
+    if !defs.ruledefs.is_defined(ruledef_ref)
+    {
+        return None;
+    }
+
+    let ruledef = defs.ruledefs.get(ruledef_ref);
+    let rule = ruledef.rules.get(rule_ref.0)?;
+
+    Some(hash_str(&format!(
+        "#{}ruledef {}\n{}",
+        if ruledef.is_subruledef { "sub" } else { "" },
+        ruledef_ref.0,
+        format_rule_pattern(rule))))
+}
+
+
+fn format_rule_pattern(rule: &asm::Rule) -> String
+{
+    let mut text = String::new();
+
+    for part in &rule.pattern
+    {
+        match part
+        {
+            asm::RulePatternPart::Whitespace =>
+                text.push(' '),
+
+            asm::RulePatternPart::Exact(c) =>
+                text.push_str(&format!("{:?}", c)),
+
+            asm::RulePatternPart::ParameterIndex(index) =>
+                text.push_str(&format!(
+                    "{{{}:{:?}}}",
+                    index,
+                    rule.parameters[*index].typ)),
+        }
+    }
+
+    text
+}
@@ -3,6 +3,7 @@ use crate::*;
 
 pub fn collect(
     report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
     ast: &mut asm::AstTopLevel,
     decls: &mut asm::ItemDecls)
     -> Result<(), ()>
@@ -17,6 +18,32 @@ pub fn collect(
             continue;
         }
 
+        // In object files, unknown banks are sections
+        // to be placed by the linker
+        if opts.allow_external_symbols
+        {
+            let maybe_item_ref = decls.bankdefs.try_get_by_name(
+                &util::SymbolContext::new_global(),
+                0,
+                &vec![node.name.clone()]);
+
+            if maybe_item_ref.is_none()
+            {
+                let item_ref = decls.bankdefs.declare(
+                    report,
+                    node.name_span,
+                    opts,
+                    &util::SymbolContext::new_global(),
+                    None,
+                    node.name.clone(),
+                    0,
+                    util::SymbolKind::Other)?;
+
+                node.item_ref = Some(item_ref);
+                continue;
+            }
+        }
+
         let item_ref = decls.bankdefs.get_by_name_global(
             report,
             node.name_span,
@@ -8,10 +8,32 @@ pub fn collect(
     decls: &mut asm::ItemDecls)
     -> Result<(), ()>
 {
+    let mut namespace_ctx_stack = vec![util::SymbolContext::new_global()];
+
     for any_node in &mut ast.nodes
     {
-        let asm::AstAny::DirectiveFn(node) = any_node
-            else { continue };
+        let node = match any_node
+        {
+            asm::AstAny::DirectiveNamespace(ast_namespace) =>
+            {
+                namespace_ctx_stack.push(decls.symbols
+                    .get(ast_namespace.item_ref.unwrap())
+                    .ctx
+                    .clone());
+
+                continue;
+            }
+
+            asm::AstAny::DirectiveNamespaceEnd(_) =>
+            {
+                namespace_ctx_stack.pop();
+                continue;
+            }
+
+            asm::AstAny::DirectiveFn(node) => node,
+
+            _ => continue,
+        };
 
         if node.item_ref.is_some()
         {
@@ -23,7 +45,7 @@ pub fn collect(
             report,
             node.name_span,
             opts,
-            &util::SymbolContext::new_global(),
+            namespace_ctx_stack.last().unwrap(),
             None,
             node.name.clone(),
             0,
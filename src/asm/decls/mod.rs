@@ -4,6 +4,7 @@ use crate::*;
 mod bank;
 mod bankdef;
 mod ruledef;
+mod section;
 mod symbol;
 mod function;
 
@@ -14,6 +15,8 @@ pub struct ItemDecls
     pub bankdefs: util::SymbolManager<asm::Bankdef>,
     pub ruledefs: util::SymbolManager<asm::Ruledef>,
     pub symbols: util::SymbolManager<asm::Symbol>,
+    /// Anonymous labels, in the order they appear in the source
+    pub anonymous_labels: Vec<util::ItemRef<asm::Symbol>>,
 }
 
 
@@ -26,6 +29,7 @@ pub fn init(
         bankdefs: util::SymbolManager::new("bank"),
         ruledefs: util::SymbolManager::new("ruledef"),
         symbols: util::SymbolManager::new("symbol"),
+        anonymous_labels: Vec::new(),
     };
 
     let initial_item_ref = decls.bankdefs.declare(
@@ -52,7 +56,8 @@ pub fn collect(
     -> Result<(), ()>
 {
     bankdef::collect(report, opts, ast, decls)?;
-    bank::collect(report, ast, decls)?;
+    section::collect(report, opts, ast, decls)?;
+    bank::collect(report, opts, ast, decls)?;
     ruledef::collect(report, opts, ast, decls)?;
     symbol::collect(report, opts, ast, decls)?;
     function::collect(report, opts, ast, decls)?;
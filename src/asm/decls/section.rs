@@ -0,0 +1,59 @@
+use crate::*;
+
+
+pub fn collect(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    ast: &mut asm::AstTopLevel,
+    decls: &mut asm::ItemDecls)
+    -> Result<(), ()>
+{
+    // Sections share a namespace with banks, and
+    // every `#section` with the same name continues
+    // where the previous one left off
+    let mut sections = std::collections::HashMap::<String, util::ItemRef<asm::Bankdef>>::new();
+
+    for any_node in &ast.nodes
+    {
+        if let asm::AstAny::DirectiveSection(node) = any_node
+        {
+            if let Some(item_ref) = node.item_ref
+            {
+                sections.insert(node.name.clone(), item_ref);
+            }
+        }
+    }
+
+    for any_node in &mut ast.nodes
+    {
+        let asm::AstAny::DirectiveSection(node) = any_node
+            else { continue };
+
+        if node.item_ref.is_some()
+        {
+            continue;
+        }
+
+        if let Some(item_ref) = sections.get(&node.name)
+        {
+            node.item_ref = Some(*item_ref);
+            continue;
+        }
+        
+        let item_ref = decls.bankdefs.declare(
+            report,
+            node.name_span,
+            opts,
+            &util::SymbolContext::new_global(),
+            None,
+            node.name.clone(),
+            0,
+            util::SymbolKind::Other)?;
+            
+        node.item_ref = Some(item_ref);
+        sections.insert(node.name.clone(), item_ref);
+    }
+
+
+    Ok(())
+}
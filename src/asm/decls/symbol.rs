@@ -9,9 +9,14 @@ pub fn collect(
     -> Result<(), ()>
 {
     let mut symbol_ctx = util::SymbolContext::new_global();
+    let mut namespace_ctx_stack = Vec::new();
 
     let mut bank_ref = None;
 
+    // Rebuilt on every pass, since `#if` and `#repeat` may
+    // have spliced new labels in between the previous ones
+    decls.anonymous_labels.clear();
+
 
     for any_node in &mut ast.nodes
     {
@@ -27,6 +32,42 @@ pub fn collect(
                 bank_ref = Some(ast_bankdef.item_ref.unwrap());
             }
 
+            asm::AstAny::DirectiveSection(ast_section) =>
+            {
+                bank_ref = Some(ast_section.item_ref.unwrap());
+            }
+
+            asm::AstAny::DirectiveNamespace(node) =>
+            {
+                if node.item_ref.is_none()
+                {
+                    let item_ref = decls.symbols.declare(
+                        report,
+                        node.name_span,
+                        opts,
+                        &symbol_ctx,
+                        None,
+                        node.name.clone(),
+                        0,
+                        util::SymbolKind::Namespace)?;
+
+                    node.item_ref = Some(item_ref);
+                }
+
+                let namespace_ctx = decls.symbols
+                    .get(node.item_ref.unwrap())
+                    .ctx
+                    .clone();
+
+                namespace_ctx_stack.push(
+                    std::mem::replace(&mut symbol_ctx, namespace_ctx));
+            }
+
+            asm::AstAny::DirectiveNamespaceEnd(_) =>
+            {
+                symbol_ctx = namespace_ctx_stack.pop().unwrap();
+            }
+
             asm::AstAny::Symbol(node) =>
             {
                 if node.item_ref.is_none()
@@ -41,23 +82,38 @@ pub fn collect(
                         }
                     };
 
+                    let name = {
+                        if node.is_anonymous
+                            { decls.symbols.generate_anonymous_name() }
+                        else
+                            { node.name.clone() }
+                    };
+
                     let item_ref = decls.symbols.declare(
                         report,
                         node.decl_span,
                         opts,
                         &symbol_ctx,
                         bank_ref,
-                        node.name.clone(),
+                        name,
                         node.hierarchy_level,
                         kind)?;
                         
                     node.item_ref = Some(item_ref);
                 }
 
-                symbol_ctx = decls.symbols
-                    .get(node.item_ref.unwrap())
-                    .ctx
-                    .clone();
+                if node.is_anonymous
+                {
+                    decls.anonymous_labels.push(node.item_ref.unwrap());
+                }
+
+                if !node.keeps_ctx
+                {
+                    symbol_ctx = decls.symbols
+                        .get(node.item_ref.unwrap())
+                        .ctx
+                        .clone();
+                }
             }
 
             _ => {}
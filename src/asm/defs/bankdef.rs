@@ -57,6 +57,7 @@ pub fn define(
                         opts,
                         decls,
                         defs,
+                        None,
                         expr)?
                     .expect_usize(report, expr.span())?,
             };
@@ -70,6 +71,7 @@ pub fn define(
                         opts,
                         decls,
                         defs,
+                        None,
                         expr)?
                     .expect_usize(report, expr.span())?),
             };
@@ -83,6 +85,7 @@ pub fn define(
                         opts,
                         decls,
                         defs,
+                        None,
                         expr)?
                     .expect_bigint(report, expr.span())?
                     .clone(),
@@ -97,6 +100,7 @@ pub fn define(
                         opts,
                         decls,
                         defs,
+                        None,
                         expr)?
                     .expect_usize(report, expr.span())?),
             };
@@ -110,6 +114,7 @@ pub fn define(
                         opts,
                         decls,
                         defs,
+                        None,
                         expr)?
                     .expect_bigint(report, expr.span())?
                     .clone()),
@@ -172,6 +177,7 @@ pub fn define(
                         opts,
                         decls,
                         defs,
+                        None,
                         expr)?
                     .expect_usize(report, expr.span())?),
             };
@@ -185,6 +191,7 @@ pub fn define(
                     opts,
                     decls,
                     defs,
+                    None,
                     expr)?,
             };
 
@@ -8,6 +8,38 @@ pub struct Instruction
     pub encoding_size_guess: Option<usize>,
     pub encoding: expr::Value,
     pub resolved: bool,
+    /// Invocation sites of the macros this instruction
+    /// was expanded from, outermost first
+    pub macro_expansions: Vec<asm::AstMacroExpansion>,
+}
+
+
+impl Instruction
+{
+    /// Returns the match that produced the final encoding.
+    pub fn get_chosen_match(&self) -> Option<&asm::InstructionMatch>
+    {
+        let maybe_size = {
+            match self.encoding
+            {
+                expr::Value::Integer(_, ref bigint) => bigint.size,
+                _ => None,
+            }
+        };
+
+        self.matches
+            .iter()
+            .find(|m| {
+                match m.encoding
+                {
+                    asm::InstructionMatchResolution::Resolved(
+                        expr::Value::Integer(_, ref bigint)) =>
+                        maybe_size.is_none() || bigint.size == maybe_size,
+                    _ => false,
+                }
+            })
+            .or(self.matches.first())
+    }
 }
 
 
@@ -29,6 +61,7 @@ pub fn define(
                 encoding_size_guess: None,
                 encoding: expr::Value::make_unknown(),
                 resolved: false,
+                macro_expansions: ast_instr.macro_expansions.clone(),
             };
             
             defs.instructions.define(item_ref, instr);
@@ -4,6 +4,9 @@ use crate::*;
 mod bankdef;
 pub use bankdef::Bankdef;
 
+mod section;
+pub use section::Section;
+
 mod ruledef;
 pub use ruledef::{
     Ruledef,
@@ -50,6 +53,11 @@ pub struct ItemDefs
 {
     pub symbols: DefList<Symbol>,
     pub bankdefs: DefList<Bankdef>,
+    /// Sections in the order given by `#layout`s
+    pub sections: Vec<Section>,
+    /// Pairs of (referencing bank, referenced bank) found while
+    /// evaluating symbols, used to drop unreferenced sections
+    pub bank_references: std::sync::Mutex<std::collections::HashSet<(usize, usize)>>,
     pub ruledefs: DefList<Ruledef>,
     pub ruledef_map: RuledefMap,
     pub functions: DefList<Function>,
@@ -109,6 +117,13 @@ impl<T> DefList<T>
     }
 
 
+    pub fn is_defined(&self, item_ref: util::ItemRef<T>) -> bool
+    {
+        item_ref.0 < self.defs.len() &&
+            self.defs[item_ref.0].is_some()
+    }
+
+
     pub fn maybe_get(&self, item_ref: util::ItemRef<T>) -> Option<&T>
     {
         if item_ref.0 >= self.defs.len()
@@ -134,6 +149,8 @@ pub fn init() -> ItemDefs
     ItemDefs {
         symbols: DefList::new(),
         bankdefs: DefList::new(),
+        sections: Vec::new(),
+        bank_references: std::sync::Mutex::new(std::collections::HashSet::new()),
         ruledefs: DefList::new(),
         ruledef_map: RuledefMap::new(),
         functions: DefList::new(),
@@ -171,6 +188,7 @@ pub fn define_remaining(
     -> Result<(), ()>
 {
     bankdef::define(report, opts, ast, decls, defs)?;
+    section::define(report, opts, ast, decls, defs)?;
     ruledef::define(report, ast, decls, defs)?;
     function::define(report, ast, decls, defs)?;
     instruction::define(report, ast, decls, defs)?;
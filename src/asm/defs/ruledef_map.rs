@@ -129,6 +129,14 @@ impl RuledefMap
     }
 
 
+    pub fn iter_entries(&self) -> impl Iterator<Item = &RuledefMapEntry>
+    {
+        self.prefixes_to_rules
+            .values()
+            .flat_map(|entries| entries.iter())
+    }
+
+
     pub fn query_prefixed(
         &self,
         prefix: RuledefMapPrefix)
@@ -0,0 +1,272 @@
+use crate::*;
+
+
+/// A `#section` along with its placement rules
+/// from a `#layout`. Each section gets its own bankdef,
+/// whose address and output offset are assigned during
+/// resolution, as the sizes of sections become known.
+#[derive(Debug)]
+pub struct Section
+{
+    pub bank_ref: util::ItemRef<asm::Bankdef>,
+    pub span: diagn::Span,
+    /// Banks to try, in order, when placing the section
+    pub candidate_refs: Vec<util::ItemRef<asm::Bankdef>>,
+    /// Alignment of the start address, in address units
+    pub align: usize,
+    /// Whether to always place the section, even if
+    /// nothing outside of it references its contents
+    pub keep: bool,
+    /// Whether to keep `#layout` order across banks, instead of
+    /// going back to fill earlier banks with free space left
+    pub ordered: bool,
+    pub discard: bool,
+    /// Set during placement for sections dropped for not being
+    /// kept nor referenced from anything that gets placed
+    pub unreferenced: bool,
+    pub placed_in: Option<util::ItemRef<asm::Bankdef>>,
+}
+
+
+impl Section
+{
+    pub fn find(
+        defs: &asm::ItemDefs,
+        bank_ref: util::ItemRef<asm::Bankdef>)
+        -> Option<&Section>
+    {
+        defs.sections
+            .iter()
+            .find(|s| s.bank_ref == bank_ref)
+    }
+}
+
+
+pub fn define(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    ast: &asm::AstTopLevel,
+    decls: &mut asm::ItemDecls,
+    defs: &mut asm::ItemDefs)
+    -> Result<(), ()>
+{
+    let section_refs = ast.nodes
+        .iter()
+        .filter_map(|node| match node {
+            asm::AstAny::DirectiveSection(ast_section) => ast_section.item_ref,
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+
+    for any_node in &ast.nodes
+    {
+        let asm::AstAny::DirectiveLayout(node) = any_node
+            else { continue };
+
+        for entry in &node.entries
+        {
+            let bank_ref = decls.bankdefs.get_by_name_global(
+                report,
+                entry.section_name_span,
+                &entry.section_name)?;
+
+            if !section_refs.contains(&bank_ref)
+            {
+                report.error_span(
+                    format!("`{}` is not a section", entry.section_name),
+                    entry.section_name_span);
+
+                return Err(());
+            }
+
+            if Section::find(defs, bank_ref).is_some()
+            {
+                report.error_span(
+                    format!("duplicate layout for section `{}`", entry.section_name),
+                    entry.section_name_span);
+
+                return Err(());
+            }
+
+            let mut candidate_refs = Vec::new();
+
+            for ast_bank in &entry.banks
+            {
+                let candidate_ref = decls.bankdefs.get_by_name_global(
+                    report,
+                    ast_bank.name_span,
+                    &ast_bank.name)?;
+
+                if section_refs.contains(&candidate_ref)
+                {
+                    report.error_span(
+                        "cannot place a section inside another section",
+                        ast_bank.name_span);
+
+                    return Err(());
+                }
+
+                candidate_refs.push(candidate_ref);
+            }
+
+            let align = match &entry.align
+            {
+                None => 1,
+                Some(expr) =>
+                    asm::resolver::eval_certain(
+                        report,
+                        opts,
+                        decls,
+                        defs,
+                        None,
+                        expr)?
+                    .expect_usize(report, expr.span())?,
+            };
+
+            let keep = eval_bool_option(
+                report,
+                opts,
+                decls,
+                defs,
+                &entry.keep,
+                true)?;
+
+            let ordered = eval_bool_option(
+                report,
+                opts,
+                decls,
+                defs,
+                &entry.ordered,
+                false)?;
+
+            let discard = eval_bool_option(
+                report,
+                opts,
+                decls,
+                defs,
+                &entry.discard,
+                false)?;
+
+            if discard && entry.keep.is_some() && keep
+            {
+                report.error_span(
+                    "section cannot be both kept and discarded",
+                    entry.section_name_span);
+
+                return Err(());
+            }
+
+            if !discard && candidate_refs.len() == 0
+            {
+                report.error_span(
+                    "expected at least one bank for section",
+                    entry.section_name_span);
+
+                return Err(());
+            }
+
+            let addr_unit = candidate_refs
+                .first()
+                .map_or(8, |c| defs.bankdefs.get(*c).addr_unit);
+
+            defs.bankdefs.define(
+                bank_ref,
+                make_unplaced_bankdef(bank_ref, addr_unit));
+
+            defs.sections.push(Section {
+                bank_ref,
+                span: entry.section_name_span,
+                candidate_refs,
+                align,
+                keep,
+                ordered,
+                discard,
+                unreferenced: false,
+                placed_in: None,
+            });
+        }
+    }
+
+
+    // Sections and banks still undefined at this point
+    // belong to object files, and get placed by the linker
+    for any_node in &ast.nodes
+    {
+        let (item_ref, name, name_span) = match any_node
+        {
+            asm::AstAny::DirectiveSection(node) =>
+                (node.item_ref.unwrap(), &node.name, node.name_span),
+
+            asm::AstAny::DirectiveBank(node) =>
+                (node.item_ref.unwrap(), &node.name, node.name_span),
+
+            _ => continue,
+        };
+
+        if defs.bankdefs.is_defined(item_ref)
+        {
+            continue;
+        }
+
+        if !opts.allow_external_symbols
+        {
+            report.error_span(
+                format!("section `{}` is not placed by any `#layout`", name),
+                name_span);
+
+            return Err(());
+        }
+
+        defs.bankdefs.define(
+            item_ref,
+            make_unplaced_bankdef(item_ref, 8));
+    }
+
+
+    Ok(())
+}
+
+
+fn eval_bool_option(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    maybe_expr: &Option<expr::Expr>,
+    default: bool)
+    -> Result<bool, ()>
+{
+    match maybe_expr
+    {
+        None => Ok(default),
+        Some(expr) =>
+            asm::resolver::eval_certain(
+                report,
+                opts,
+                decls,
+                defs,
+                None,
+                expr)?
+            .expect_bool(report, expr.span()),
+    }
+}
+
+
+fn make_unplaced_bankdef(
+    item_ref: util::ItemRef<asm::Bankdef>,
+    addr_unit: usize)
+    -> asm::Bankdef
+{
+    asm::Bankdef {
+        item_ref,
+        addr_unit,
+        label_align: None,
+        addr_start: util::BigInt::new(0, None),
+        size_in_units: None,
+        size_in_bits: None,
+        output_offset: None,
+        fill: false,
+        userdata: expr::Value::make_void(),
+    }
+}
@@ -23,6 +23,12 @@ pub fn define(
 {
     for any_node in &ast.nodes
     {
+        if let asm::AstAny::DirectiveNamespace(node) = any_node
+        {
+            define_namespace(node, defs);
+            continue;
+        }
+
         let asm::AstAny::Symbol(node) = any_node
             else { continue };
 
@@ -51,4 +57,41 @@ pub fn define(
 
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Namespaces get a void value, only useful
+/// for accessing their members, except for `#struct`s,
+/// whose sized value is resolved like a constant
+fn define_namespace(
+    node: &asm::AstDirectiveNamespace,
+    defs: &mut asm::ItemDefs)
+{
+    let item_ref = node.item_ref.unwrap();
+
+    if defs.symbols.maybe_get(item_ref).is_some()
+    {
+        return;
+    }
+
+    let is_struct = matches!(
+        node.kind,
+        asm::AstNamespaceKind::Struct(_));
+
+    let value = {
+        if is_struct
+            { expr::Value::make_unknown() }
+        else
+            { expr::Value::make_void().statically_known() }
+    };
+
+    let symbol = Symbol {
+        item_ref,
+        no_emit: true,
+        value: value.with_symbol_ref(item_ref),
+        resolved: !is_struct,
+        driver_defined: false,
+        bankdef_ref: None,
+    };
+
+    defs.symbols.define(item_ref, symbol);
+}
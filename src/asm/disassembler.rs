@@ -0,0 +1,756 @@
+use crate::*;
+
+
+/// A piece of a rule's encoding, as found
+/// by inverting its output expression.
+#[derive(Clone, Debug)]
+enum EncodingField
+{
+    Const(util::BigInt),
+    Param {
+        index: usize,
+        lsb: usize,
+        size: usize,
+    },
+    Subrule {
+        index: usize,
+        ruledef_ref: util::ItemRef<asm::Ruledef>,
+        size: usize,
+    },
+}
+
+
+#[derive(Clone, Debug)]
+struct Template
+{
+    ruledef_ref: util::ItemRef<asm::Ruledef>,
+    rule_index: usize,
+
+    /// Ordered from the most-significant bit
+    fields: Vec<EncodingField>,
+    size: usize,
+    const_bits: usize,
+}
+
+
+enum TemplateState
+{
+    Pending,
+    InProgress,
+    Done(Vec<Template>),
+}
+
+
+struct Disassembler<'a>
+{
+    defs: &'a asm::ItemDefs,
+    data: &'a [u8],
+    templates: Vec<TemplateState>,
+}
+
+
+/// Decodes `data` back into instructions using the
+/// ruledefs in `defs`. Rules whose output can't be
+/// inverted are ignored, and undecodable units are
+/// emitted as `#d` directives.
+pub fn disassemble(
+    report: &mut diagn::Report,
+    defs: &asm::ItemDefs,
+    data: &[u8])
+    -> Result<String, ()>
+{
+    let mut disasm = Disassembler {
+        defs,
+        data,
+        templates: (0..defs.ruledefs.len())
+            .map(|_| TemplateState::Pending)
+            .collect(),
+    };
+
+    let mut candidates = Vec::new();
+
+    for i in 0..defs.ruledefs.len()
+    {
+        let ruledef_ref = util::ItemRef::new(i);
+
+        if defs.ruledefs.get(ruledef_ref).is_subruledef
+        {
+            continue;
+        }
+
+        disasm.compile_ruledef(ruledef_ref);
+
+        if let TemplateState::Done(templates) = &disasm.templates[i]
+        {
+            candidates.extend(templates.iter().cloned());
+        }
+    }
+
+    if candidates.len() == 0
+    {
+        report.error("no instructions available for disassembly");
+        return Err(());
+    }
+
+    candidates.sort_by(|a, b| b.const_bits.cmp(&a.const_bits));
+
+
+    // Take the addressing scheme from the first
+    // user-declared bank, if there's one
+    let bankdef = defs.bankdefs.get(util::ItemRef::new(
+        if defs.bankdefs.len() > 1 { 1 } else { 0 }));
+
+    let addr_unit = bankdef.addr_unit;
+    let total_bits = data.len() * 8;
+
+    let mut lines = Vec::new();
+    let mut pos = 0;
+
+    while pos < total_bits
+    {
+        let addr = bankdef.addr_start.checked_add(
+            report,
+            diagn::Span::new_dummy(),
+            &util::BigInt::from(pos / addr_unit))?;
+
+        let decoded = candidates
+            .iter()
+            .filter(|t| t.size > 0 && t.size <= total_bits - pos)
+            .find_map(|t| disasm.decode(t, pos).map(|text| (text, t.size)));
+
+        let (text, size) = decoded.unwrap_or_else(|| {
+            let size = addr_unit.min(total_bits - pos);
+            let value = disasm.read_bits(pos, size);
+
+            let text = {
+                if size % 4 == 0
+                {
+                    format!("#d 0x{}", format_digits(&value, 16, size / 4))
+                }
+                else
+                {
+                    format!("#d 0b{}", format_digits(&value, 2, size))
+                }
+            };
+
+            (text, size)
+        });
+
+        let bits = disasm.read_bits(pos, size);
+
+        lines.push((
+            text,
+            addr,
+            format_digits(&bits, 16, (size + 3) / 4)));
+
+        pos += size;
+    }
+
+
+    let text_width = lines
+        .iter()
+        .map(|l| l.0.len())
+        .max()
+        .unwrap_or(0);
+
+    let addr_width = lines
+        .iter()
+        .map(|l| l.1.to_str_radix(16).len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    let mut result = String::new();
+
+    for (text, addr, bits) in lines
+    {
+        result.push_str(&format!(
+            "{:<text_width$} ; 0x{}: {}\n",
+            text,
+            format_digits(&addr, 16, addr_width),
+            bits,
+            text_width = text_width));
+    }
+
+    Ok(result)
+}
+
+
+impl<'a> Disassembler<'a>
+{
+    fn compile_ruledef(
+        &mut self,
+        ruledef_ref: util::ItemRef<asm::Ruledef>)
+    {
+        if !matches!(self.templates[ruledef_ref.0], TemplateState::Pending)
+        {
+            return;
+        }
+
+        self.templates[ruledef_ref.0] = TemplateState::InProgress;
+
+        let ruledef = self.defs.ruledefs.get(ruledef_ref);
+
+        let mut templates = Vec::new();
+
+        for (rule_index, rule) in ruledef.rules.iter().enumerate()
+        {
+            let mut fields = Vec::new();
+
+            if self.compile_expr(rule, &rule.expr, &mut fields).is_none()
+            {
+                continue;
+            }
+
+            // Every parameter must be recoverable from the output
+            let all_params_used = (0..rule.parameters.len())
+                .all(|index| fields.iter().any(|f| {
+                    match f
+                    {
+                        EncodingField::Param { index: i, .. } |
+                        EncodingField::Subrule { index: i, .. } => *i == index,
+                        _ => false,
+                    }
+                }));
+
+            if !all_params_used
+            {
+                continue;
+            }
+
+            let const_bits = fields
+                .iter()
+                .map(|f| match f {
+                    EncodingField::Const(_) => f.size(),
+                    _ => 0,
+                })
+                .sum();
+
+            templates.push(Template {
+                ruledef_ref,
+                rule_index,
+                size: fields.iter().map(|f| f.size()).sum(),
+                fields,
+                const_bits,
+            });
+        }
+
+        self.templates[ruledef_ref.0] = TemplateState::Done(templates);
+    }
+
+
+    /// Returns the encoding size shared by every
+    /// decodable rule of a subruledef.
+    fn subruledef_size(
+        &mut self,
+        ruledef_ref: util::ItemRef<asm::Ruledef>)
+        -> Option<usize>
+    {
+        self.compile_ruledef(ruledef_ref);
+
+        let TemplateState::Done(templates) = &self.templates[ruledef_ref.0]
+            else { return None };
+
+        let size = templates.first()?.size;
+
+        if templates.iter().any(|t| t.size != size)
+        {
+            return None;
+        }
+
+        Some(size)
+    }
+
+
+    fn compile_expr(
+        &mut self,
+        rule: &asm::Rule,
+        expr: &expr::Expr,
+        fields: &mut Vec<EncodingField>)
+        -> Option<()>
+    {
+        match expr
+        {
+            expr::Expr::Block(_, exprs) if exprs.len() == 1 =>
+                self.compile_expr(rule, &exprs[0], fields),
+
+            expr::Expr::Literal(_, expr::Value::Integer(_, bigint)) =>
+            {
+                let size = bigint.size?;
+
+                if size == 0 || bigint.sign() < 0
+                {
+                    return None;
+                }
+
+                fields.push(EncodingField::Const(bigint.clone()));
+                Some(())
+            }
+
+            expr::Expr::Literal(_, value @ expr::Value::String(..)) =>
+                self.compile_expr(
+                    rule,
+                    &value.clone().coerce_to_integer().make_literal(),
+                    fields),
+
+            expr::Expr::BinaryOp(_, _, expr::BinaryOp::Concat, lhs, rhs) =>
+            {
+                self.compile_expr(rule, lhs, fields)?;
+                self.compile_expr(rule, rhs, fields)
+            }
+
+            expr::Expr::Variable(_, name) =>
+            {
+                let index = rule.parameters
+                    .iter()
+                    .position(|p| p.name == *name)?;
+
+                let field = {
+                    match rule.parameters[index].typ
+                    {
+                        asm::RuleParameterType::Unspecified =>
+                            return None,
+
+                        asm::RuleParameterType::Unsigned(size) |
+                        asm::RuleParameterType::Signed(size) |
+                        asm::RuleParameterType::Integer(size) =>
+                            EncodingField::Param {
+                                index,
+                                lsb: 0,
+                                size,
+                            },
+
+                        asm::RuleParameterType::RuledefRef(ruledef_ref) =>
+                            EncodingField::Subrule {
+                                index,
+                                ruledef_ref,
+                                size: self.subruledef_size(ruledef_ref)?,
+                            },
+                    }
+                };
+
+                fields.push(field);
+                Some(())
+            }
+
+            expr::Expr::Slice(_, _, left, right, inner) =>
+            {
+                let msb = eval_literal_usize(left)?;
+                let lsb = eval_literal_usize(right)?;
+                self.compile_slice(rule, inner, msb, lsb, fields)
+            }
+
+            expr::Expr::SliceShort(_, _, size, inner) =>
+            {
+                let size = eval_literal_usize(size)?;
+                self.compile_slice(rule, inner, size.checked_sub(1)?, 0, fields)
+            }
+
+            expr::Expr::Call(_, func, args) =>
+            {
+                let expr::Expr::Variable(_, ref func_name) = **func
+                    else { return None };
+
+                if func_name != "le" || args.len() != 1
+                {
+                    return None;
+                }
+
+                let mut inner_fields = Vec::new();
+                self.compile_expr(rule, &args[0], &mut inner_fields)?;
+
+                let size: usize = inner_fields.iter().map(|f| f.size()).sum();
+                if size % 8 != 0
+                {
+                    return None;
+                }
+
+                // The least-significant byte comes out first
+                for byte in 0..(size / 8)
+                {
+                    fields.extend(slice_fields(
+                        &inner_fields,
+                        byte * 8 + 7,
+                        byte * 8)?);
+                }
+
+                Some(())
+            }
+
+            _ => None,
+        }
+    }
+
+
+    fn compile_slice(
+        &mut self,
+        rule: &asm::Rule,
+        inner: &expr::Expr,
+        msb: usize,
+        lsb: usize,
+        fields: &mut Vec<EncodingField>)
+        -> Option<()>
+    {
+        if msb < lsb
+        {
+            return None;
+        }
+
+        // Plain parameters can be sliced at any bit,
+        // regardless of their declared size
+        if let expr::Expr::Variable(_, name) = inner
+        {
+            let index = rule.parameters
+                .iter()
+                .position(|p| p.name == *name)?;
+
+            // Subrules can only be taken as a whole
+            if let asm::RuleParameterType::RuledefRef(ruledef_ref) = rule.parameters[index].typ
+            {
+                let size = self.subruledef_size(ruledef_ref)?;
+
+                if lsb != 0 || msb + 1 != size
+                {
+                    return None;
+                }
+
+                fields.push(EncodingField::Subrule {
+                    index,
+                    ruledef_ref,
+                    size,
+                });
+
+                return Some(());
+            }
+
+            fields.push(EncodingField::Param {
+                index,
+                lsb,
+                size: msb + 1 - lsb,
+            });
+
+            return Some(());
+        }
+
+        let mut inner_fields = Vec::new();
+        self.compile_expr(rule, inner, &mut inner_fields)?;
+
+        fields.extend(slice_fields(&inner_fields, msb, lsb)?);
+        Some(())
+    }
+
+
+    fn read_bit(&self, index: usize) -> bool
+    {
+        (self.data[index / 8] >> (7 - index % 8)) & 1 != 0
+    }
+
+
+    fn read_bits(&self, pos: usize, size: usize) -> util::BigInt
+    {
+        let mut value = util::BigInt::from(0);
+
+        for k in 0..size
+        {
+            value.set_bit(k, self.read_bit(pos + size - 1 - k));
+        }
+
+        value
+    }
+
+
+    /// Tries to decode the given template at the given
+    /// bit position, returning the rendered instruction.
+    fn decode(
+        &self,
+        template: &Template,
+        pos: usize)
+        -> Option<String>
+    {
+        let rule = &self.defs.ruledefs
+            .get(template.ruledef_ref)
+            .rules[template.rule_index];
+
+        let mut param_bits = vec![
+            std::collections::BTreeMap::<usize, bool>::new();
+            rule.parameters.len()];
+
+        let mut subrule_texts = vec![None; rule.parameters.len()];
+
+        let mut field_pos = pos;
+
+        for field in &template.fields
+        {
+            let size = field.size();
+
+            match field
+            {
+                EncodingField::Const(value) =>
+                {
+                    for k in 0..size
+                    {
+                        if self.read_bit(field_pos + size - 1 - k) != value.get_bit(k)
+                        {
+                            return None;
+                        }
+                    }
+                }
+
+                EncodingField::Param { index, lsb, .. } =>
+                {
+                    for k in 0..size
+                    {
+                        let bit = self.read_bit(field_pos + size - 1 - k);
+                        let prev = param_bits[*index].insert(lsb + k, bit);
+
+                        if prev.is_some_and(|p| p != bit)
+                        {
+                            return None;
+                        }
+                    }
+                }
+
+                EncodingField::Subrule { index, ruledef_ref, .. } =>
+                {
+                    let text = self.decode_subrule(*ruledef_ref, field_pos)?;
+
+                    if let Some(ref prev) = subrule_texts[*index]
+                    {
+                        if *prev != text
+                        {
+                            return None;
+                        }
+                    }
+
+                    subrule_texts[*index] = Some(text);
+                }
+            }
+
+            field_pos += size;
+        }
+
+        let mut result = String::new();
+
+        for part in &rule.pattern
+        {
+            match part
+            {
+                asm::RulePatternPart::Whitespace =>
+                    result.push(' '),
+
+                asm::RulePatternPart::Exact(c) =>
+                    result.push(*c),
+
+                asm::RulePatternPart::ParameterIndex(index) =>
+                {
+                    if let Some(ref text) = subrule_texts[*index]
+                    {
+                        result.push_str(text);
+                    }
+                    else
+                    {
+                        let value = param_value(
+                            rule.parameters[*index].typ,
+                            &param_bits[*index])?;
+
+                        result.push_str(&format_value(&value));
+                    }
+                }
+            }
+        }
+
+        Some(result.trim().to_string())
+    }
+
+
+    fn decode_subrule(
+        &self,
+        ruledef_ref: util::ItemRef<asm::Ruledef>,
+        pos: usize)
+        -> Option<String>
+    {
+        let TemplateState::Done(templates) = &self.templates[ruledef_ref.0]
+            else { return None };
+
+        let mut best: Option<(&Template, String)> = None;
+
+        for template in templates
+        {
+            if best.as_ref().is_some_and(|b| b.0.const_bits >= template.const_bits)
+            {
+                continue;
+            }
+
+            if let Some(text) = self.decode(template, pos)
+            {
+                best = Some((template, text));
+            }
+        }
+
+        best.map(|b| b.1)
+    }
+}
+
+
+impl EncodingField
+{
+    fn size(&self) -> usize
+    {
+        match self
+        {
+            EncodingField::Const(value) => value.size.unwrap(),
+            EncodingField::Param { size, .. } => *size,
+            EncodingField::Subrule { size, .. } => *size,
+        }
+    }
+}
+
+
+/// Selects the bits `[msb:lsb]` out of a sequence of fields.
+/// Fails if that would split a subrule.
+fn slice_fields(
+    fields: &[EncodingField],
+    msb: usize,
+    lsb: usize)
+    -> Option<Vec<EncodingField>>
+{
+    let mut result = Vec::new();
+    let mut field_lsb: usize = fields.iter().map(|f| f.size()).sum();
+
+    if msb >= field_lsb
+    {
+        return None;
+    }
+
+    for field in fields
+    {
+        let size = field.size();
+        field_lsb -= size;
+
+        let hi = msb.min(field_lsb + size - 1);
+        let lo = lsb.max(field_lsb);
+
+        if hi < lo
+        {
+            continue;
+        }
+
+        let (hi, lo) = (hi - field_lsb, lo - field_lsb);
+
+        let sliced = {
+            match field
+            {
+                EncodingField::Const(value) =>
+                    EncodingField::Const(value.slice(hi + 1, lo)),
+
+                EncodingField::Param { index, lsb, .. } =>
+                    EncodingField::Param {
+                        index: *index,
+                        lsb: lsb + lo,
+                        size: hi + 1 - lo,
+                    },
+
+                EncodingField::Subrule { .. } =>
+                {
+                    if lo != 0 || hi + 1 != size
+                    {
+                        return None;
+                    }
+
+                    field.clone()
+                }
+            }
+        };
+
+        result.push(sliced);
+    }
+
+    Some(result)
+}
+
+
+fn eval_literal_usize(expr: &expr::Expr) -> Option<usize>
+{
+    match expr
+    {
+        expr::Expr::Literal(_, expr::Value::Integer(_, bigint)) =>
+            bigint.maybe_into::<usize>(),
+
+        _ => None,
+    }
+}
+
+
+/// Rebuilds a parameter's value from its extracted bits,
+/// checking that any bits past its declared size are
+/// consistent with it.
+fn param_value(
+    typ: asm::RuleParameterType,
+    bits: &std::collections::BTreeMap<usize, bool>)
+    -> Option<util::BigInt>
+{
+    let (size, signed) = {
+        match typ
+        {
+            asm::RuleParameterType::Unsigned(size) |
+            asm::RuleParameterType::Integer(size) => (size, false),
+            asm::RuleParameterType::Signed(size) => (size, true),
+            asm::RuleParameterType::Unspecified =>
+                (bits.keys().next_back().map_or(0, |k| k + 1), false),
+            asm::RuleParameterType::RuledefRef(_) => return None,
+        }
+    };
+
+    let sign_bit = signed &&
+        size > 0 &&
+        bits.get(&(size - 1)).copied().unwrap_or(false);
+
+    let mut value = util::BigInt::from(0);
+
+    for (&index, &bit) in bits
+    {
+        if index < size
+        {
+            value.set_bit(index, bit);
+        }
+        else if bit != sign_bit
+        {
+            return None;
+        }
+    }
+
+    if sign_bit
+    {
+        let mut offset = util::BigInt::from(0);
+        offset.set_bit(size, true);
+        value = value.maybe_sub(&offset)?;
+    }
+
+    Some(value)
+}
+
+
+fn format_value(value: &util::BigInt) -> String
+{
+    if value.sign() < 0
+    {
+        format!("-0x{}", (-value).to_str_radix(16))
+    }
+    else
+    {
+        format!("0x{}", value.to_str_radix(16))
+    }
+}
+
+
+fn format_digits(
+    value: &util::BigInt,
+    radix: usize,
+    min_digits: usize)
+    -> String
+{
+    format!(
+        "{:0>width$}",
+        value.to_str_radix(radix),
+        width = min_digits)
+}
@@ -0,0 +1,1062 @@
+use crate::*;
+
+
+/// Where the linker placed a chunk of an object.
+#[derive(Copy, Clone)]
+struct Placement
+{
+    bank_ref: util::ItemRef<asm::Bankdef>,
+    position: usize,
+    output_position: Option<usize>,
+}
+
+
+/// What the linker needs to know about each object
+/// once its chunks are placed and its symbols declared.
+struct LinkedObject
+{
+    placements: Vec<Placement>,
+    ruledef_refs: Vec<util::ItemRef<asm::Ruledef>>,
+    /// Linker names for the object's anonymous labels
+    anonymous_names: std::collections::HashMap<String, String>,
+}
+
+
+/// Links object files produced by `asm::format_object`,
+/// along with layout source files that declare the `#bankdef`s
+/// and `#layout`s to place them into.
+///
+/// The chunks of every object are placed, in the order the
+/// files were given, after the contents of the layout files.
+/// Definitions shared between objects, like the rules of a CPU,
+/// are kept only once. Once placed, the objects' symbols get
+/// their final values, and every relocation is evaluated and
+/// patched into the output.
+pub fn link<S>(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    filenames: &[S])
+    -> asm::AssemblyResult
+    where S: std::borrow::Borrow<str>
+{
+    let mut assembly = asm::AssemblyResult::new();
+
+    let mut run = || -> Result<(), ()>
+    {
+        let mut ast = asm::AstTopLevel {
+            nodes: Vec::new(),
+        };
+
+        let mut objects = Vec::new();
+        let mut once_filenames = std::collections::HashSet::new();
+
+        for filename in filenames
+        {
+            let filename = filename.borrow();
+
+            let file_handle = fileserver.get_handle(
+                report,
+                None,
+                filename)?;
+
+            let src = fileserver.get_str(
+                report,
+                None,
+                file_handle)?;
+
+            if src.starts_with(asm::object::OBJECT_HEADER)
+            {
+                objects.push(asm::object::read_object(
+                    report,
+                    opts,
+                    file_handle,
+                    &src)?);
+
+                continue;
+            }
+
+            let layout_ast = asm::parser::parse_and_resolve_includes(
+                report,
+                None,
+                opts,
+                fileserver,
+                filename,
+                &mut Vec::new(),
+                &mut once_filenames)?;
+
+            check_layout_options(report, &layout_ast)?;
+
+            ast.nodes.extend(layout_ast.nodes);
+        }
+
+        let default_bank_node = get_default_bank_node(&ast);
+
+        // Keep one copy of each definition, and remember
+        // which ruledef each object refers to by index
+        let mut seen_definitions = std::collections::HashMap::<String, Vec<diagn::Span>>::new();
+        let mut object_ruledef_spans = Vec::new();
+
+        for object in &mut objects
+        {
+            let mut ruledef_spans = Vec::new();
+
+            for definition in std::mem::take(&mut object.definitions)
+            {
+                if let Some(spans) = seen_definitions.get(&definition.src)
+                {
+                    ruledef_spans.extend_from_slice(spans);
+                    continue;
+                }
+
+                let spans = definition.ast.nodes
+                    .iter()
+                    .filter_map(|node| match node {
+                        asm::AstAny::DirectiveRuledef(ast_ruledef) =>
+                            Some(ast_ruledef.header_span),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                ruledef_spans.extend_from_slice(&spans);
+                seen_definitions.insert(definition.src, spans);
+                ast.nodes.extend(definition.ast.nodes);
+            }
+
+            object_ruledef_spans.push(ruledef_spans);
+        }
+
+        for object in &objects
+        {
+            for chunk in &object.chunks
+            {
+                push_chunk_nodes(
+                    &mut ast,
+                    &default_bank_node,
+                    chunk);
+            }
+        }
+
+        asm::parser::expand_macros(
+            report,
+            &mut ast)?;
+
+        asm::assemble_rounds(
+            report,
+            opts,
+            fileserver,
+            &mut assembly,
+            ast)?;
+
+        let ast = assembly.ast.as_ref().unwrap();
+        let decls = assembly.decls.as_mut().unwrap();
+        let defs = assembly.defs.as_mut().unwrap();
+        let output = assembly.output.as_mut().unwrap();
+
+        let mut linked = get_placements(
+            report,
+            ast,
+            decls,
+            defs,
+            &objects,
+            &object_ruledef_spans)?;
+
+        declare_symbols(
+            report,
+            opts,
+            fileserver,
+            decls,
+            defs,
+            &objects,
+            &mut linked)?;
+
+        check_externals(
+            report,
+            decls,
+            &objects)?;
+
+        resolve_constants(
+            report,
+            opts,
+            fileserver,
+            decls,
+            defs,
+            &objects,
+            &linked)?;
+
+        apply_relocations(
+            report,
+            opts,
+            fileserver,
+            decls,
+            defs,
+            output,
+            &objects,
+            &linked)
+    };
+
+    match run()
+    {
+        Ok(()) => {}
+        Err(()) =>
+        {
+            // The output was already built
+            // before the failed relocation
+            assembly.error = true;
+            assembly.output = None;
+            assert!(report.has_errors());
+        }
+    }
+
+    assembly
+}
+
+
+/// Rejects `keep` options, since object data carries no
+/// symbol references for telling which sections are used.
+fn check_layout_options(
+    report: &mut diagn::Report,
+    ast: &asm::AstTopLevel)
+    -> Result<(), ()>
+{
+    for node in &ast.nodes
+    {
+        let asm::AstAny::DirectiveLayout(ast_layout) = node
+            else { continue };
+
+        for entry in &ast_layout.entries
+        {
+            if let Some(expr) = &entry.keep
+            {
+                report.error_span(
+                    "layout option `keep` is not supported when linking objects",
+                    expr.span());
+
+                return Err(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Finds the bank that the layout files leave selected,
+/// where objects' default chunks get placed.
+fn get_default_bank_node(ast: &asm::AstTopLevel) -> asm::AstAny
+{
+    for node in ast.nodes.iter().rev()
+    {
+        match node
+        {
+            asm::AstAny::DirectiveBank(ast_bank) =>
+                return asm::AstAny::DirectiveBank(ast_bank.clone()),
+
+            asm::AstAny::DirectiveSection(ast_section) =>
+                return asm::AstAny::DirectiveSection(ast_section.clone()),
+
+            asm::AstAny::DirectiveBankdef(ast_bankdef) =>
+                return asm::AstAny::DirectiveBank(asm::AstDirectiveBank {
+                    header_span: ast_bankdef.header_span,
+                    name_span: ast_bankdef.name_span,
+                    name: ast_bankdef.name.clone(),
+                    item_ref: None,
+                }),
+
+            _ => {}
+        }
+    }
+
+    asm::AstAny::DirectiveBank(asm::AstDirectiveBank {
+        header_span: diagn::Span::new_dummy(),
+        name_span: diagn::Span::new_dummy(),
+        name: "#global_bankdef".to_string(),
+        item_ref: Some(util::ItemRef::new(0)),
+    })
+}
+
+
+/// Appends the nodes that select a chunk's bank and write its
+/// contents. The `#res 0` marks where the chunk starts,
+/// and shares its span with the chunk.
+fn push_chunk_nodes(
+    ast: &mut asm::AstTopLevel,
+    default_bank_node: &asm::AstAny,
+    chunk: &asm::object::ObjectChunk)
+{
+    let span = chunk.span;
+
+    let bank_node = match chunk.kind
+    {
+        asm::object::ObjectChunkKind::Default =>
+            default_bank_node.clone(),
+
+        asm::object::ObjectChunkKind::Bank =>
+            asm::AstAny::DirectiveBank(asm::AstDirectiveBank {
+                header_span: span,
+                name_span: span,
+                name: chunk.name.clone(),
+                item_ref: None,
+            }),
+
+        asm::object::ObjectChunkKind::Section =>
+            asm::AstAny::DirectiveSection(asm::AstDirectiveSection {
+                header_span: span,
+                name_span: span,
+                name: chunk.name.clone(),
+                item_ref: None,
+            }),
+    };
+
+    ast.nodes.push(bank_node);
+
+    if chunk.align > 1
+    {
+        ast.nodes.push(asm::AstAny::DirectiveAlign(asm::AstDirectiveAlign {
+            header_span: span,
+            expr: expr::Expr::Literal(
+                span,
+                expr::Value::make_integer(chunk.align)),
+            macro_expansions: Vec::new(),
+            item_ref: None,
+        }));
+    }
+
+    ast.nodes.push(asm::AstAny::DirectiveRes(asm::AstDirectiveRes {
+        header_span: span,
+        expr: expr::Expr::Literal(
+            span,
+            expr::Value::make_integer(0)),
+        macro_expansions: Vec::new(),
+        item_ref: None,
+    }));
+
+    if chunk.data.size.unwrap() > 0
+    {
+        ast.nodes.push(asm::AstAny::DirectiveData(asm::AstDirectiveData {
+            header_span: span,
+            elem_size: None,
+            elems: vec![expr::Expr::Literal(
+                span,
+                expr::Value::make_integer(chunk.data.clone()))],
+            macro_expansions: Vec::new(),
+            item_refs: Vec::new(),
+        }));
+    }
+}
+
+
+fn get_placements(
+    report: &mut diagn::Report,
+    ast: &asm::AstTopLevel,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    objects: &[asm::object::Object],
+    object_ruledef_spans: &[Vec<diagn::Span>])
+    -> Result<Vec<LinkedObject>, ()>
+{
+    let mut placements = std::collections::HashMap::new();
+
+    let mut iter = asm::ResolveIterator::new(
+        ast,
+        defs,
+        false,
+        true);
+
+    while let Some(ctx) = iter.next(report, decls, defs)?
+    {
+        if let asm::ResolverNode::Res(ast_res) = ctx.node
+        {
+            placements.insert(
+                ast_res.header_span,
+                Placement {
+                    bank_ref: ctx.bank_ref,
+                    position: ctx.bank_data.cur_position,
+                    output_position: ctx.get_output_position(defs),
+                });
+        }
+    }
+
+    let mut ruledef_refs = std::collections::HashMap::new();
+
+    for node in &ast.nodes
+    {
+        if let asm::AstAny::DirectiveRuledef(ast_ruledef) = node
+        {
+            ruledef_refs.insert(
+                ast_ruledef.header_span,
+                ast_ruledef.item_ref.unwrap());
+        }
+    }
+
+    let mut linked = Vec::new();
+
+    for (object, ruledef_spans) in objects.iter().zip(object_ruledef_spans)
+    {
+        linked.push(LinkedObject {
+            placements: object.chunks
+                .iter()
+                .map(|chunk| placements[&chunk.span])
+                .collect(),
+            ruledef_refs: ruledef_spans
+                .iter()
+                .map(|span| ruledef_refs[span])
+                .collect(),
+            anonymous_names: std::collections::HashMap::new(),
+        });
+    }
+
+    Ok(linked)
+}
+
+
+/// Declares the symbols of every object, giving labels
+/// their final addresses. Namespaces and constants that
+/// objects share from an included file are kept only once.
+fn declare_symbols(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &dyn util::FileServer,
+    decls: &mut asm::ItemDecls,
+    defs: &mut asm::ItemDefs,
+    objects: &[asm::object::Object],
+    linked: &mut [LinkedObject])
+    -> Result<(), ()>
+{
+    let mut constant_srcs = std::collections::HashMap::new();
+
+    for (object, linked) in objects.iter().zip(linked.iter_mut())
+    {
+        for symbol in &object.symbols
+        {
+            let mut hierarchy = symbol.hierarchy
+                .iter()
+                .map(|name| {
+                    linked.anonymous_names
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| name.clone())
+                })
+                .collect::<Vec<_>>();
+
+            let name = hierarchy.last_mut().unwrap();
+
+            if name.starts_with("$anonymous_")
+            {
+                let new_name = decls.symbols.generate_anonymous_name();
+                linked.anonymous_names.insert(name.clone(), new_name.clone());
+                *name = new_name;
+            }
+
+            let name = name.clone();
+
+            let value_src = symbol.value
+                .as_ref()
+                .map(|value| fileserver.get_excerpt(value.span()));
+
+            // Reuse namespaces and identical constants
+            // already declared by a previous object
+            let existing_ref = decls.symbols.traverse(None, &hierarchy);
+
+            if let Some(existing_ref) = existing_ref
+            {
+                let existing = decls.symbols.get(existing_ref);
+
+                let is_shared = match (existing.kind, symbol.kind)
+                {
+                    (util::SymbolKind::Namespace, util::SymbolKind::Namespace) =>
+                        symbol.value.is_none() &&
+                            matches!(defs.symbols.get(existing_ref).value, expr::Value::Void(_)),
+
+                    (util::SymbolKind::Constant, util::SymbolKind::Constant) =>
+                        symbol.site.is_none() &&
+                            value_src.is_some() &&
+                            constant_srcs.get(&existing_ref.0) == value_src.as_ref(),
+
+                    _ => false,
+                };
+
+                if is_shared
+                {
+                    continue;
+                }
+            }
+
+            let parent_ctx = match hierarchy.len()
+            {
+                1 => util::SymbolContext::new_global(),
+                len =>
+                {
+                    let Some(parent_ref) = decls.symbols.traverse(None, &hierarchy[..len - 1])
+                    else
+                    {
+                        report.error_span(
+                            "symbol declared before its parent",
+                            symbol.span);
+
+                        return Err(());
+                    };
+
+                    decls.symbols.get(parent_ref).ctx.clone()
+                }
+            };
+
+            let placement = symbol.site
+                .map(|site| (linked.placements[site.chunk_index], site.offset));
+
+            let item_ref = decls.symbols.declare(
+                report,
+                symbol.span,
+                opts,
+                &parent_ctx,
+                placement.map(|p| p.0.bank_ref),
+                name,
+                symbol.hierarchy_level,
+                symbol.kind)?;
+
+            let mut value = match symbol.kind
+            {
+                util::SymbolKind::Namespace if symbol.value.is_none() =>
+                    expr::Value::make_void(),
+                _ =>
+                    expr::Value::make_unknown(),
+            };
+
+            let mut resolved = false;
+
+            if let util::SymbolKind::Label = symbol.kind
+            {
+                let (placement, offset) = placement.unwrap();
+
+                value = make_ctx_at(placement, offset, &mut |ctx| {
+                    ctx.eval_address(
+                        report,
+                        symbol.span,
+                        defs,
+                        false)
+                })?;
+
+                value.get_mut_metadata().symbol_ref = Some(item_ref);
+                resolved = true;
+            }
+            else if let Some(value_src) = value_src
+            {
+                constant_srcs.insert(item_ref.0, value_src);
+            }
+
+            defs.symbols.define(
+                item_ref,
+                asm::Symbol {
+                    item_ref,
+                    no_emit: symbol.no_emit,
+                    value,
+                    resolved,
+                    driver_defined: false,
+                    bankdef_ref: placement.map(|p| p.0.bank_ref),
+                });
+        }
+    }
+
+    Ok(())
+}
+
+
+fn check_externals(
+    report: &mut diagn::Report,
+    decls: &asm::ItemDecls,
+    objects: &[asm::object::Object])
+    -> Result<(), ()>
+{
+    let mut had_error = false;
+
+    for object in objects
+    {
+        for external in &object.externals
+        {
+            if decls.symbols.traverse(None, &external.hierarchy).is_none()
+            {
+                report.error_span(
+                    format!(
+                        "unknown symbol `{}`",
+                        external.hierarchy.join(".")),
+                    external.span);
+
+                had_error = true;
+            }
+        }
+    }
+
+    match had_error
+    {
+        false => Ok(()),
+        true => Err(()),
+    }
+}
+
+
+/// Evaluates the objects' constants, which may refer to
+/// each other in any order, until no more can be resolved.
+fn resolve_constants(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &mut asm::ItemDefs,
+    objects: &[asm::object::Object],
+    linked: &[LinkedObject])
+    -> Result<(), ()>
+{
+    let mut pending = Vec::new();
+
+    for (object, linked) in objects.iter().zip(linked)
+    {
+        for symbol in &object.symbols
+        {
+            let Some(ref value_expr) = symbol.value
+                else { continue };
+
+            let hierarchy = symbol.hierarchy
+                .iter()
+                .map(|name| {
+                    linked.anonymous_names
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| name.clone())
+                })
+                .collect::<Vec<_>>();
+
+            let item_ref = decls.symbols.traverse(None, &hierarchy).unwrap();
+
+            if defs.symbols.get(item_ref).resolved
+            {
+                continue;
+            }
+
+            pending.push((
+                item_ref,
+                symbol.site.map(|site| (linked.placements[site.chunk_index], site.offset)),
+                rename_anonymous(value_expr.clone(), &linked.anonymous_names)));
+        }
+    }
+
+    loop
+    {
+        let prev_pending_count = pending.len();
+        let mut still_pending = Vec::new();
+
+        for (item_ref, placement, value_expr) in pending
+        {
+            let mut throwaway_report = diagn::Report::new();
+
+            let maybe_value = eval_at(
+                &mut throwaway_report,
+                opts,
+                fileserver,
+                decls,
+                defs,
+                placement,
+                &value_expr);
+
+            match maybe_value
+            {
+                Ok(mut value) if !value.is_unknown() =>
+                {
+                    value.get_mut_metadata().symbol_ref = Some(item_ref);
+
+                    let symbol = defs.symbols.get_mut(item_ref);
+                    symbol.value = value;
+                    symbol.resolved = true;
+                }
+
+                _ => still_pending.push((item_ref, placement, value_expr)),
+            }
+        }
+
+        pending = still_pending;
+
+        if pending.len() == prev_pending_count
+        {
+            break;
+        }
+    }
+
+    // Report whatever could not be resolved
+    for (_, placement, value_expr) in &pending
+    {
+        let value = eval_at(
+            report,
+            opts,
+            fileserver,
+            decls,
+            defs,
+            *placement,
+            value_expr)?;
+
+        if value.is_unknown()
+        {
+            report.error_span(
+                "cannot resolve expression",
+                value_expr.span());
+        }
+    }
+
+    match pending.len()
+    {
+        0 => Ok(()),
+        _ => Err(()),
+    }
+}
+
+
+fn apply_relocations(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &mut util::BitVec,
+    objects: &[asm::object::Object],
+    linked: &[LinkedObject])
+    -> Result<(), ()>
+{
+    let mut had_error = false;
+
+    for (object, linked) in objects.iter().zip(linked)
+    {
+        for relocation in &object.relocations
+        {
+            let placement = linked.placements[relocation.site.chunk_index];
+
+            let maybe_bigint = make_ctx_at(placement, relocation.site.offset, &mut |ctx| {
+                eval_relocation(
+                    report,
+                    opts,
+                    fileserver,
+                    decls,
+                    defs,
+                    ctx,
+                    linked,
+                    relocation)
+            });
+
+            let Ok(maybe_bigint) = maybe_bigint
+            else
+            {
+                had_error = true;
+                continue;
+            };
+
+            let Some(bigint) = maybe_bigint
+                else { continue };
+
+            if bigint.size.unwrap() != relocation.size
+            {
+                report.push_parent(
+                    "relocated value does not fit the space left for it",
+                    relocation.span);
+
+                report.note(
+                    format!(
+                        "object has {} bits, got {} bits",
+                        relocation.size,
+                        bigint.size.unwrap()));
+
+                report.pop_parent();
+
+                had_error = true;
+                continue;
+            }
+
+            // Discarded sections have no output
+            if let Some(output_position) = placement.output_position
+            {
+                output.write_bigint(
+                    output_position + relocation.site.offset,
+                    &bigint);
+            }
+        }
+    }
+
+    match had_error
+    {
+        false => Ok(()),
+        true => Err(()),
+    }
+}
+
+
+fn eval_relocation(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    ctx: &asm::ResolverContext,
+    linked: &LinkedObject,
+    relocation: &asm::object::ObjectRelocation)
+    -> Result<Option<util::BigInt>, ()>
+{
+    match relocation.kind
+    {
+        asm::object::ObjectRelocationKind::Data { elem_size, ref expr } =>
+        {
+            let expr = rename_anonymous(expr.clone(), &linked.anonymous_names);
+
+            let value = asm::resolver::eval(
+                report,
+                fileserver,
+                opts,
+                decls,
+                defs,
+                ctx,
+                &mut expr::EvalContext::new(opts),
+                &expr)?;
+
+            let elem = asm::DataElement {
+                item_ref: util::ItemRef::new(0),
+                elem_size,
+                encoding: value.coerce_to_integer(),
+                resolved: true,
+            };
+
+            let bigint = asm::resolver::check_final_data_element(
+                report,
+                expr.span(),
+                &elem)?;
+
+            Ok(Some(bigint))
+        }
+
+        asm::object::ObjectRelocationKind::Instruction(ref object_match) =>
+        {
+            let mut matches = vec![make_match(linked, object_match)];
+
+            let maybe_encodings = asm::resolver::resolve_encoding(
+                report,
+                opts,
+                relocation.span,
+                fileserver,
+                &mut matches,
+                decls,
+                defs,
+                ctx,
+                &mut expr::EvalContext::new(opts))?;
+
+            let Some(encodings) = maybe_encodings
+            else
+            {
+                if !report.has_errors()
+                {
+                    report.error_span(
+                        "failed to resolve instruction",
+                        relocation.span);
+                }
+
+                return Err(());
+            };
+
+            Ok(Some(encodings[0].1.unwrap_bigint().clone()))
+        }
+
+        asm::object::ObjectRelocationKind::Assert(ref expr) =>
+        {
+            let expr = rename_anonymous(expr.clone(), &linked.anonymous_names);
+
+            let value = asm::resolver::eval(
+                report,
+                fileserver,
+                opts,
+                decls,
+                defs,
+                ctx,
+                &mut expr::EvalContext::new(opts),
+                &expr)?;
+
+            let satisfied = value.expect_bool(
+                report,
+                expr.span())?;
+
+            if !satisfied
+            {
+                report.error_span(
+                    "assertion failed",
+                    expr.span());
+
+                return Err(());
+            }
+
+            Ok(None)
+        }
+    }
+}
+
+
+fn make_match(
+    linked: &LinkedObject,
+    object_match: &asm::object::ObjectMatch)
+    -> asm::InstructionMatch
+{
+    let args = object_match.args
+        .iter()
+        .map(|arg| asm::InstructionArgument {
+            kind: match arg.kind
+            {
+                asm::object::ObjectArgumentKind::Expr(ref expr) =>
+                    asm::InstructionArgumentKind::Expr(
+                        rename_anonymous(expr.clone(), &linked.anonymous_names)),
+
+                asm::object::ObjectArgumentKind::Nested(ref nested) =>
+                    asm::InstructionArgumentKind::Nested(
+                        make_match(linked, nested)),
+            },
+            span: arg.span,
+            excerpt: arg.excerpt.clone(),
+        })
+        .collect();
+
+    asm::InstructionMatch {
+        ruledef_ref: linked.ruledef_refs[object_match.ruledef_index],
+        rule_ref: util::ItemRef::new(object_match.rule_index),
+        args,
+        exact_part_count: 0,
+        encoding: asm::InstructionMatchResolution::Unresolved,
+        encoding_size_guess: None,
+    }
+}
+
+
+fn eval_at(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    placement: Option<(Placement, usize)>,
+    value_expr: &expr::Expr)
+    -> Result<expr::Value, ()>
+{
+    let (placement, offset) = placement.unwrap_or((
+        Placement {
+            bank_ref: util::ItemRef::new(0),
+            position: 0,
+            output_position: None,
+        },
+        0));
+
+    make_ctx_at(placement, offset, &mut |ctx| {
+        asm::resolver::eval(
+            report,
+            fileserver,
+            opts,
+            decls,
+            defs,
+            ctx,
+            &mut expr::EvalContext::new(opts),
+            value_expr)
+    })
+}
+
+
+/// Calls `f` with a context for evaluating expressions
+/// at an offset into a placed chunk, in the global scope.
+fn make_ctx_at<T>(
+    placement: Placement,
+    offset: usize,
+    f: &mut dyn FnMut(&asm::ResolverContext) -> Result<T, ()>)
+    -> Result<T, ()>
+{
+    let bank_data = asm::resolver::BankData {
+        cur_position: placement.position + offset,
+        cur_position_resolved: true,
+    };
+
+    let symbol_ctx = util::SymbolContext::new_global();
+
+    let ctx = asm::ResolverContext {
+        node: asm::ResolverNode::None,
+        is_first_iteration: false,
+        is_last_iteration: true,
+        file_handle_ctx: None,
+        symbol_ctx: &symbol_ctx,
+        anonymous_label_count: 0,
+        bank_ref: placement.bank_ref,
+        bank_data: &bank_data,
+    };
+
+    f(&ctx)
+}
+
+
+/// Replaces the object names of anonymous labels
+/// with the ones they were declared with.
+fn rename_anonymous(
+    expr: expr::Expr,
+    names: &std::collections::HashMap<String, String>)
+    -> expr::Expr
+{
+    let rename = |e: Box<expr::Expr>| Box::new(rename_anonymous(*e, names));
+
+    match expr
+    {
+        expr::Expr::Variable(span, name) =>
+        {
+            let name = names
+                .get(&name)
+                .cloned()
+                .unwrap_or(name);
+
+            expr::Expr::Variable(span, name)
+        }
+
+        expr::Expr::MemberAccess { span, lhs, member_name } =>
+            expr::Expr::MemberAccess {
+                span,
+                lhs: rename(lhs),
+                member_name,
+            },
+
+        expr::Expr::StructInit { span, members_init } =>
+            expr::Expr::StructInit {
+                span,
+                members_init: members_init
+                    .into_iter()
+                    .map(|m| expr::ExprStructMemberInit {
+                        span: m.span,
+                        name: m.name,
+                        value: rename_anonymous(m.value, names),
+                    })
+                    .collect(),
+            },
+
+        expr::Expr::UnaryOp(span, op_span, op, inner) =>
+            expr::Expr::UnaryOp(span, op_span, op, rename(inner)),
+
+        expr::Expr::BinaryOp(span, op_span, op, lhs, rhs) =>
+            expr::Expr::BinaryOp(span, op_span, op, rename(lhs), rename(rhs)),
+
+        expr::Expr::TernaryOp(span, cond, true_branch, false_branch) =>
+            expr::Expr::TernaryOp(span, rename(cond), rename(true_branch), rename(false_branch)),
+
+        expr::Expr::Slice(span, span_inner, left, right, inner) =>
+            expr::Expr::Slice(span, span_inner, rename(left), rename(right), rename(inner)),
+
+        expr::Expr::SliceShort(span, span_inner, size, inner) =>
+            expr::Expr::SliceShort(span, span_inner, rename(size), rename(inner)),
+
+        expr::Expr::Block(span, exprs) =>
+            expr::Expr::Block(
+                span,
+                exprs.into_iter().map(|e| rename_anonymous(e, names)).collect()),
+
+        expr::Expr::Call(span, target, args) =>
+            expr::Expr::Call(
+                span,
+                rename(target),
+                args.into_iter().map(|e| rename_anonymous(e, names)).collect()),
+
+        expr::Expr::Literal(..) |
+        expr::Expr::NestingLevel { .. } |
+        expr::Expr::AnonymousLabel { .. } |
+        expr::Expr::Asm(..) => expr,
+    }
+}
\ No newline at end of file
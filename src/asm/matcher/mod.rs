@@ -148,40 +148,134 @@ pub fn match_all(
     defs: &mut asm::ItemDefs)
     -> Result<(), ()>
 {
-    for any_node in &ast.nodes
+    let ast_instrs = ast.nodes
+        .iter()
+        .filter_map(|node| match node {
+            asm::AstAny::Instruction(ast_instr) => Some(ast_instr),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let all_matches = match_instrs(
+        opts,
+        defs,
+        &ast_instrs);
+
+    for (ast_instr, matches) in ast_instrs.iter().zip(all_matches)
     {
-        if let asm::AstAny::Instruction(ast_instr) = any_node
-        {
-            let matches = match_instr(
-                opts,
-                defs,
-                ast_instr.span,
-                &ast_instr.src);
+        let first_msg = report.len();
 
-            if let Err(()) = error_on_no_matches(
-                report,
-                ast_instr.span,
-                &matches)
-            {
-                continue;
-            }
+        let maybe_no_matches = error_on_no_matches(
+            report,
+            ast_instr.span,
+            &matches);
 
-            let instr = defs.instructions.get_mut(
-                ast_instr.item_ref.unwrap());
-            
-            instr.matches = matches;
+        asm::parser::note_expansions(
+            report,
+            first_msg,
+            &ast_instr.macro_expansions);
 
-            instr.encoding_size_guess = instr.matches
-                .iter()
-                .max_by_key(|m| m.encoding_size_guess)
-                .and_then(|m| m.encoding_size_guess);
+        if let Err(()) = maybe_no_matches
+        {
+            continue;
         }
+
+        let instr = defs.instructions.get_mut(
+            ast_instr.item_ref.unwrap());
+        
+        instr.matches = matches;
+
+        instr.encoding_size_guess = instr.matches
+            .iter()
+            .max_by_key(|m| m.encoding_size_guess)
+            .and_then(|m| m.encoding_size_guess);
     }
 
     report.stop_at_errors()
 }
 
 
+/// Matches each of the given instructions,
+/// returning their matches in the same order.
+#[cfg(not(feature = "parallel-matcher"))]
+fn match_instrs(
+    opts: &asm::AssemblyOptions,
+    defs: &asm::ItemDefs,
+    ast_instrs: &[&asm::AstInstruction])
+    -> Vec<InstructionMatches>
+{
+    match_instrs_sequential(opts, defs, ast_instrs)
+}
+
+
+/// Matches each of the given instructions,
+/// returning their matches in the same order.
+/// Instructions are split into contiguous chunks, one per
+/// worker thread, and the results are concatenated back
+/// in chunk order, so the output doesn't depend on scheduling.
+#[cfg(feature = "parallel-matcher")]
+fn match_instrs(
+    opts: &asm::AssemblyOptions,
+    defs: &asm::ItemDefs,
+    ast_instrs: &[&asm::AstInstruction])
+    -> Vec<InstructionMatches>
+{
+    let thread_count = {
+        match opts.matcher_thread_count
+        {
+            0 => std::thread::available_parallelism()
+                .map_or(1, |n| n.get()),
+            n => n,
+        }
+    };
+
+    let chunk_size = ast_instrs.len()
+        .div_ceil(thread_count)
+        .max(MIN_INSTRUCTIONS_PER_THREAD);
+
+    if thread_count <= 1 || chunk_size >= ast_instrs.len()
+    {
+        return match_instrs_sequential(opts, defs, ast_instrs);
+    }
+
+    std::thread::scope(|scope|
+    {
+        let workers = ast_instrs
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move ||
+                match_instrs_sequential(opts, defs, chunk)))
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+
+/// Below this, spawning threads costs more than it saves.
+#[cfg(feature = "parallel-matcher")]
+const MIN_INSTRUCTIONS_PER_THREAD: usize = 256;
+
+
+fn match_instrs_sequential(
+    opts: &asm::AssemblyOptions,
+    defs: &asm::ItemDefs,
+    ast_instrs: &[&asm::AstInstruction])
+    -> Vec<InstructionMatches>
+{
+    ast_instrs
+        .iter()
+        .map(|ast_instr| match_instr_cached(
+            opts,
+            defs,
+            ast_instr.span,
+            &ast_instr.src))
+        .collect()
+}
+
+
 pub fn error_on_no_matches(
     report: &mut diagn::Report,
     span: diagn::Span,
@@ -203,6 +297,59 @@ pub fn error_on_no_matches(
 }
 
 
+/// Like `match_instr`, but only tries the rules that
+/// `opts.cache` remembers matching the same text before.
+fn match_instr_cached(
+    opts: &asm::AssemblyOptions,
+    defs: &asm::ItemDefs,
+    span: diagn::Span,
+    src: &str)
+    -> InstructionMatches
+{
+    let Some(cache) = &opts.cache
+        else { return match_instr(opts, defs, span, src) };
+
+    let cached_rules = cache
+        .lock()
+        .unwrap()
+        .get_instr_rules(defs, src);
+
+    if let Some(rules) = cached_rules
+    {
+        let walker = syntax::Walker::new(
+            src,
+            span.file_handle,
+            span.location().unwrap().0);
+
+        let mut working_matches = WorkingMatches::new();
+
+        for (ruledef_ref, rule_ref) in rules
+        {
+            let ruledef = defs.ruledefs.get(ruledef_ref);
+
+            working_matches.extend(begin_match_with_rule(
+                defs,
+                ruledef_ref,
+                rule_ref,
+                ruledef.get_rule(rule_ref),
+                walker.clone(),
+                true));
+        }
+
+        return finish_matches(opts, defs, working_matches);
+    }
+
+    let matches = match_instr(opts, defs, span, src);
+
+    cache
+        .lock()
+        .unwrap()
+        .insert_instr_rules(defs, src, &matches);
+
+    matches
+}
+
+
 /// Runs the instruction-matching algorithm on the given
 /// string, and returns the matches.
 pub fn match_instr(
@@ -248,7 +395,19 @@ pub fn match_instr(
             working_matches.extend(ruledef_matches);
         }
     }
-    
+
+    finish_matches(opts, defs, working_matches)
+}
+
+
+/// Keeps the most specific matches, and
+/// guesses their encoding sizes.
+fn finish_matches(
+    opts: &asm::AssemblyOptions,
+    defs: &asm::ItemDefs,
+    working_matches: WorkingMatches)
+    -> InstructionMatches
+{
     if working_matches.len() == 0
     {
         return vec![];
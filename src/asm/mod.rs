@@ -12,16 +12,30 @@ pub use parser::{
     AstDirectiveBits,
     AstDirectiveData,
     AstDirectiveFn,
+    AstDirectiveIf,
     AstDirectiveInclude,
     AstDirectiveLabelAlign,
+    AstDirectiveLayout,
+    AstDirectiveMacro,
+    AstDirectiveNamespace,
+    AstDirectiveNamespaceEnd,
     AstDirectiveNoEmit,
     AstDirectiveOnce,
+    AstDirectiveRepeat,
     AstDirectiveRes,
     AstDirectiveRuledef,
+    AstDirectiveSection,
+    AstDirectiveTest,
     AstField,
     AstFields,
     AstFnParameter,
     AstInstruction,
+    AstLayoutBank,
+    AstLayoutEntry,
+    AstMacroExpansion,
+    AstMacroParameter,
+    AstNamespaceKind,
+    AstNamespaceStruct,
     AstSymbol,
     AstSymbolKind,
     AstSymbolConstant,
@@ -47,6 +61,7 @@ pub use defs::{
     RuleParameterType,
     RulePattern,
     RulePatternPart,
+    Section,
     Symbol,
     Function,
     FunctionParameter,
@@ -77,6 +92,24 @@ pub use resolver::{
 
 pub mod output;
 
+pub mod disassembler;
+pub use disassembler::disassemble;
+
+pub mod object;
+pub use object::format_object;
+
+pub mod link;
+pub use link::link;
+
+pub mod cache;
+pub use cache::AssemblyCache;
+
+pub mod test_runner;
+pub use test_runner::{
+    TestResult,
+    run_tests,
+};
+
 
 pub struct AssemblyResult
 {
@@ -96,8 +129,20 @@ pub struct AssemblyOptions
     pub debug_iterations: bool,
     pub optimize_statically_known: bool,
     pub optimize_instruction_matching: bool,
+    pub allow_external_symbols: bool,
+
+    /// How many threads to match instructions on, when built
+    /// with the `parallel-matcher` feature, or 0 for one per core
+    pub matcher_thread_count: usize,
+
+    /// Directories searched for `#include`d files
+    /// not found relative to the including file
+    pub include_paths: Vec<String>,
 
     pub driver_symbol_defs: Vec<DriverSymbolDef>,
+
+    /// Reuses parsing and matching work from previous assemblies
+    pub cache: Option<std::sync::Arc<std::sync::Mutex<AssemblyCache>>>,
 }
 
 
@@ -134,8 +179,14 @@ impl AssemblyOptions
             debug_iterations: false,
             optimize_statically_known: true,
             optimize_instruction_matching: true,
+            allow_external_symbols: false,
+            matcher_thread_count: 0,
+
+            include_paths: Vec::new(),
 
             driver_symbol_defs: Vec::new(),
+
+            cache: None,
         }
     }
 }
@@ -153,127 +204,286 @@ pub fn assemble<S>(
 
     let mut run = || -> Result<(), ()>
     {
-        assembly.ast = Some(parser::parse_many_and_resolve_includes(
+        let mut ast = parser::parse_many_and_resolve_includes(
+            report,
+            opts,
+            fileserver,
+            root_filenames)?;
+
+        parser::expand_macros(
+            report,
+            &mut ast)?;
+
+        assemble_rounds(
             report,
             opts,
             fileserver,
-            root_filenames)?);
+            &mut assembly,
+            ast)
+    };
+    
+    match run()
+    {
+        Ok(()) => {}
+        Err(()) =>
+        {
+            assembly.error = true;
+            assert!(report.has_errors());
+        }
+    }
+
+    assembly
+}
+
 
-        assembly.decls = Some(decls::init(report, opts)?);
+/// Runs as many rounds of the assembly pipeline
+/// as needed for an already-parsed AST.
+fn assemble_rounds(
+    report: &mut diagn::Report,
+    opts: &AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    assembly: &mut AssemblyResult,
+    ast: asm::AstTopLevel)
+    -> Result<(), ()>
+{
+    // Loops whose ranges depend on labels are expanded
+    // with guessed ranges, and the whole assembly is
+    // retried until the guesses match the final values
+    let mut guesses = Vec::new();
+    let mut round_index = 1;
+
+    loop
+    {
+        if opts.debug_iterations && round_index > 1
+        {
+            println!(
+                "[===== round #{} =====]",
+                round_index);
+        }
+
+        assembly.ast = Some(ast.clone());
 
-        assembly.defs = Some(defs::init());
+        let mut round_report = diagn::Report::new();
 
-        let mut prev_resolved_constants_count = 0;
-        let mut pre_iteration_index = 1;
+        let round_result = assemble_round(
+            &mut round_report,
+            opts,
+            fileserver,
+            assembly,
+            &mut guesses);
 
-        loop
+        match round_result
         {
-            if opts.debug_iterations
+            Ok(None) =>
             {
-                println!(
-                    "[===== pre-iteration #{} =====]",
-                    pre_iteration_index);
+                round_report.transfer_to(report);
+                return Ok(());
             }
 
-            decls::collect(
-                report,
-                opts,
-                assembly.ast.as_mut().unwrap(),
-                assembly.decls.as_mut().unwrap())?;
-
-            defs::define_symbols(
-                report,
-                opts,
-                assembly.ast.as_mut().unwrap(),
-                assembly.decls.as_ref().unwrap(),
-                assembly.defs.as_mut().unwrap())?;
-                
-            let resolved_constants_count = resolver::resolve_constants_simple(
-                report,
-                opts,
-                fileserver,
-                assembly.ast.as_ref().unwrap(),
-                assembly.decls.as_ref().unwrap(),
-                assembly.defs.as_mut().unwrap())?;
-    
-            let resolved_ifs_count = resolver::resolve_ifs(
-                report,
-                opts,
-                fileserver,
-                assembly.ast.as_mut().unwrap(),
-                assembly.decls.as_ref().unwrap(),
-                assembly.defs.as_mut().unwrap())?;
+            Err(()) =>
+            {
+                round_report.transfer_to(report);
+                return Err(());
+            }
 
-            if resolved_constants_count == prev_resolved_constants_count &&
-                resolved_ifs_count == 0
+            Ok(Some(mismatch_span)) =>
             {
-                break;
+                if round_index >= opts.max_iterations
+                {
+                    round_report.transfer_to(report);
+
+                    report.error_span(
+                        "repeat count did not converge",
+                        mismatch_span);
+
+                    return Err(());
+                }
             }
+        }
+
+        round_index += 1;
+    }
+}
 
-            prev_resolved_constants_count = resolved_constants_count;
-            pre_iteration_index += 1;
+
+/// Runs the assembly pipeline on `assembly.ast`.
+/// Returns the span of a loop whose guessed range
+/// turned out wrong, in which case another round is needed.
+fn assemble_round(
+    report: &mut diagn::Report,
+    opts: &AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    assembly: &mut AssemblyResult,
+    guesses: &mut Vec<resolver::RepeatGuess>)
+    -> Result<Option<diagn::Span>, ()>
+{
+    let prev_guesses = std::mem::take(guesses);
+
+    assembly.decls = Some(decls::init(report, opts)?);
+
+    assembly.defs = Some(defs::init());
+
+    let mut prev_resolved_constants_count = 0;
+    let mut pre_iteration_index = 1;
+
+    loop
+    {
+        if opts.debug_iterations
+        {
+            println!(
+                "[===== pre-iteration #{} =====]",
+                pre_iteration_index);
         }
 
-        resolver::check_leftover_ifs(
-            report,
-            opts,
-            assembly.ast.as_ref().unwrap(),
-            assembly.decls.as_ref().unwrap(),
-            assembly.defs.as_ref().unwrap())?;
-            
-        defs::define_remaining(
+        decls::collect(
             report,
             opts,
             assembly.ast.as_mut().unwrap(),
-            assembly.defs.as_mut().unwrap(),
             assembly.decls.as_mut().unwrap())?;
 
-        matcher::match_all(
+        defs::define_symbols(
             report,
             opts,
-            assembly.ast.as_ref().unwrap(),
+            assembly.ast.as_mut().unwrap(),
             assembly.decls.as_ref().unwrap(),
             assembly.defs.as_mut().unwrap())?;
-
-        assembly.iterations_taken = Some(resolver::resolve_iteratively(
+            
+        let resolved_constants_count = resolver::resolve_constants_simple(
             report,
             opts,
             fileserver,
             assembly.ast.as_ref().unwrap(),
             assembly.decls.as_ref().unwrap(),
-            assembly.defs.as_mut().unwrap(),
-            opts.max_iterations)?);
+            assembly.defs.as_mut().unwrap())?;
 
-        output::check_bank_overlap(
+        let resolved_ifs_count = resolver::resolve_ifs(
             report,
+            opts,
+            fileserver,
+            assembly.ast.as_mut().unwrap(),
             assembly.decls.as_ref().unwrap(),
             assembly.defs.as_mut().unwrap())?;
 
-        assembly.output = Some(output::build_output(
+        let resolved_repeats_count = resolver::resolve_repeats(
             report,
-            assembly.ast.as_ref().unwrap(),
+            opts,
+            fileserver,
+            assembly.ast.as_mut().unwrap(),
             assembly.decls.as_ref().unwrap(),
-            assembly.defs.as_ref().unwrap())?);
+            assembly.defs.as_mut().unwrap())?;
 
-        check_unused_defines(
-            report,
-            opts,
-            assembly.decls.as_ref().unwrap())?;
+        if resolved_constants_count == prev_resolved_constants_count &&
+            resolved_ifs_count == 0 &&
+            resolved_repeats_count == 0
+        {
+            let guessed_repeats_count = resolver::guess_repeats(
+                report,
+                assembly.ast.as_mut().unwrap(),
+                &prev_guesses,
+                guesses)?;
 
-        Ok(())
-    };
-    
-    match run()
+            if guessed_repeats_count == 0
+            {
+                break;
+            }
+        }
+
+        prev_resolved_constants_count = resolved_constants_count;
+        pre_iteration_index += 1;
+    }
+
+    resolver::check_leftover_ifs(
+        report,
+        opts,
+        assembly.ast.as_ref().unwrap(),
+        assembly.decls.as_ref().unwrap(),
+        assembly.defs.as_ref().unwrap())?;
+        
+    defs::define_remaining(
+        report,
+        opts,
+        assembly.ast.as_mut().unwrap(),
+        assembly.defs.as_mut().unwrap(),
+        assembly.decls.as_mut().unwrap())?;
+
+    if let Some(cache) = &opts.cache
     {
-        Ok(()) => {}
-        Err(()) =>
+        cache
+            .lock()
+            .unwrap()
+            .set_ruledefs(assembly.defs.as_ref().unwrap());
+    }
+
+    matcher::match_all(
+        report,
+        opts,
+        assembly.ast.as_ref().unwrap(),
+        assembly.decls.as_ref().unwrap(),
+        assembly.defs.as_mut().unwrap())?;
+
+    let iterations_taken = resolver::resolve_iteratively(
+        report,
+        opts,
+        fileserver,
+        assembly.ast.as_ref().unwrap(),
+        assembly.decls.as_ref().unwrap(),
+        assembly.defs.as_mut().unwrap(),
+        opts.max_iterations);
+
+    if guesses.len() > 0
+    {
+        // Errors caused by a wrong guess are irrelevant
+        // if another round is going to be run
+        let mut check_report = diagn::Report::new();
+
+        let mismatch = resolver::check_repeat_guesses(
+            &mut check_report,
+            opts,
+            assembly.decls.as_ref().unwrap(),
+            assembly.defs.as_ref().unwrap(),
+            guesses);
+
+        match mismatch
         {
-            assembly.error = true;
-            assert!(report.has_errors());
+            Ok(Some(span)) => return Ok(Some(span)),
+            Ok(None) => {}
+            Err(()) =>
+            {
+                if iterations_taken.is_ok()
+                {
+                    check_report.transfer_to(report);
+                    return Err(());
+                }
+            }
         }
     }
 
-    assembly
+    assembly.iterations_taken = Some(iterations_taken?);
+
+    // Objects only get their output once linked
+    if opts.allow_external_symbols
+    {
+        assembly.output = Some(util::BitVec::new());
+        return Ok(None);
+    }
+
+    output::check_bank_overlap(
+        report,
+        assembly.decls.as_ref().unwrap(),
+        assembly.defs.as_mut().unwrap())?;
+
+    assembly.output = Some(output::build_output(
+        report,
+        assembly.ast.as_ref().unwrap(),
+        assembly.decls.as_ref().unwrap(),
+        assembly.defs.as_ref().unwrap())?);
+
+    check_unused_defines(
+        report,
+        opts,
+        assembly.decls.as_ref().unwrap())?;
+
+    Ok(None)
 }
 
 
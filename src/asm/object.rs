@@ -0,0 +1,2047 @@
+use crate::*;
+
+
+/// The first line of every object file, which the
+/// linker uses to tell objects apart from source files.
+pub const OBJECT_HEADER: &str = "; customasm object";
+
+
+/// How many bits each `data` line of an object holds.
+const DATA_LINE_BITS: usize = 256;
+
+
+/// An object file, as read back by the linker.
+///
+/// Objects hold the final contents of every bank and section
+/// used by the source, as chunks of bits. Symbols are given as
+/// offsets into chunks or as values, and every item whose value
+/// depends on where chunks get placed is left as a relocation,
+/// to be evaluated and patched in by the linker. The rules and
+/// functions that relocations may use come along as source.
+pub struct Object
+{
+    pub definitions: Vec<ObjectDefinition>,
+    pub chunks: Vec<ObjectChunk>,
+    pub symbols: Vec<ObjectSymbol>,
+    pub externals: Vec<ObjectExternal>,
+    pub relocations: Vec<ObjectRelocation>,
+}
+
+
+pub struct ObjectDefinition
+{
+    /// The source text, which tells apart definitions
+    /// that objects share from an included file
+    pub src: String,
+    pub ast: asm::AstTopLevel,
+}
+
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ObjectChunkKind
+{
+    /// The bank selected before any `#bank`,
+    /// which the linker picks from the layout files
+    Default,
+    Bank,
+    Section,
+}
+
+
+pub struct ObjectChunk
+{
+    pub span: diagn::Span,
+    pub kind: ObjectChunkKind,
+    pub name: String,
+    /// Alignment of the start position, in bits
+    pub align: usize,
+    /// The contents, sized to the whole chunk
+    pub data: util::BigInt,
+}
+
+
+/// A position within a chunk, in bits.
+#[derive(Copy, Clone, Debug)]
+pub struct ObjectSite
+{
+    pub chunk_index: usize,
+    pub offset: usize,
+}
+
+
+pub struct ObjectSymbol
+{
+    pub span: diagn::Span,
+    pub kind: util::SymbolKind,
+    pub hierarchy_level: usize,
+    /// The fully-qualified name, one entry per level
+    pub hierarchy: Vec<String>,
+    pub no_emit: bool,
+    /// Where labels are, and where constants
+    /// that use the current address are declared
+    pub site: Option<ObjectSite>,
+    pub value: Option<expr::Expr>,
+}
+
+
+pub struct ObjectExternal
+{
+    pub span: diagn::Span,
+    pub hierarchy: Vec<String>,
+}
+
+
+pub struct ObjectRelocation
+{
+    pub span: diagn::Span,
+    pub site: ObjectSite,
+    pub size: usize,
+    pub kind: ObjectRelocationKind,
+}
+
+
+pub enum ObjectRelocationKind
+{
+    Data {
+        elem_size: Option<usize>,
+        expr: expr::Expr,
+    },
+    Instruction(ObjectMatch),
+    Assert(expr::Expr),
+}
+
+
+/// An instruction match, referring to a rule by the index
+/// of its ruledef among the object's definitions.
+pub struct ObjectMatch
+{
+    pub ruledef_index: usize,
+    pub rule_index: usize,
+    pub args: Vec<ObjectArgument>,
+}
+
+
+pub struct ObjectArgument
+{
+    pub span: diagn::Span,
+    pub excerpt: String,
+    pub kind: ObjectArgumentKind,
+}
+
+
+pub enum ObjectArgumentKind
+{
+    Expr(expr::Expr),
+    Nested(ObjectMatch),
+}
+
+
+/// Where an expression is evaluated, for resolving
+/// the symbols it refers to.
+#[derive(Clone)]
+struct Scope
+{
+    symbol_ctx: util::SymbolContext,
+    anonymous_label_count: usize,
+}
+
+
+/// The expression giving the value of a symbol, along with
+/// the names local to it, like the parameters of a `#fn`.
+struct SymbolExpr<'ast>
+{
+    scope: Scope,
+    expr: &'ast expr::Expr,
+    locals: Vec<String>,
+}
+
+
+struct ObjectWriter<'a>
+{
+    opts: &'a asm::AssemblyOptions,
+    fileserver: &'a dyn util::FileServer,
+    decls: &'a asm::ItemDecls,
+    defs: &'a asm::ItemDefs,
+
+    /// The bank of each chunk, in order of first use
+    chunk_refs: Vec<util::ItemRef<asm::Bankdef>>,
+    chunk_aligns: Vec<usize>,
+    chunk_data: Vec<util::BitVec>,
+
+    /// Object names for anonymous labels, which are
+    /// reserved names the linker replaces with its own
+    anonymous_names: std::collections::HashMap<String, String>,
+    ruledef_indices: std::collections::HashMap<usize, usize>,
+    /// Symbols whose value depends on where chunks get placed
+    relocatable: std::collections::HashSet<usize>,
+    sites: std::collections::HashMap<usize, ObjectSite>,
+    externals: Vec<String>,
+}
+
+
+/// Serializes an assembly made with `allow_external_symbols`
+/// into an object file.
+pub fn format_object(
+    report: &mut diagn::Report,
+    fileserver: &dyn util::FileServer,
+    opts: &asm::AssemblyOptions,
+    ast: &asm::AstTopLevel,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs)
+    -> Result<String, ()>
+{
+    let mut writer = ObjectWriter {
+        opts,
+        fileserver,
+        decls,
+        defs,
+
+        chunk_refs: Vec::new(),
+        chunk_aligns: Vec::new(),
+        chunk_data: Vec::new(),
+
+        anonymous_names: std::collections::HashMap::new(),
+        ruledef_indices: std::collections::HashMap::new(),
+        relocatable: std::collections::HashSet::new(),
+        sites: std::collections::HashMap::new(),
+        externals: Vec::new(),
+    };
+
+    for (index, &item_ref) in decls.anonymous_labels.iter().enumerate()
+    {
+        writer.anonymous_names.insert(
+            decls.symbols.get(item_ref).name.clone(),
+            format!("$anonymous_{}", index));
+    }
+
+    let mut definitions = String::new();
+
+    for node in &ast.nodes
+    {
+        let src = match node
+        {
+            asm::AstAny::DirectiveRuledef(ast_ruledef) =>
+            {
+                writer.ruledef_indices.insert(
+                    ast_ruledef.item_ref.unwrap().0,
+                    writer.ruledef_indices.len());
+
+                get_braced_src(fileserver, ast_ruledef.header_span)
+            }
+
+            asm::AstAny::DirectiveFn(ast_fn) =>
+                get_src_between(fileserver, ast_fn.header_span, ast_fn.body.span()),
+
+            _ => continue,
+        };
+
+        definitions.push_str(&format!(
+            "definition {}\n{}\n\n",
+            src.lines().count(),
+            src));
+    }
+
+    let symbol_exprs = writer.collect_symbols(report, ast)?;
+    writer.collect_relocatable(&symbol_exprs);
+
+    let relocations = writer.write_chunks(report, ast)?;
+
+    let mut symbols = String::new();
+
+    for decl in decls.symbols.iter()
+    {
+        if let Some(line) = writer.format_symbol(decl, &symbol_exprs)
+        {
+            symbols.push_str(&line);
+        }
+    }
+
+    let mut result = String::new();
+    result.push_str(OBJECT_HEADER);
+    result.push_str("\n\n");
+    result.push_str(&definitions);
+
+    for (chunk_index, &bank_ref) in writer.chunk_refs.iter().enumerate()
+    {
+        result.push_str(&writer.format_chunk(chunk_index, bank_ref, ast));
+    }
+
+    if symbols.len() > 0
+    {
+        result.push_str(&symbols);
+        result.push_str("\n");
+    }
+
+    writer.externals.sort();
+    writer.externals.dedup();
+
+    for external in &writer.externals
+    {
+        result.push_str(&format!("extern {}\n", external));
+    }
+
+    if writer.externals.len() > 0
+    {
+        result.push_str("\n");
+    }
+
+    result.push_str(&relocations);
+
+    Ok(result)
+}
+
+
+impl<'a> ObjectWriter<'a>
+{
+    fn get_chunk_index(
+        &mut self,
+        bank_ref: util::ItemRef<asm::Bankdef>)
+        -> usize
+    {
+        if let Some(index) = self.chunk_refs.iter().position(|r| *r == bank_ref)
+        {
+            return index;
+        }
+
+        self.chunk_refs.push(bank_ref);
+        self.chunk_aligns.push(1);
+        self.chunk_data.push(util::BitVec::new());
+        self.chunk_refs.len() - 1
+    }
+
+
+    fn get_site(
+        &mut self,
+        ctx: &asm::ResolverContext)
+        -> ObjectSite
+    {
+        ObjectSite {
+            chunk_index: self.get_chunk_index(ctx.bank_ref),
+            offset: ctx.bank_data.cur_position,
+        }
+    }
+
+
+    /// Finds the expression behind every symbol that has one,
+    /// and where every label and positional constant is.
+    fn collect_symbols<'ast>(
+        &mut self,
+        report: &mut diagn::Report,
+        ast: &'ast asm::AstTopLevel)
+        -> Result<std::collections::HashMap<usize, SymbolExpr<'ast>>, ()>
+    {
+        let mut symbol_exprs = std::collections::HashMap::new();
+
+        for node in &ast.nodes
+        {
+            if let asm::AstAny::DirectiveFn(ast_fn) = node
+            {
+                symbol_exprs.insert(
+                    ast_fn.item_ref.unwrap().0,
+                    SymbolExpr {
+                        scope: Scope {
+                            symbol_ctx: util::SymbolContext::new_global(),
+                            anonymous_label_count: 0,
+                        },
+                        expr: &ast_fn.body,
+                        locals: ast_fn.params
+                            .iter()
+                            .map(|p| p.name.clone())
+                            .collect(),
+                    });
+            }
+        }
+
+        let mut iter = asm::ResolveIterator::new(
+            ast,
+            self.defs,
+            false,
+            true);
+
+        while let Some(ctx) = iter.next(report, self.decls, self.defs)?
+        {
+            let scope = Scope {
+                symbol_ctx: ctx.symbol_ctx.clone(),
+                anonymous_label_count: ctx.anonymous_label_count,
+            };
+
+            match ctx.node
+            {
+                asm::ResolverNode::Symbol(ast_symbol) =>
+                {
+                    let item_ref = ast_symbol.item_ref.unwrap();
+
+                    match ast_symbol.kind
+                    {
+                        asm::AstSymbolKind::Label =>
+                        {
+                            let site = self.get_site(&ctx);
+                            self.sites.insert(item_ref.0, site);
+                        }
+
+                        asm::AstSymbolKind::Constant(ref constant) =>
+                        {
+                            if expr_uses_address(self.opts, &constant.expr)
+                            {
+                                let site = self.get_site(&ctx);
+                                self.sites.insert(item_ref.0, site);
+                            }
+
+                            symbol_exprs.insert(
+                                item_ref.0,
+                                SymbolExpr {
+                                    scope,
+                                    expr: &constant.expr,
+                                    locals: Vec::new(),
+                                });
+                        }
+                    }
+                }
+
+                asm::ResolverNode::Namespace(ast_namespace) =>
+                {
+                    if let asm::AstNamespaceKind::Struct(ref ast_struct) = ast_namespace.kind
+                    {
+                        symbol_exprs.insert(
+                            ast_namespace.item_ref.unwrap().0,
+                            SymbolExpr {
+                                scope,
+                                expr: &ast_struct.value_expr,
+                                locals: Vec::new(),
+                            });
+                    }
+                }
+
+                asm::ResolverNode::None |
+                asm::ResolverNode::NamespaceEnd(..) => {}
+
+                _ =>
+                {
+                    self.get_chunk_index(ctx.bank_ref);
+                }
+            }
+        }
+
+        Ok(symbol_exprs)
+    }
+
+
+    /// Finds every symbol whose value depends on where
+    /// chunks get placed, or on other objects.
+    fn collect_relocatable(
+        &mut self,
+        symbol_exprs: &std::collections::HashMap<usize, SymbolExpr>)
+    {
+        for decl in self.decls.symbols.iter()
+        {
+            if let util::SymbolKind::Label = decl.kind
+            {
+                self.relocatable.insert(decl.item_ref.0);
+            }
+        }
+
+        loop
+        {
+            let mut changed = false;
+
+            for (&index, symbol_expr) in symbol_exprs
+            {
+                if self.relocatable.contains(&index)
+                {
+                    continue;
+                }
+
+                if self.is_relocatable(
+                    &symbol_expr.scope,
+                    symbol_expr.expr,
+                    &mut symbol_expr.locals.clone())
+                {
+                    self.relocatable.insert(index);
+                    changed = true;
+                }
+            }
+
+            if !changed
+            {
+                break;
+            }
+        }
+    }
+
+
+    /// Writes out the contents of every chunk,
+    /// returning the relocations found along the way.
+    fn write_chunks(
+        &mut self,
+        report: &mut diagn::Report,
+        ast: &asm::AstTopLevel)
+        -> Result<String, ()>
+    {
+        let mut relocations = String::new();
+
+        let mut iter = asm::ResolveIterator::new(
+            ast,
+            self.defs,
+            false,
+            true);
+
+        while let Some(ctx) = iter.next(report, self.decls, self.defs)?
+        {
+            let scope = Scope {
+                symbol_ctx: ctx.symbol_ctx.clone(),
+                anonymous_label_count: ctx.anonymous_label_count,
+            };
+
+            match ctx.node
+            {
+                asm::ResolverNode::Instruction(ast_instr) =>
+                {
+                    let instr = self.defs.instructions.get(ast_instr.item_ref.unwrap());
+
+                    let encoding = asm::resolver::finalize_instruction(
+                        report,
+                        ast_instr.span,
+                        instr)?;
+
+                    let site = self.get_site(&ctx);
+
+                    match instr.get_chosen_match()
+                    {
+                        Some(mtch) if self.is_match_relocatable(&scope, mtch) =>
+                        {
+                            relocations.push_str(&format!(
+                                "reloc {} {} {} {}end\n",
+                                site.chunk_index,
+                                site.offset,
+                                encoding.size.unwrap(),
+                                self.format_match(&scope, mtch)));
+                        }
+
+                        _ =>
+                        {
+                            self.chunk_data[site.chunk_index].write_bigint(
+                                site.offset,
+                                encoding);
+                        }
+                    }
+                }
+
+                asm::ResolverNode::DataElement(ast_data, elem_index) =>
+                {
+                    let expr = &ast_data.elems[elem_index];
+                    let elem = self.defs.data_elems.get(ast_data.item_refs[elem_index]);
+
+                    let bigint = asm::resolver::check_final_data_element(
+                        report,
+                        expr.span(),
+                        elem)?;
+
+                    let site = self.get_site(&ctx);
+
+                    if self.is_relocatable(&scope, expr, &mut Vec::new())
+                    {
+                        let directive = ast_data.elem_size
+                            .map_or("d".to_string(), |size| format!("d{}", size));
+
+                        relocations.push_str(&format!(
+                            "reloc {} {} {} {} = {}\n",
+                            site.chunk_index,
+                            site.offset,
+                            bigint.size.unwrap(),
+                            directive,
+                            self.format_expr(&scope, expr)));
+                    }
+                    else
+                    {
+                        self.chunk_data[site.chunk_index].write_bigint(
+                            site.offset,
+                            &bigint);
+                    }
+                }
+
+                asm::ResolverNode::Assert(ast_assert) =>
+                {
+                    let expr = &ast_assert.condition_expr;
+
+                    if self.is_relocatable(&scope, expr, &mut Vec::new())
+                    {
+                        let site = self.get_site(&ctx);
+
+                        relocations.push_str(&format!(
+                            "reloc {} {} 0 assert = {}\n",
+                            site.chunk_index,
+                            site.offset,
+                            self.format_expr(&scope, expr)));
+                    }
+                }
+
+                asm::ResolverNode::Res(ast_res) =>
+                {
+                    self.check_not_relocatable(report, &scope, &ast_res.expr)?;
+                }
+
+                asm::ResolverNode::Addr(ast_addr) =>
+                {
+                    self.check_not_relocatable(report, &scope, &ast_addr.expr)?;
+                }
+
+                asm::ResolverNode::Align(ast_align) =>
+                {
+                    self.check_not_relocatable(report, &scope, &ast_align.expr)?;
+
+                    // Keeps the chunk's padding valid wherever it gets placed
+                    let align_size = self.defs.align_directives
+                        .get(ast_align.item_ref.unwrap())
+                        .align_size
+                        .max(1);
+
+                    let chunk_index = self.get_chunk_index(ctx.bank_ref);
+                    let chunk_align = self.chunk_aligns[chunk_index];
+
+                    self.chunk_aligns[chunk_index] =
+                        chunk_align / gcd(chunk_align, align_size) * align_size;
+                }
+
+                asm::ResolverNode::Symbol(..) |
+                asm::ResolverNode::Namespace(..) |
+                asm::ResolverNode::NamespaceEnd(..) |
+                asm::ResolverNode::None => {}
+            }
+        }
+
+        // Chunks extend up to the final position in their bank,
+        // covering any space left by `#res`
+        for (chunk_index, bank_ref) in self.chunk_refs.iter().enumerate()
+        {
+            let end = iter.get_bank_data()[bank_ref.0].cur_position;
+            let chunk = &mut self.chunk_data[chunk_index];
+
+            if chunk.len() < end
+            {
+                chunk.write_bigint(
+                    chunk.len(),
+                    &util::BigInt::new(0, Some(end - chunk.len())));
+            }
+        }
+
+        Ok(relocations)
+    }
+
+
+    fn check_not_relocatable(
+        &mut self,
+        report: &mut diagn::Report,
+        scope: &Scope,
+        expr: &expr::Expr)
+        -> Result<(), ()>
+    {
+        if self.is_relocatable(scope, expr, &mut Vec::new())
+        {
+            report.error_span(
+                "value must be known before linking",
+                expr.span());
+
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+
+    fn format_chunk(
+        &self,
+        chunk_index: usize,
+        bank_ref: util::ItemRef<asm::Bankdef>,
+        ast: &asm::AstTopLevel)
+        -> String
+    {
+        let is_section = ast.nodes
+            .iter()
+            .any(|node| match node {
+                asm::AstAny::DirectiveSection(ast_section) =>
+                    ast_section.item_ref == Some(bank_ref),
+                _ => false,
+            });
+
+        let kind = {
+            if is_section
+                { format!("section {}", self.decls.bankdefs.get(bank_ref).name) }
+            else if bank_ref.0 == 0
+                { "default".to_string() }
+            else
+                { format!("bank {}", self.decls.bankdefs.get(bank_ref).name) }
+        };
+
+        let data = &self.chunk_data[chunk_index];
+        let size = data.len();
+
+        let mut result = format!(
+            "chunk {} {} size {}",
+            chunk_index,
+            kind,
+            size);
+
+        if self.chunk_aligns[chunk_index] > 1
+        {
+            result.push_str(&format!(
+                " align {}",
+                self.chunk_aligns[chunk_index]));
+        }
+
+        result.push_str("\n");
+
+        let mut offset = 0;
+
+        while offset < size
+        {
+            let line_size = DATA_LINE_BITS.min(size - offset);
+            let mut bigint = util::BigInt::new(0, Some(line_size));
+
+            for i in 0..line_size
+            {
+                bigint.set_bit(
+                    line_size - 1 - i,
+                    data.read_bit(offset + i));
+            }
+
+            result.push_str(&format!(
+                "data {}\n",
+                format_sized_literal(&bigint).unwrap()));
+
+            offset += line_size;
+        }
+
+        result.push_str("\n");
+        result
+    }
+
+
+    fn format_symbol(
+        &mut self,
+        decl: &util::SymbolDecl<asm::Symbol>,
+        symbol_exprs: &std::collections::HashMap<usize, SymbolExpr>)
+        -> Option<String>
+    {
+        let index = decl.item_ref.0;
+        let symbol = self.defs.symbols.maybe_get(decl.item_ref)?;
+
+        let kind = match decl.kind
+        {
+            util::SymbolKind::Label => "label",
+            util::SymbolKind::Constant => "constant",
+            util::SymbolKind::Namespace => "namespace",
+            util::SymbolKind::Function |
+            util::SymbolKind::Other => return None,
+        };
+
+        let mut line = format!(
+            "symbol {} {} {}",
+            kind,
+            decl.depth,
+            self.get_object_name(decl.item_ref));
+
+        if symbol.no_emit && !matches!(decl.kind, util::SymbolKind::Namespace)
+        {
+            line.push_str(" noemit");
+        }
+
+        if let Some(site) = self.sites.get(&index)
+        {
+            line.push_str(&format!(
+                " at {} {}",
+                site.chunk_index,
+                site.offset));
+        }
+
+        let maybe_literal = {
+            if self.relocatable.contains(&index)
+                { None }
+            else
+                { format_value(&symbol.value) }
+        };
+
+        if let Some(literal) = maybe_literal
+        {
+            line.push_str(&format!(" = {}", literal));
+        }
+        else if let Some(symbol_expr) = symbol_exprs.get(&index)
+        {
+            if !matches!(decl.kind, util::SymbolKind::Label)
+            {
+                let scope = symbol_expr.scope.clone();
+                let value = self.format_expr(&scope, symbol_expr.expr);
+                line.push_str(&format!(" = {}", value));
+            }
+        }
+
+        line.push_str("\n");
+        Some(line)
+    }
+
+
+    fn get_object_name(
+        &self,
+        item_ref: util::ItemRef<asm::Symbol>)
+        -> String
+    {
+        self.decls.symbols
+            .get(item_ref)
+            .name
+            .split(".")
+            .map(|name| {
+                self.anonymous_names
+                    .get(name)
+                    .map_or(name, |n| n.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+
+    fn format_match(
+        &mut self,
+        scope: &Scope,
+        mtch: &asm::InstructionMatch)
+        -> String
+    {
+        let mut result = format!(
+            "instr {} {}\n",
+            self.ruledef_indices[&mtch.ruledef_ref.0],
+            mtch.rule_ref.0);
+
+        for arg in &mtch.args
+        {
+            match arg.kind
+            {
+                asm::InstructionArgumentKind::Expr(ref expr) =>
+                {
+                    result.push_str(&format!(
+                        "arg = {}\n",
+                        self.format_expr(scope, expr)));
+                }
+
+                asm::InstructionArgumentKind::Nested(ref nested) =>
+                {
+                    result.push_str(&format!(
+                        "arg {}end\n",
+                        self.format_match(scope, nested)));
+                }
+            }
+        }
+
+        result
+    }
+
+
+    /// Whether an instruction's encoding depends on where
+    /// chunks get placed, either through its arguments
+    /// or through the rules it uses.
+    fn is_match_relocatable(
+        &self,
+        scope: &Scope,
+        mtch: &asm::InstructionMatch)
+        -> bool
+    {
+        for arg in &mtch.args
+        {
+            let is_relocatable = match arg.kind
+            {
+                asm::InstructionArgumentKind::Expr(ref expr) =>
+                    self.is_relocatable(scope, expr, &mut Vec::new()),
+
+                asm::InstructionArgumentKind::Nested(ref nested) =>
+                    self.is_match_relocatable(scope, nested),
+            };
+
+            if is_relocatable
+            {
+                return true;
+            }
+        }
+
+        let rule = self.defs.ruledefs
+            .get(mtch.ruledef_ref)
+            .get_rule(mtch.rule_ref);
+
+        let mut locals = rule.parameters
+            .iter()
+            .map(|param| param.name.clone())
+            .collect::<Vec<_>>();
+
+        self.is_relocatable(scope, &rule.expr, &mut locals)
+    }
+
+
+    /// Whether an expression refers to the current address,
+    /// to a label, to an `asm` block, or to a symbol not
+    /// defined in this object. Names in `locals` or assigned
+    /// within the expression don't count.
+    fn is_relocatable(
+        &self,
+        scope: &Scope,
+        expr: &expr::Expr,
+        locals: &mut Vec<String>)
+        -> bool
+    {
+        match expr
+        {
+            expr::Expr::Literal(..) => false,
+
+            expr::Expr::Variable(_, name) =>
+            {
+                if locals.contains(name)
+                {
+                    return false;
+                }
+
+                if asm::resolver::resolve_builtin_symbol(name, self.opts).is_some()
+                {
+                    return true;
+                }
+
+                if is_builtin_fn(self.opts, name)
+                {
+                    return false;
+                }
+
+                self.decls.symbols
+                    .try_get_by_name(&scope.symbol_ctx, 0, &[name.as_str()])
+                    .map_or(true, |r| self.relocatable.contains(&r.0))
+            }
+
+            expr::Expr::NestingLevel { .. } => true,
+            expr::Expr::AnonymousLabel { .. } => true,
+            expr::Expr::Asm(..) => true,
+
+            expr::Expr::MemberAccess { lhs, .. } =>
+            {
+                let Some((level, hierarchy)) = flatten_member_access(expr)
+                    else { return self.is_relocatable(scope, lhs, locals) };
+
+                if level == 0 && locals.contains(&hierarchy[0].to_string())
+                {
+                    return false;
+                }
+
+                match self.resolve_prefix(scope, level, &hierarchy)
+                {
+                    Some((symbol_ref, _)) => self.relocatable.contains(&symbol_ref.0),
+                    None => true,
+                }
+            }
+
+            expr::Expr::StructInit { members_init, .. } =>
+                members_init
+                    .iter()
+                    .any(|m| self.is_relocatable(scope, &m.value, locals)),
+
+            expr::Expr::UnaryOp(_, _, _, inner) =>
+                self.is_relocatable(scope, inner, locals),
+
+            expr::Expr::BinaryOp(_, _, expr::BinaryOp::Assign, lhs, rhs) =>
+            {
+                if let expr::Expr::Variable(_, ref name) = **lhs
+                {
+                    locals.push(name.clone());
+                }
+
+                self.is_relocatable(scope, rhs, locals)
+            }
+
+            expr::Expr::BinaryOp(_, _, _, lhs, rhs) =>
+                self.is_relocatable(scope, lhs, locals) ||
+                    self.is_relocatable(scope, rhs, locals),
+
+            expr::Expr::TernaryOp(_, cond, true_branch, false_branch) =>
+                self.is_relocatable(scope, cond, locals) ||
+                    self.is_relocatable(scope, true_branch, locals) ||
+                    self.is_relocatable(scope, false_branch, locals),
+
+            expr::Expr::Slice(_, _, left, right, inner) =>
+                self.is_relocatable(scope, left, locals) ||
+                    self.is_relocatable(scope, right, locals) ||
+                    self.is_relocatable(scope, inner, locals),
+
+            expr::Expr::SliceShort(_, _, size, inner) =>
+                self.is_relocatable(scope, size, locals) ||
+                    self.is_relocatable(scope, inner, locals),
+
+            expr::Expr::Block(_, exprs) =>
+                exprs.iter().any(|e| self.is_relocatable(scope, e, locals)),
+
+            expr::Expr::Call(_, target, args) =>
+                self.is_relocatable(scope, target, locals) ||
+                    args.iter().any(|e| self.is_relocatable(scope, e, locals)),
+        }
+    }
+
+
+    /// Finds the symbol named by the longest prefix of a
+    /// hierarchy, returning it along with how many names
+    /// it took up. The rest are members of its value.
+    fn resolve_prefix(
+        &self,
+        scope: &Scope,
+        hierarchy_level: usize,
+        hierarchy: &[&str])
+        -> Option<(util::ItemRef<asm::Symbol>, usize)>
+    {
+        for len in (1..=hierarchy.len()).rev()
+        {
+            let maybe_symbol_ref = self.decls.symbols.try_get_by_name(
+                &scope.symbol_ctx,
+                hierarchy_level,
+                &hierarchy[..len]);
+
+            if let Some(symbol_ref) = maybe_symbol_ref
+            {
+                return Some((symbol_ref, len));
+            }
+        }
+
+        if hierarchy_level == 0
+        {
+            return None;
+        }
+
+        self.decls.symbols
+            .get_current_label(
+                &mut diagn::Report::new(),
+                diagn::Span::new_dummy(),
+                &scope.symbol_ctx,
+                hierarchy_level)
+            .ok()
+            .map(|symbol_ref| (symbol_ref, 0))
+    }
+
+
+    /// Prints an expression back as source code, with every
+    /// symbol given by its fully-qualified name, so that it
+    /// can be evaluated anywhere by the linker.
+    fn format_expr(
+        &mut self,
+        scope: &Scope,
+        expr: &expr::Expr)
+        -> String
+    {
+        match expr
+        {
+            expr::Expr::Literal(span, value) =>
+            {
+                match format_value(value)
+                {
+                    Some(literal) => literal,
+                    None => self.fileserver.get_excerpt(*span),
+                }
+            }
+
+            expr::Expr::Variable(..) |
+            expr::Expr::NestingLevel { .. } =>
+                self.format_member_access(scope, expr),
+
+            expr::Expr::MemberAccess { lhs, member_name, .. } =>
+            {
+                match flatten_member_access(expr)
+                {
+                    Some(_) => self.format_member_access(scope, expr),
+                    None => format!("{}.{}", self.format_expr(scope, lhs), member_name),
+                }
+            }
+
+            expr::Expr::AnonymousLabel { span, offset } =>
+            {
+                let index = {
+                    if *offset < 0
+                        { scope.anonymous_label_count.checked_sub(offset.unsigned_abs()) }
+                    else
+                        { Some(scope.anonymous_label_count + *offset as usize - 1) }
+                };
+
+                match index.and_then(|i| self.decls.anonymous_labels.get(i))
+                {
+                    Some(&symbol_ref) => self.get_object_name(symbol_ref),
+                    None => self.fileserver.get_excerpt(*span),
+                }
+            }
+
+            expr::Expr::UnaryOp(_, _, op, inner) =>
+            {
+                let op_str = match op
+                {
+                    expr::UnaryOp::Neg => "-",
+                    expr::UnaryOp::Not => "!",
+                };
+
+                format!("({}{})", op_str, self.format_expr(scope, inner))
+            }
+
+            expr::Expr::BinaryOp(_, _, op, lhs, rhs) =>
+            {
+                let op_str = match op
+                {
+                    expr::BinaryOp::Assign => "=",
+                    expr::BinaryOp::Add => "+",
+                    expr::BinaryOp::Sub => "-",
+                    expr::BinaryOp::Mul => "*",
+                    expr::BinaryOp::Div => "/",
+                    expr::BinaryOp::Mod => "%",
+                    expr::BinaryOp::Shl => "<<",
+                    expr::BinaryOp::Shr => ">>",
+                    expr::BinaryOp::And => "&",
+                    expr::BinaryOp::Or => "|",
+                    expr::BinaryOp::Xor => "^",
+                    expr::BinaryOp::Eq => "==",
+                    expr::BinaryOp::Ne => "!=",
+                    expr::BinaryOp::Lt => "<",
+                    expr::BinaryOp::Le => "<=",
+                    expr::BinaryOp::Gt => ">",
+                    expr::BinaryOp::Ge => ">=",
+                    expr::BinaryOp::LazyAnd => "&&",
+                    expr::BinaryOp::LazyOr => "||",
+                    expr::BinaryOp::Concat => "@",
+                };
+
+                // Assignments declare names local to the expression
+                if let expr::BinaryOp::Assign = op
+                {
+                    if let expr::Expr::Variable(_, ref name) = **lhs
+                    {
+                        return format!(
+                            "{} = {}",
+                            name,
+                            self.format_expr(scope, rhs));
+                    }
+                }
+
+                format!(
+                    "({} {} {})",
+                    self.format_expr(scope, lhs),
+                    op_str,
+                    self.format_expr(scope, rhs))
+            }
+
+            expr::Expr::TernaryOp(_, cond, true_branch, false_branch) =>
+            {
+                format!(
+                    "({} ? {} : {})",
+                    self.format_expr(scope, cond),
+                    self.format_expr(scope, true_branch),
+                    self.format_expr(scope, false_branch))
+            }
+
+            expr::Expr::Slice(_, _, left, right, inner) =>
+            {
+                format!(
+                    "({})[{}:{}]",
+                    self.format_expr(scope, inner),
+                    self.format_expr(scope, left),
+                    self.format_expr(scope, right))
+            }
+
+            expr::Expr::SliceShort(_, _, size, inner) =>
+            {
+                format!(
+                    "({})`{}",
+                    self.format_expr(scope, inner),
+                    self.format_expr(scope, size))
+            }
+
+            expr::Expr::Block(_, exprs) =>
+            {
+                format!(
+                    "{{{}}}",
+                    exprs.iter()
+                        .map(|e| self.format_expr(scope, e))
+                        .collect::<Vec<_>>()
+                        .join(", "))
+            }
+
+            expr::Expr::StructInit { members_init, .. } =>
+            {
+                format!(
+                    "struct {{ {} }}",
+                    members_init.iter()
+                        .map(|m| format!("{} = {}", m.name, self.format_expr(scope, &m.value)))
+                        .collect::<Vec<_>>()
+                        .join(", "))
+            }
+
+            expr::Expr::Call(_, target, args) =>
+            {
+                format!(
+                    "{}({})",
+                    self.format_expr(scope, target),
+                    args.iter()
+                        .map(|arg| self.format_expr(scope, arg))
+                        .collect::<Vec<_>>()
+                        .join(", "))
+            }
+
+            expr::Expr::Asm(span, _) =>
+                self.fileserver.get_excerpt(*span),
+        }
+    }
+
+
+    /// Prints a variable or a chain of member accesses,
+    /// replacing the symbol it starts with by its
+    /// fully-qualified name. Unknown global names are
+    /// taken to be external.
+    fn format_member_access(
+        &mut self,
+        scope: &Scope,
+        expr: &expr::Expr)
+        -> String
+    {
+        let (level, hierarchy) = flatten_member_access(expr).unwrap();
+
+        if level == 0 &&
+            (asm::resolver::resolve_builtin_symbol(hierarchy[0], self.opts).is_some() ||
+                is_builtin_fn(self.opts, hierarchy[0]))
+        {
+            return hierarchy.join(".");
+        }
+
+        let (mut name, members) = match self.resolve_prefix(scope, level, &hierarchy)
+        {
+            Some((symbol_ref, len)) =>
+                (self.get_object_name(symbol_ref), &hierarchy[len..]),
+
+            None if level == 0 =>
+            {
+                self.externals.push(hierarchy[0].to_string());
+                (hierarchy[0].to_string(), &hierarchy[1..])
+            }
+
+            None =>
+                (".".repeat(level), &hierarchy[..]),
+        };
+
+        for member in members
+        {
+            if !name.ends_with(".")
+            {
+                name.push_str(".");
+            }
+
+            name.push_str(member);
+        }
+
+        name
+    }
+}
+
+
+/// Reads an object file written by `format_object`.
+pub fn read_object(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    file_handle: util::FileServerHandle,
+    src: &str)
+    -> Result<Object, ()>
+{
+    let mut object = Object {
+        definitions: Vec::new(),
+        chunks: Vec::new(),
+        symbols: Vec::new(),
+        externals: Vec::new(),
+        relocations: Vec::new(),
+    };
+
+    let mut chunk_data = util::BitVec::new();
+    let mut index = 0;
+
+    loop
+    {
+        let mut walker = syntax::Walker::new(
+            &src[index..],
+            file_handle,
+            index);
+
+        walker.skip_ignorable();
+
+        if walker.is_over()
+        {
+            break;
+        }
+
+        if walker.maybe_expect_linebreak().is_some()
+        {
+            index += walker.get_cursor_index();
+            continue;
+        }
+
+        let (keyword, keyword_span) = expect_word(report, &mut walker)?;
+
+        match keyword.as_str()
+        {
+            "definition" =>
+            {
+                let line_count = expect_usize(report, &mut walker)?;
+                walker.expect_linebreak(report)?;
+
+                let start = index + walker.get_cursor_index();
+                let mut end = start;
+
+                for _ in 0..line_count
+                {
+                    end = src[end..]
+                        .find('\n')
+                        .map_or(src.len(), |i| end + i + 1);
+                }
+
+                let mut def_walker = syntax::Walker::new(
+                    &src[start..end],
+                    file_handle,
+                    start);
+
+                let ast = asm::parser::parse(
+                    report,
+                    opts,
+                    &mut def_walker)?;
+
+                object.definitions.push(ObjectDefinition {
+                    src: src[start..end].trim().to_string(),
+                    ast,
+                });
+
+                index = end;
+                continue;
+            }
+
+            "chunk" =>
+            {
+                finish_chunk(report, &mut object, &mut chunk_data)?;
+
+                let chunk_index = expect_usize(report, &mut walker)?;
+
+                if chunk_index != object.chunks.len()
+                {
+                    report.error_span(
+                        "chunks must be numbered in order",
+                        keyword_span);
+
+                    return Err(());
+                }
+
+                let (kind_name, kind_span) = expect_word(report, &mut walker)?;
+
+                let kind = match kind_name.as_str()
+                {
+                    "default" => ObjectChunkKind::Default,
+                    "bank" => ObjectChunkKind::Bank,
+                    "section" => ObjectChunkKind::Section,
+                    _ =>
+                    {
+                        report.error_span(
+                            format!("unknown chunk kind `{}`", kind_name),
+                            kind_span);
+
+                        return Err(());
+                    }
+                };
+
+                let name = match kind
+                {
+                    ObjectChunkKind::Default => "".to_string(),
+                    _ => expect_word(report, &mut walker)?.0,
+                };
+
+                expect_keyword(report, &mut walker, "size")?;
+                let size = expect_usize(report, &mut walker)?;
+
+                let align = {
+                    if maybe_expect_keyword(&mut walker, "align")
+                        { expect_usize(report, &mut walker)? }
+                    else
+                        { 1 }
+                };
+
+                object.chunks.push(ObjectChunk {
+                    span: keyword_span.join(walker.get_cursor_span()),
+                    kind,
+                    name,
+                    align,
+                    data: util::BigInt::new(0, Some(size)),
+                });
+            }
+
+            "data" =>
+            {
+                if object.chunks.len() == 0
+                {
+                    report.error_span(
+                        "data outside of a chunk",
+                        keyword_span);
+
+                    return Err(());
+                }
+
+                let tk_data = walker.expect(report, syntax::TokenKind::Number)?;
+
+                let bigint = syntax::excerpt_as_bigint(
+                    Some(report),
+                    tk_data.span,
+                    walker.get_span_excerpt(tk_data.span))?;
+
+                if bigint.size.is_none()
+                {
+                    report.error_span(
+                        "data must be given in hexadecimal or binary",
+                        tk_data.span);
+
+                    return Err(());
+                }
+
+                chunk_data.write_bigint(
+                    chunk_data.len(),
+                    &bigint);
+            }
+
+            "symbol" =>
+            {
+                let (kind_name, kind_span) = expect_word(report, &mut walker)?;
+
+                let kind = match kind_name.as_str()
+                {
+                    "label" => util::SymbolKind::Label,
+                    "constant" => util::SymbolKind::Constant,
+                    "namespace" => util::SymbolKind::Namespace,
+                    _ =>
+                    {
+                        report.error_span(
+                            format!("unknown symbol kind `{}`", kind_name),
+                            kind_span);
+
+                        return Err(());
+                    }
+                };
+
+                let hierarchy_level = expect_usize(report, &mut walker)?;
+                let (hierarchy, name_span) = expect_name(report, &mut walker)?;
+                let no_emit = maybe_expect_keyword(&mut walker, "noemit");
+
+                let site = {
+                    if maybe_expect_keyword(&mut walker, "at")
+                        { Some(expect_site(report, &mut walker, &object)?) }
+                    else
+                        { None }
+                };
+
+                let value = {
+                    if walker.maybe_expect(syntax::TokenKind::Equal).is_some()
+                        { Some(expr::parse(report, &mut walker)?) }
+                    else
+                        { None }
+                };
+
+                if let util::SymbolKind::Label = kind
+                {
+                    if site.is_none()
+                    {
+                        report.error_span(
+                            "label has no position",
+                            name_span);
+
+                        return Err(());
+                    }
+                }
+
+                object.symbols.push(ObjectSymbol {
+                    span: name_span,
+                    kind,
+                    hierarchy_level,
+                    hierarchy,
+                    no_emit,
+                    site,
+                    value,
+                });
+            }
+
+            "extern" =>
+            {
+                let (hierarchy, span) = expect_name(report, &mut walker)?;
+
+                object.externals.push(ObjectExternal {
+                    span,
+                    hierarchy,
+                });
+            }
+
+            "reloc" =>
+            {
+                let site = expect_site(report, &mut walker, &object)?;
+                let size = expect_usize(report, &mut walker)?;
+                let (kind_name, kind_span) = expect_word(report, &mut walker)?;
+
+                let kind = {
+                    if kind_name == "instr"
+                    {
+                        let mtch = parse_match(
+                            report,
+                            src,
+                            file_handle,
+                            &mut index,
+                            &mut walker)?;
+
+                        walker = syntax::Walker::new(
+                            &src[index..],
+                            file_handle,
+                            index);
+
+                        ObjectRelocationKind::Instruction(mtch)
+                    }
+                    else if kind_name == "assert"
+                    {
+                        walker.expect(report, syntax::TokenKind::Equal)?;
+                        ObjectRelocationKind::Assert(expr::parse(report, &mut walker)?)
+                    }
+                    else if let Some(elem_size) = parse_data_directive(&kind_name)
+                    {
+                        walker.expect(report, syntax::TokenKind::Equal)?;
+
+                        ObjectRelocationKind::Data {
+                            elem_size,
+                            expr: expr::parse(report, &mut walker)?,
+                        }
+                    }
+                    else
+                    {
+                        report.error_span(
+                            format!("unknown relocation kind `{}`", kind_name),
+                            kind_span);
+
+                        return Err(());
+                    }
+                };
+
+                object.relocations.push(ObjectRelocation {
+                    span: keyword_span.join(kind_span),
+                    site,
+                    size,
+                    kind,
+                });
+
+                if let ObjectRelocationKind::Instruction(..) = object.relocations.last().unwrap().kind
+                {
+                    continue;
+                }
+            }
+
+            _ =>
+            {
+                report.error_span(
+                    format!("unknown object directive `{}`", keyword),
+                    keyword_span);
+
+                return Err(());
+            }
+        }
+
+        walker.expect_linebreak(report)?;
+        index += walker.get_cursor_index();
+    }
+
+    finish_chunk(report, &mut object, &mut chunk_data)?;
+
+    Ok(object)
+}
+
+
+/// Moves the bits read from `data` lines
+/// into the last chunk, checking their size.
+fn finish_chunk(
+    report: &mut diagn::Report,
+    object: &mut Object,
+    chunk_data: &mut util::BitVec)
+    -> Result<(), ()>
+{
+    let data = std::mem::replace(chunk_data, util::BitVec::new());
+
+    let Some(chunk) = object.chunks.last_mut()
+        else { return Ok(()) };
+
+    let size = chunk.data.size.unwrap();
+
+    if data.len() != size
+    {
+        report.error_span(
+            format!(
+                "chunk has size {}, but got {} bits of data",
+                size,
+                data.len()),
+            chunk.span);
+
+        return Err(());
+    }
+
+    let mut bigint = data.to_bigint();
+    bigint.size = Some(size);
+    chunk.data = bigint;
+
+    Ok(())
+}
+
+
+/// Reads the arguments of an instruction relocation,
+/// one per line, up to its `end`.
+fn parse_match(
+    report: &mut diagn::Report,
+    src: &str,
+    file_handle: util::FileServerHandle,
+    index: &mut usize,
+    walker: &mut syntax::Walker)
+    -> Result<ObjectMatch, ()>
+{
+    let ruledef_index = expect_usize(report, walker)?;
+    let rule_index = expect_usize(report, walker)?;
+    walker.expect_linebreak(report)?;
+    *index += walker.get_cursor_index();
+
+    let mut args = Vec::new();
+
+    loop
+    {
+        let mut walker = syntax::Walker::new(
+            &src[*index..],
+            file_handle,
+            *index);
+
+        let (keyword, keyword_span) = expect_word(report, &mut walker)?;
+
+        if keyword == "end"
+        {
+            walker.expect_linebreak(report)?;
+            *index += walker.get_cursor_index();
+            break;
+        }
+
+        if keyword != "arg"
+        {
+            report.error_span(
+                "expected `arg` or `end`",
+                keyword_span);
+
+            return Err(());
+        }
+
+        if walker.maybe_expect(syntax::TokenKind::Equal).is_some()
+        {
+            let expr = expr::parse(report, &mut walker)?;
+            let span = expr.span();
+
+            args.push(ObjectArgument {
+                span,
+                excerpt: walker.get_span_excerpt(span).to_string(),
+                kind: ObjectArgumentKind::Expr(expr),
+            });
+
+            walker.expect_linebreak(report)?;
+            *index += walker.get_cursor_index();
+        }
+        else
+        {
+            expect_keyword(report, &mut walker, "instr")?;
+
+            let start = *index;
+
+            let nested = parse_match(
+                report,
+                src,
+                file_handle,
+                index,
+                &mut walker)?;
+
+            let span = diagn::Span::new(file_handle, start, *index);
+
+            args.push(ObjectArgument {
+                span,
+                excerpt: src[start..*index].trim().to_string(),
+                kind: ObjectArgumentKind::Nested(nested),
+            });
+        }
+    }
+
+    Ok(ObjectMatch {
+        ruledef_index,
+        rule_index,
+        args,
+    })
+}
+
+
+/// Reads the size of a data relocation, as in `d` or `d16`.
+fn parse_data_directive(name: &str) -> Option<Option<usize>>
+{
+    let size = name.strip_prefix("d")?;
+
+    if size.len() == 0
+    {
+        return Some(None);
+    }
+
+    size.parse().ok().map(Some)
+}
+
+
+fn expect_word(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker)
+    -> Result<(String, diagn::Span), ()>
+{
+    let tk_word = walker.expect(report, syntax::TokenKind::Identifier)?;
+
+    Ok((
+        walker.get_span_excerpt(tk_word.span).to_string(),
+        tk_word.span))
+}
+
+
+fn maybe_expect_keyword(
+    walker: &mut syntax::Walker,
+    keyword: &str)
+    -> bool
+{
+    let mut lookahead = walker.clone();
+
+    match lookahead.maybe_expect(syntax::TokenKind::Identifier)
+    {
+        Some(tk) if lookahead.get_span_excerpt(tk.span) == keyword =>
+        {
+            *walker = lookahead;
+            true
+        }
+
+        _ => false,
+    }
+}
+
+
+fn expect_keyword(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    keyword: &str)
+    -> Result<(), ()>
+{
+    if !maybe_expect_keyword(walker, keyword)
+    {
+        report.error_span(
+            format!("expected `{}`", keyword),
+            walker.get_cursor_span());
+
+        return Err(());
+    }
+
+    Ok(())
+}
+
+
+fn expect_usize(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker)
+    -> Result<usize, ()>
+{
+    let tk_number = walker.expect(report, syntax::TokenKind::Number)?;
+
+    syntax::excerpt_as_usize(
+        report,
+        tk_number.span,
+        walker.get_span_excerpt(tk_number.span))
+}
+
+
+/// Reads a fully-qualified symbol name, like `a.b.c`.
+fn expect_name(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker)
+    -> Result<(Vec<String>, diagn::Span), ()>
+{
+    let (name, mut span) = expect_word(report, walker)?;
+    let mut hierarchy = vec![name];
+
+    while walker.maybe_expect(syntax::TokenKind::Dot).is_some()
+    {
+        let (name, name_span) = expect_word(report, walker)?;
+        hierarchy.push(name);
+        span = span.join(name_span);
+    }
+
+    Ok((hierarchy, span))
+}
+
+
+fn expect_site(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    object: &Object)
+    -> Result<ObjectSite, ()>
+{
+    let start_span = walker.get_cursor_span();
+    let chunk_index = expect_usize(report, walker)?;
+    let offset = expect_usize(report, walker)?;
+
+    let in_bounds = object.chunks
+        .get(chunk_index)
+        .map_or(false, |c| offset <= c.data.size.unwrap());
+
+    if !in_bounds
+    {
+        report.error_span(
+            "position is outside of any chunk",
+            start_span.join(walker.get_cursor_span()));
+
+        return Err(());
+    }
+
+    Ok(ObjectSite {
+        chunk_index,
+        offset,
+    })
+}
+
+
+/// Writes a value as a literal, if it's a plain
+/// integer or boolean.
+fn format_value(value: &expr::Value) -> Option<String>
+{
+    match value
+    {
+        expr::Value::Integer(_, bigint) =>
+        {
+            match bigint.size
+            {
+                Some(0) => None,
+                Some(_) => format_sized_literal(bigint),
+                None => Some(bigint.to_str_radix(10)),
+            }
+        }
+
+        expr::Value::Bool(_, true) => Some("true".to_string()),
+        expr::Value::Bool(_, false) => Some("false".to_string()),
+
+        _ => None,
+    }
+}
+
+
+fn format_sized_literal(bigint: &util::BigInt) -> Option<String>
+{
+    let size = bigint.size?;
+
+    if size == 0
+    {
+        return None;
+    }
+
+    let bits_per_digit = if size % 4 == 0 { 4 } else { 1 };
+
+    let mut literal = String::new();
+    literal.push_str(if bits_per_digit == 4 { "0x" } else { "0b" });
+
+    for digit_index in 0..(size / bits_per_digit)
+    {
+        let mut digit = 0;
+
+        for i in 0..bits_per_digit
+        {
+            let bit = bigint.get_bit(
+                size - 1 - digit_index * bits_per_digit - i);
+
+            digit <<= 1;
+            digit |= if bit { 1 } else { 0 };
+        }
+
+        literal.push(std::char::from_digit(digit, 16).unwrap());
+    }
+
+    Some(literal)
+}
+
+
+/// Splits a variable, or a chain of member accesses on one,
+/// into its hierarchy level and names.
+fn flatten_member_access(expr: &expr::Expr) -> Option<(usize, Vec<&str>)>
+{
+    match expr
+    {
+        expr::Expr::Variable(_, name) =>
+            Some((0, vec![name.as_str()])),
+
+        expr::Expr::NestingLevel { nesting_level, .. } =>
+            Some((*nesting_level, Vec::new())),
+
+        expr::Expr::MemberAccess { lhs, member_name, .. } =>
+        {
+            let (level, mut hierarchy) = flatten_member_access(lhs)?;
+            hierarchy.push(member_name.as_str());
+            Some((level, hierarchy))
+        }
+
+        _ => None,
+    }
+}
+
+
+fn gcd(a: usize, b: usize) -> usize
+{
+    if b == 0
+        { a }
+    else
+        { gcd(b, a % b) }
+}
+
+
+/// Whether an expression refers to the current address.
+fn expr_uses_address(
+    opts: &asm::AssemblyOptions,
+    expr: &expr::Expr)
+    -> bool
+{
+    let mut uses_address = false;
+
+    walk_variables(expr, &mut |name| {
+        if asm::resolver::resolve_builtin_symbol(name, opts).is_some()
+        {
+            uses_address = true;
+        }
+    });
+
+    uses_address
+}
+
+
+fn is_builtin_fn(
+    opts: &asm::AssemblyOptions,
+    name: &str)
+    -> bool
+{
+    expr::resolve_builtin_fn(name, opts).is_some() ||
+        asm::resolver::resolve_builtin_fn(name, opts).is_some()
+}
+
+
+/// Calls `f` with the name of every top-level variable
+/// used in an expression, skipping `asm` blocks.
+fn walk_variables(
+    expr: &expr::Expr,
+    f: &mut dyn FnMut(&str))
+{
+    match expr
+    {
+        expr::Expr::Variable(_, name) => f(name),
+
+        expr::Expr::MemberAccess { lhs, .. } =>
+            walk_variables(lhs, f),
+
+        expr::Expr::StructInit { members_init, .. } =>
+        {
+            for member in members_init
+            {
+                walk_variables(&member.value, f);
+            }
+        }
+
+        expr::Expr::UnaryOp(_, _, _, inner) =>
+            walk_variables(inner, f),
+
+        expr::Expr::BinaryOp(_, _, _, lhs, rhs) =>
+        {
+            walk_variables(lhs, f);
+            walk_variables(rhs, f);
+        }
+
+        expr::Expr::TernaryOp(_, cond, true_branch, false_branch) =>
+        {
+            walk_variables(cond, f);
+            walk_variables(true_branch, f);
+            walk_variables(false_branch, f);
+        }
+
+        expr::Expr::Slice(_, _, left, right, inner) =>
+        {
+            walk_variables(left, f);
+            walk_variables(right, f);
+            walk_variables(inner, f);
+        }
+
+        expr::Expr::SliceShort(_, _, size, inner) =>
+        {
+            walk_variables(size, f);
+            walk_variables(inner, f);
+        }
+
+        expr::Expr::Block(_, exprs) =>
+        {
+            for e in exprs
+            {
+                walk_variables(e, f);
+            }
+        }
+
+        expr::Expr::Call(_, target, args) =>
+        {
+            walk_variables(target, f);
+
+            for arg in args
+            {
+                walk_variables(arg, f);
+            }
+        }
+
+        expr::Expr::Literal(..) |
+        expr::Expr::NestingLevel { .. } |
+        expr::Expr::AnonymousLabel { .. } |
+        expr::Expr::Asm(..) => {}
+    }
+}
+
+
+/// Extracts the source of a directive from its header
+/// up to the closing brace of its body.
+fn get_braced_src(
+    fileserver: &dyn util::FileServer,
+    header_span: diagn::Span)
+    -> String
+{
+    let src = fileserver.get_str_unwrap(header_span.file_handle);
+    let start = header_span.location().unwrap().0;
+
+    let mut index = start;
+    let mut depth = 0;
+
+    while index < src.len()
+    {
+        let (kind, length) = syntax::decide_next_token(&src[index..]);
+        index += length;
+
+        if kind == syntax::TokenKind::BraceOpen
+        {
+            depth += 1;
+        }
+        else if kind == syntax::TokenKind::BraceClose
+        {
+            depth -= 1;
+
+            if depth == 0
+            {
+                break;
+            }
+        }
+    }
+
+    src[start..index].to_string()
+}
+
+
+fn get_src_between(
+    fileserver: &dyn util::FileServer,
+    start_span: diagn::Span,
+    end_span: diagn::Span)
+    -> String
+{
+    let src = fileserver.get_str_unwrap(start_span.file_handle);
+    let start = start_span.location().unwrap().0;
+    let end = end_span.location().unwrap().1;
+
+    src[start..end].to_string()
+}
\ No newline at end of file
@@ -27,6 +27,14 @@ pub fn check_bank_overlap(
                 continue;
             }
 
+            // Sections are placed clear of the
+            // contents of the bank that holds them
+            if is_section_placed_in(defs, bankdef1.item_ref, bankdef2.item_ref) ||
+                is_section_placed_in(defs, bankdef2.item_ref, bankdef1.item_ref)
+            {
+                continue;
+            }
+
             let outp1 = bankdef1.output_offset.unwrap();
             let outp2 = bankdef2.output_offset.unwrap();
 
@@ -74,6 +82,17 @@ pub fn check_bank_overlap(
 }
 
 
+fn is_section_placed_in(
+    defs: &asm::ItemDefs,
+    section_ref: util::ItemRef<asm::Bankdef>,
+    bank_ref: util::ItemRef<asm::Bankdef>)
+    -> bool
+{
+    asm::Section::find(defs, section_ref)
+        .map_or(false, |s| s.placed_in == Some(bank_ref))
+}
+
+
 pub fn build_output(
     report: &mut diagn::Report,
     ast: &asm::AstTopLevel,
@@ -97,166 +116,235 @@ pub fn build_output(
 
     while let Some(ctx) = iter.next(report, decls, defs)?
     {
-        if let asm::ResolverNode::Symbol(ast_symbol) = ctx.node
+        let is_discarded = asm::Section::find(defs, ctx.bank_ref)
+            .map_or(false, |s| s.discard || s.unreferenced);
+
+        if is_discarded
         {
-            let symbol = defs.symbols.get(ast_symbol.item_ref.unwrap());
+            continue;
+        }
 
-            if let asm::AstSymbolKind::Label = ast_symbol.kind
-            {
-                check_bank_usage(
-                    report,
-                    ast_symbol.decl_span,
-                    defs,
-                    &ctx)?;
+        let first_msg = report.len();
 
-                check_bank_output(
-                    report,
-                    ast_symbol.decl_span,
-                    decls,
-                    defs,
-                    &ctx,
-                    0,
-                    false)?;
+        let maybe_ok = build_node_output(
+            report,
+            decls,
+            defs,
+            &ctx,
+            &mut output,
+            &mut overlap_checker);
 
-                let maybe_pos = ctx.get_output_position(defs);
+        asm::parser::note_expansions(
+            report,
+            first_msg,
+            ctx.node.macro_expansions());
 
-                output.mark_span(
-                    maybe_pos,
-                    0,
-                    symbol.value.unwrap_bigint().clone(),
-                    ast_symbol.decl_span);
-            }
-        }
-        
-        else if let asm::ResolverNode::Instruction(ast_instr) = ctx.node
+        maybe_ok?;
+    }
+
+    output.bank_ends = iter
+        .get_bank_data()
+        .iter()
+        .map(|b| b.cur_position)
+        .collect();
+
+    Ok(output)
+}
+
+
+fn build_node_output(
+    report: &mut diagn::Report,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    ctx: &asm::ResolverContext,
+    output: &mut util::BitVec,
+    overlap_checker: &mut util::OverlapChecker)
+    -> Result<(), ()>
+{
+    if let asm::ResolverNode::Symbol(ast_symbol) = ctx.node
+    {
+        let symbol = defs.symbols.get(ast_symbol.item_ref.unwrap());
+
+        if let asm::AstSymbolKind::Label = ast_symbol.kind
         {
-            let instr = defs.instructions.get(ast_instr.item_ref.unwrap());
+            check_bank_usage(
+                report,
+                ast_symbol.decl_span,
+                defs,
+                ctx)?;
+
+            check_bank_output(
+                report,
+                ast_symbol.decl_span,
+                decls,
+                defs,
+                ctx,
+                0,
+                false)?;
+
+            let maybe_pos = ctx.get_output_position(defs);
+
+            output.mark_span(
+                maybe_pos,
+                0,
+                symbol.value.unwrap_bigint().clone(),
+                ast_symbol.decl_span,
+                ctx.bank_ref,
+                None);
+        }
+    }
+    
+    else if let asm::ResolverNode::Instruction(ast_instr) = ctx.node
+    {
+        let instr = defs.instructions.get(ast_instr.item_ref.unwrap());
 
-            if let Ok(encoding) = asm::resolver::finalize_instruction(
+        if let Ok(encoding) = asm::resolver::finalize_instruction(
+            report,
+            ast_instr.span,
+            instr)
+        {
+            check_bank_usage(
                 report,
                 ast_instr.span,
-                instr)
-            {
-                check_bank_usage(
-                    report,
-                    ast_instr.span,
-                    defs,
-                    &ctx)?;
+                defs,
+                ctx)?;
 
-                check_bank_output(
+            check_bank_output(
+                report,
+                ast_instr.span,
+                decls,
+                defs,
+                ctx,
+                encoding.size.unwrap(),
+                true)?;
+                
+            let addr = ctx
+                .get_address(
                     report,
                     ast_instr.span,
-                    decls,
                     defs,
-                    &ctx,
-                    encoding.size.unwrap(),
-                    true)?;
-                    
-                let addr = ctx
-                    .get_address(
-                        report,
-                        ast_instr.span,
-                        defs,
-                        true)?
-                    .unwrap();
-                
-                let pos = ctx.get_output_position(defs).unwrap();
+                    true)?
+                .unwrap();
+            
+            let pos = ctx.get_output_position(defs).unwrap();
 
-                overlap_checker.check_and_insert(
-                    report,
-                    ast_instr.span,
-                    pos,
-                    encoding.size.unwrap())?;
+            overlap_checker.check_and_insert(
+                report,
+                ast_instr.span,
+                pos,
+                encoding.size.unwrap())?;
 
-                output.write_bigint_with_span(
-                    ast_instr.span,
-                    pos,
-                    addr,
-                    &encoding);
-            }
+            output.write_bigint_with_span(
+                ast_instr.span,
+                pos,
+                addr,
+                ctx.bank_ref,
+                ast_instr.item_ref,
+                &encoding);
         }
-        
-        else if let asm::ResolverNode::DataElement(ast_data, elem_index) = ctx.node
+    }
+    
+    else if let asm::ResolverNode::DataElement(ast_data, elem_index) = ctx.node
+    {
+        let item_ref = ast_data.item_refs[elem_index];
+        let elem = defs.data_elems.get(item_ref);
+        let span = ast_data.elems[elem_index].span();
+
+        if let Ok(bigint) = asm::resolver::check_final_data_element(
+            report,
+            span,
+            elem)
         {
-            let item_ref = ast_data.item_refs[elem_index];
-            let elem = defs.data_elems.get(item_ref);
-            let span = ast_data.elems[elem_index].span();
+            check_bank_usage(
+                report,
+                span,
+                defs,
+                ctx)?;
 
-            if let Ok(bigint) = asm::resolver::check_final_data_element(
+            check_bank_output(
                 report,
                 span,
-                elem)
-            {
-                check_bank_usage(
+                decls,
+                defs,
+                ctx,
+                bigint.size.unwrap(),
+                true)?;
+                
+            let pos = ctx.get_output_position(defs).unwrap();
+            let addr = ctx
+                .get_address(
                     report,
                     span,
                     defs,
-                    &ctx)?;
+                    true)?
+                .unwrap();
 
-                check_bank_output(
-                    report,
-                    span,
-                    decls,
-                    defs,
-                    &ctx,
-                    bigint.size.unwrap(),
-                    true)?;
-                    
-                let pos = ctx.get_output_position(defs).unwrap();
-                let addr = ctx
-                    .get_address(
-                        report,
-                        span,
-                        defs,
-                        true)?
-                    .unwrap();
-
-                overlap_checker.check_and_insert(
-                    report,
-                    span,
-                    pos,
-                    bigint.size.unwrap())?;
+            overlap_checker.check_and_insert(
+                report,
+                span,
+                pos,
+                bigint.size.unwrap())?;
 
-                output.write_bigint_with_span(
-                    span,
-                    pos,
-                    addr,
-                    &bigint);
-            }
+            output.write_bigint_with_span(
+                span,
+                pos,
+                addr,
+                ctx.bank_ref,
+                None,
+                &bigint);
         }
-        
-        else if let asm::ResolverNode::Res(ast_res) = ctx.node
+    }
+    
+    else if let asm::ResolverNode::Res(ast_res) = ctx.node
+    {
+        let item_ref = ast_res.item_ref.unwrap();
+        let res = defs.res_directives.get(item_ref);
+
+        check_bank_usage(
+            report,
+            ast_res.header_span,
+            defs,
+            ctx)?;
+
+        check_bank_output(
+            report,
+            ast_res.header_span,
+            decls,
+            defs,
+            ctx,
+            res.reserve_size,
+            false)?;
+            
+        let maybe_pos = ctx.get_output_position(defs);
+
+        if let Some(pos) = maybe_pos
         {
-            let item_ref = ast_res.item_ref.unwrap();
-            let res = defs.res_directives.get(item_ref);
-
-            check_bank_usage(
+            overlap_checker.check_and_insert(
                 report,
                 ast_res.header_span,
-                defs,
-                &ctx)?;
+                pos,
+                res.reserve_size)?;
+        }
 
-            check_bank_output(
+        let addr = ctx
+            .get_address(
                 report,
                 ast_res.header_span,
-                decls,
                 defs,
-                &ctx,
-                res.reserve_size,
-                false)?;
-                
-            if let Some(pos) = ctx.get_output_position(defs)
-            {
-                overlap_checker.check_and_insert(
-                    report,
-                    ast_res.header_span,
-                    pos,
-                    res.reserve_size)?;
-            }
-        }
+                true)?
+            .unwrap();
+
+        output.reserved_spans.push(util::BitVecSpan {
+            offset: maybe_pos,
+            size: res.reserve_size,
+            addr,
+            span: ast_res.header_span,
+            bank_ref: ctx.bank_ref,
+            instr_ref: None,
+        });
     }
 
-    Ok(output)
+    Ok(())
 }
 
 
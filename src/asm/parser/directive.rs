@@ -8,7 +8,14 @@ pub fn parse(
     -> Result<asm::AstAny, ()>
 {
     let tk_hash = walker.expect(report, syntax::TokenKind::Hash)?;
-    let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+
+    // `struct` is a keyword in expressions
+    let tk_name = match walker.maybe_expect(syntax::TokenKind::KeywordStruct)
+    {
+        Some(tk_struct) => tk_struct,
+        None => walker.expect(report, syntax::TokenKind::Identifier)?,
+    };
+
     let header_span = tk_hash.span.join(tk_name.span);
 
     let name = walker.get_span_excerpt(tk_name.span).to_ascii_lowercase();
@@ -55,36 +62,72 @@ pub fn parse(
         "const" => Ok(asm::AstAny::Symbol(
             asm::parser::directive_const::parse(report, walker, header_span)?)),
             
+        "enum" => Ok(asm::AstAny::DirectiveNamespace(
+            asm::parser::directive_enum::parse(report, walker, header_span)?)),
+            
         "fn" => Ok(asm::AstAny::DirectiveFn(
             asm::parser::directive_fn::parse(report, walker, header_span)?)),
         
+        "for" => Ok(asm::AstAny::DirectiveRepeat(
+            asm::parser::directive_repeat::parse_for(report, opts, walker, header_span)?)),
+        
         "if" => Ok(asm::AstAny::DirectiveIf(
             asm::parser::directive_if::parse(report, opts, walker, header_span)?)),
         
         "include" => Ok(asm::AstAny::DirectiveInclude(
             asm::parser::directive_include::parse(report, walker, header_span)?)),
         
+        "layout" => Ok(asm::AstAny::DirectiveLayout(
+            asm::parser::directive_layout::parse(report, walker, header_span)?)),
+        
         "labelalign" => Ok(asm::AstAny::DirectiveLabelAlign(
             asm::parser::directive_labelalign::parse(report, walker, header_span)?)),
         
+        "macro" => Ok(asm::AstAny::DirectiveMacro(
+            asm::parser::directive_macro::parse(report, opts, walker, header_span)?)),
+        
+        "namespace" => Ok(asm::AstAny::DirectiveNamespace(
+            asm::parser::directive_namespace::parse(report, opts, walker, header_span)?)),
+        
         "noemit" => Ok(asm::AstAny::DirectiveNoEmit(
             asm::parser::directive_noemit::parse(report, walker, header_span)?)),
         
         "once" => Ok(asm::AstAny::DirectiveOnce(
             asm::parser::directive_once::parse(report, walker, header_span)?)),
             
+        "repeat" => Ok(asm::AstAny::DirectiveRepeat(
+            asm::parser::directive_repeat::parse_repeat(report, opts, walker, header_span)?)),
+        
         "res" => Ok(asm::AstAny::DirectiveRes(
             asm::parser::directive_res::parse(report, walker, header_span)?)),
         
         "ruledef" => Ok(asm::AstAny::DirectiveRuledef(
             asm::parser::directive_ruledef::parse(report, opts, walker, false, header_span)?)),
         
+        "section" => Ok(asm::AstAny::DirectiveSection(
+            asm::parser::directive_section::parse(report, walker, header_span)?)),
+        
+        "struct" => Ok(asm::AstAny::DirectiveNamespace(
+            asm::parser::directive_struct::parse(report, walker, header_span)?)),
+        
         "subruledef" => Ok(asm::AstAny::DirectiveRuledef(
             asm::parser::directive_ruledef::parse(report, opts, walker, true, header_span)?)),
         
+        "test" => Ok(asm::AstAny::DirectiveTest(
+            asm::parser::directive_test::parse(report, opts, walker, header_span)?)),
+        
         "assert" => Ok(asm::AstAny::DirectiveAssert(
             asm::parser::directive_assert::parse(report, walker, header_span)?)),
         
+        "export" =>
+        {
+            report.error_span(
+                "`#export` must be directly inside a `#namespace`",
+                header_span);
+            
+            Err(())
+        }
+        
         _ =>
         {
             report.error_span(
@@ -1,11 +1,14 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveAlign
 {
     pub header_span: diagn::Span,
     pub expr: expr::Expr,
+    pub macro_expansions: Vec<asm::AstMacroExpansion>,
+
+    #[serde(skip)]
 
     pub item_ref: Option<util::ItemRef<asm::AlignDirective>>,
 }
@@ -24,6 +27,7 @@ pub fn parse(
     Ok(AstDirectiveAlign {
         header_span,
         expr,
+        macro_expansions: Vec::new(),
 
         item_ref: None,
     })
@@ -1,11 +1,12 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveAssert
 {
     pub header_span: diagn::Span,
     pub condition_expr: expr::Expr,
+    pub macro_expansions: Vec<asm::AstMacroExpansion>,
 }
 
 
@@ -22,5 +23,6 @@ pub fn parse(
     Ok(AstDirectiveAssert {
         header_span,
         condition_expr: expr,
+        macro_expansions: Vec::new(),
     })
 }
\ No newline at end of file
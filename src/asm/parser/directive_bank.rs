@@ -1,13 +1,15 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveBank
 {
     pub header_span: diagn::Span,
     pub name_span: diagn::Span,
     pub name: String,
     
+    #[serde(skip)]
+    
     pub item_ref: Option<util::ItemRef::<asm::Bankdef>>,
 }
 
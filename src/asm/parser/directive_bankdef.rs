@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveBankdef
 {
     pub header_span: diagn::Span,
@@ -17,6 +17,8 @@ pub struct AstDirectiveBankdef
 	pub fill: bool,
 	pub userdata: Option<expr::Expr>,
     
+    #[serde(skip)]
+    
     pub item_ref: Option<util::ItemRef::<asm::Bankdef>>,
 }
 
@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveBits
 {
     pub header_span: diagn::Span,
@@ -58,6 +58,9 @@ pub fn parse(
             expr,
         }),
         no_emit,
+        keeps_ctx: false,
+        is_anonymous: false,
+        macro_expansions: Vec::new(),
 
         item_ref: None,
     })
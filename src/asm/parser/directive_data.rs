@@ -1,12 +1,15 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveData
 {
     pub header_span: diagn::Span,
     pub elem_size: Option<usize>,
     pub elems: Vec<expr::Expr>,
+    pub macro_expansions: Vec<asm::AstMacroExpansion>,
+
+    #[serde(skip)]
 
     pub item_refs: Vec<util::ItemRef<asm::DataElement>>,
 }
@@ -42,6 +45,7 @@ pub fn parse(
         header_span,
         elem_size,
         elems,
+        macro_expansions: Vec::new(),
 
         item_refs: Vec::new(),
     })
@@ -0,0 +1,83 @@
+use crate::*;
+
+
+/// Parses `#enum Name { A, B = 5, C }` into a namespace
+/// holding a constant per entry. Entries without a value
+/// follow the previous one, starting from zero.
+pub fn parse(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<asm::AstDirectiveNamespace, ()>
+{
+    let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let name = walker.get_span_excerpt(tk_name.span).to_string();
+
+    walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let mut nodes = Vec::new();
+    let mut prev_entry: Option<(diagn::Span, String)> = None;
+
+    while !walker.next_useful_is(0, syntax::TokenKind::BraceClose)
+    {
+        let tk_entry = walker.expect(report, syntax::TokenKind::Identifier)?;
+        let entry_name = walker.get_span_excerpt(tk_entry.span).to_string();
+
+        let expr = {
+            if walker.maybe_expect(syntax::TokenKind::Equal).is_some()
+            {
+                expr::parse(report, walker)?
+            }
+            else if let Some((prev_span, prev_name)) = prev_entry
+            {
+                expr::Expr::BinaryOp(
+                    tk_entry.span,
+                    tk_entry.span,
+                    expr::BinaryOp::Add,
+                    Box::new(expr::Expr::Variable(prev_span, prev_name)),
+                    Box::new(expr::Expr::Literal(
+                        tk_entry.span,
+                        expr::Value::make_integer(1))))
+            }
+            else
+            {
+                expr::Expr::Literal(
+                    tk_entry.span,
+                    expr::Value::make_integer(0))
+            }
+        };
+
+        nodes.push(asm::AstAny::Symbol(
+            asm::parser::directive_namespace::make_constant(
+                tk_entry.span,
+                0,
+                entry_name.clone(),
+                expr)));
+
+        prev_entry = Some((tk_entry.span, entry_name));
+
+        if walker.maybe_expect(syntax::TokenKind::Comma).is_none() &&
+            walker.maybe_expect_linebreak().is_none()
+        {
+            break;
+        }
+    }
+
+    let tk_brace_close = walker.expect(report, syntax::TokenKind::BraceClose)?;
+    walker.expect_linebreak(report)?;
+
+    Ok(asm::AstDirectiveNamespace {
+        header_span: header_span.join(tk_name.span),
+        name_span: tk_name.span,
+        name,
+        end_span: tk_brace_close.span,
+        kind: asm::AstNamespaceKind::Enum,
+
+        body: asm::AstTopLevel {
+            nodes,
+        },
+        exports: Vec::new(),
+
+        item_ref: None,
+    })
+}
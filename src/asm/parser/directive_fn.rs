@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveFn
 {
     pub header_span: diagn::Span,
@@ -10,11 +10,13 @@ pub struct AstDirectiveFn
     pub params: Vec<AstFnParameter>,
     pub body: expr::Expr,
 
+    #[serde(skip)]
+
     pub item_ref: Option<util::ItemRef<asm::Symbol>>,
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstFnParameter
 {
     pub name: String,
@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveInclude
 {
     pub header_span: diagn::Span,
@@ -0,0 +1,154 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveLayout
+{
+    pub header_span: diagn::Span,
+    pub entries: Vec<AstLayoutEntry>,
+}
+
+
+/// A line of a `#layout` block, as in
+/// `code => rom0, rom1, align = 0x100`.
+/// Options are always given as `key = value`,
+/// so that they can't be mistaken for bank names:
+///
+/// * `align`: alignment of the start address, in address units.
+/// * `keep`: when `false`, the section is dropped unless
+///   something placed outside of it references its contents.
+/// * `ordered`: when `true`, the section is never placed into
+///   a bank earlier than the one the previous section went into,
+///   instead of the first bank with enough free space left.
+/// * `discard`: when `true`, the section is resolved
+///   but never placed nor written to the output.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstLayoutEntry
+{
+    pub section_name_span: diagn::Span,
+    pub section_name: String,
+    pub banks: Vec<AstLayoutBank>,
+    pub align: Option<expr::Expr>,
+    pub keep: Option<expr::Expr>,
+    pub ordered: Option<expr::Expr>,
+    pub discard: Option<expr::Expr>,
+}
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstLayoutBank
+{
+    pub name_span: diagn::Span,
+    pub name: String,
+}
+
+
+pub fn parse(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveLayout, ()>
+{
+    walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let mut entries = Vec::new();
+
+    while !walker.next_useful_is(0, syntax::TokenKind::BraceClose)
+    {
+        entries.push(parse_entry(report, walker)?);
+
+        if !walker.maybe_expect_linebreak().is_some()
+        {
+            break;
+        }
+    }
+
+    walker.expect(report, syntax::TokenKind::BraceClose)?;
+    walker.expect_linebreak(report)?;
+
+    Ok(AstDirectiveLayout {
+        header_span,
+        entries,
+    })
+}
+
+
+fn parse_entry(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker)
+    -> Result<AstLayoutEntry, ()>
+{
+    let tk_section_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let section_name = walker.get_span_excerpt(tk_section_name.span).to_string();
+
+    walker.expect(report, syntax::TokenKind::HeavyArrowRight)?;
+
+    let mut entry = AstLayoutEntry {
+        section_name_span: tk_section_name.span,
+        section_name,
+        banks: Vec::new(),
+        align: None,
+        keep: None,
+        ordered: None,
+        discard: None,
+    };
+
+    loop
+    {
+        let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+        let name = walker.get_span_excerpt(tk_name.span).to_string();
+
+        if walker.maybe_expect(syntax::TokenKind::Equal).is_none()
+        {
+            entry.banks.push(AstLayoutBank {
+                name_span: tk_name.span,
+                name,
+            });
+        }
+        else
+        {
+            let option = match name.as_str()
+            {
+                "align" => &mut entry.align,
+                "keep" => &mut entry.keep,
+                "ordered" => &mut entry.ordered,
+                "discard" => &mut entry.discard,
+                _ =>
+                {
+                    report.error_span(
+                        format!("unknown layout option `{}`", name),
+                        tk_name.span);
+
+                    return Err(());
+                }
+            };
+
+            if option.is_some()
+            {
+                report.error_span(
+                    format!("duplicate layout option `{}`", name),
+                    tk_name.span);
+
+                return Err(());
+            }
+
+            *option = Some(expr::parse(report, walker)?);
+        }
+
+        if walker.maybe_expect(syntax::TokenKind::Comma).is_none()
+        {
+            break;
+        }
+    }
+
+    if entry.banks.len() == 0 && entry.discard.is_none()
+    {
+        report.error_span(
+            "expected at least one bank for section",
+            entry.section_name_span);
+
+        return Err(());
+    }
+
+    Ok(entry)
+}
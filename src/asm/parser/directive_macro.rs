@@ -0,0 +1,705 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveMacro
+{
+    pub header_span: diagn::Span,
+    pub name_span: diagn::Span,
+    pub name: String,
+    pub params: Vec<AstMacroParameter>,
+    pub body: asm::AstTopLevel,
+}
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstMacroParameter
+{
+    pub span: diagn::Span,
+    pub name: String,
+}
+
+
+/// The invocation site of a macro, kept on the expanded
+/// nodes so that later errors can point back to it.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstMacroExpansion
+{
+    pub span: diagn::Span,
+    pub name: String,
+}
+
+
+const EXPANSION_DEPTH_MAX: usize = 25;
+
+
+pub fn parse(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveMacro, ()>
+{
+    let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let name = walker.get_span_excerpt(tk_name.span).to_string();
+
+    walker.expect(report, syntax::TokenKind::ParenOpen)?;
+
+    let mut params: Vec<AstMacroParameter> = Vec::new();
+
+    while !walker.is_over() &&
+        !walker.next_useful_is(0, syntax::TokenKind::ParenClose)
+    {
+        let tk_param_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+        let param_name = walker.get_span_excerpt(tk_param_name.span).to_string();
+
+        if params.iter().any(|p| p.name == param_name)
+        {
+            report.error_span(
+                format!("duplicate macro parameter `{}`", param_name),
+                tk_param_name.span);
+
+            return Err(());
+        }
+
+        params.push(AstMacroParameter {
+            span: tk_param_name.span,
+            name: param_name,
+        });
+
+        walker.maybe_expect(syntax::TokenKind::Comma);
+    }
+
+    walker.expect(report, syntax::TokenKind::ParenClose)?;
+    walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let body = asm::parser::parse_nested_toplevel(
+        report,
+        opts,
+        walker)?;
+
+    walker.expect(report, syntax::TokenKind::BraceClose)?;
+
+    check_body(report, &body)?;
+
+    Ok(AstDirectiveMacro {
+        header_span,
+        name_span: tk_name.span,
+        name,
+        params,
+        body,
+    })
+}
+
+
+/// Symbols declared inside a macro are local to each
+/// expansion, so only top-level ones are accepted,
+/// just like in `asm` blocks.
+fn check_body(
+    report: &mut diagn::Report,
+    body: &asm::AstTopLevel)
+    -> Result<(), ()>
+{
+    for node in &body.nodes
+    {
+        match node
+        {
+            asm::AstAny::Symbol(ast_symbol) =>
+            {
+                if ast_symbol.hierarchy_level != 0
+                {
+                    report.error_span(
+                        "only top-level symbols are permitted in macros",
+                        ast_symbol.decl_span);
+
+                    return Err(());
+                }
+            }
+
+            asm::AstAny::DirectiveIf(ast_if) =>
+            {
+                check_body(report, &ast_if.true_arm)?;
+
+                if let Some(false_arm) = &ast_if.false_arm
+                {
+                    check_body(report, false_arm)?;
+                }
+            }
+
+            asm::AstAny::DirectiveRepeat(ast_repeat) =>
+                check_body(report, &ast_repeat.body)?,
+
+            asm::AstAny::DirectiveMacro(ast_macro) =>
+            {
+                report.error_span(
+                    "macros can only be declared at the top level",
+                    ast_macro.header_span);
+
+                return Err(());
+            }
+
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+
+struct MacroArgument
+{
+    text: String,
+    expr: Option<expr::Expr>,
+}
+
+
+struct MacroExpander
+{
+    macros: std::collections::HashMap<String, AstDirectiveMacro>,
+    expansions: Vec<AstMacroExpansion>,
+    expansion_count: usize,
+}
+
+
+/// Collects all top-level `#macro` declarations and
+/// replaces their invocations with the expanded bodies.
+pub fn expand_macros(
+    report: &mut diagn::Report,
+    ast: &mut asm::AstTopLevel)
+    -> Result<(), ()>
+{
+    let mut expander = MacroExpander {
+        macros: std::collections::HashMap::new(),
+        expansions: Vec::new(),
+        expansion_count: 0,
+    };
+
+    let mut nodes = Vec::new();
+
+    for node in std::mem::take(&mut ast.nodes)
+    {
+        let asm::AstAny::DirectiveMacro(ast_macro) = node
+            else {
+                nodes.push(node);
+                continue;
+            };
+
+        if let Some(prev_macro) = expander.macros.get(&ast_macro.name)
+        {
+            report.push_parent(
+                format!("duplicate macro `{}`", ast_macro.name),
+                ast_macro.name_span);
+
+            report.note_span(
+                "first declared here",
+                prev_macro.name_span);
+
+            report.pop_parent();
+
+            return Err(());
+        }
+
+        expander.macros.insert(
+            ast_macro.name.clone(),
+            ast_macro);
+    }
+
+    warn_shadowed_mnemonics(
+        report,
+        &nodes,
+        &expander.macros);
+
+    ast.nodes = expander.expand_nodes(
+        report,
+        nodes,
+        &asm::parser::substitution::SubstitutionMap::new())?;
+
+    Ok(())
+}
+
+
+/// Macro invocations are expanded before instruction
+/// matching, so a macro named after a ruledef mnemonic
+/// makes those rules unreachable.
+fn warn_shadowed_mnemonics(
+    report: &mut diagn::Report,
+    nodes: &[asm::AstAny],
+    macros: &std::collections::HashMap<String, AstDirectiveMacro>)
+{
+    let mut warned = std::collections::HashSet::new();
+
+    for node in nodes
+    {
+        let asm::AstAny::DirectiveRuledef(ast_ruledef) = node
+            else { continue };
+
+        if ast_ruledef.is_subruledef
+        {
+            continue;
+        }
+
+        for rule in &ast_ruledef.rules
+        {
+            let pattern_start = rule.pattern
+                .iter()
+                .map_while(|part| match part {
+                    asm::AstRulePatternPart::Exact(c) => Some(*c),
+                    _ => None,
+                })
+                .collect::<String>();
+
+            let (kind, length) = syntax::decide_next_token(&pattern_start);
+            if kind != syntax::TokenKind::Identifier
+            {
+                continue;
+            }
+
+            let mnemonic = &pattern_start[..length];
+
+            let Some(ast_macro) = macros
+                .values()
+                .find(|m| m.name.eq_ignore_ascii_case(mnemonic))
+                else { continue };
+
+            if !warned.insert(&ast_macro.name)
+            {
+                continue;
+            }
+
+            let mut msg = diagn::Message::warning_span(
+                format!(
+                    "macro `{}` shadows instructions with the same mnemonic",
+                    ast_macro.name),
+                ast_macro.name_span);
+
+            msg.inner.push(diagn::Message::short_note_span(
+                "rule never matched, since the macro is expanded instead",
+                rule.pattern_span));
+
+            report.message(msg);
+        }
+    }
+}
+
+
+/// Attaches the chain of macro invocations, innermost first,
+/// as notes under each message reported since `first_msg`.
+pub fn note_expansions(
+    report: &mut diagn::Report,
+    first_msg: usize,
+    expansions: &[AstMacroExpansion])
+{
+    for msg in &mut report.messages_mut()[first_msg..]
+    {
+        for expansion in expansions.iter().rev()
+        {
+            msg.inner.push(diagn::Message::note_span(
+                format!("in expansion of macro `{}`", expansion.name),
+                expansion.span));
+        }
+    }
+}
+
+
+/// Macro arguments are substituted into the text of an
+/// instruction, so spans past an argument don't line up with
+/// the source anymore. Clamps them to the instruction's span.
+pub fn clamp_expanded_spans(
+    report: &mut diagn::Report,
+    first_msg: usize,
+    ast_instr: &asm::AstInstruction)
+{
+    if ast_instr.macro_expansions.len() == 0
+    {
+        return;
+    }
+
+    let Some((start, end)) = ast_instr.span.location()
+        else { return };
+
+    for msg in &mut report.messages_mut()[first_msg..]
+    {
+        clamp_spans(msg, ast_instr.span.file_handle, start, end, start + ast_instr.src.len());
+    }
+}
+
+
+fn clamp_spans(
+    msg: &mut diagn::Message,
+    file_handle: util::FileServerHandle,
+    start: usize,
+    end: usize,
+    src_end: usize)
+{
+    if let Some(span) = &mut msg.span
+    {
+        if let Some((span_start, span_end)) = span.location()
+        {
+            if span.file_handle == file_handle &&
+                span_start >= start &&
+                span_start <= src_end
+            {
+                *span = diagn::Span::new(
+                    file_handle,
+                    span_start.min(end),
+                    span_end.min(end));
+            }
+        }
+    }
+
+    for inner in &mut msg.inner
+    {
+        clamp_spans(inner, file_handle, start, end, src_end);
+    }
+}
+
+
+impl MacroExpander
+{
+    fn expand_nodes(
+        &mut self,
+        report: &mut diagn::Report,
+        nodes: Vec<asm::AstAny>,
+        substs: &asm::parser::substitution::SubstitutionMap)
+        -> Result<Vec<asm::AstAny>, ()>
+    {
+        let mut result = Vec::new();
+
+        for mut node in nodes
+        {
+            if let asm::AstAny::DirectiveMacro(ast_macro) = &node
+            {
+                report.error_span(
+                    "macros can only be declared at the top level",
+                    ast_macro.header_span);
+
+                return Err(());
+            }
+
+            if let asm::AstAny::Instruction(ast_instr) = &node
+            {
+                if let Some(ast_macro) = self.find_invocation(ast_instr)
+                {
+                    let expanded = self.expand_invocation(
+                        report,
+                        ast_instr,
+                        ast_macro,
+                        substs)?;
+
+                    result.extend(expanded);
+                    continue;
+                }
+            }
+
+            if let asm::AstAny::DirectiveRepeat(ast_repeat) = &mut node
+            {
+                ast_repeat.body.nodes = self.expand_nodes(
+                    report,
+                    std::mem::take(&mut ast_repeat.body.nodes),
+                    substs)?;
+            }
+
+            if let asm::AstAny::DirectiveIf(ast_if) = &mut node
+            {
+                ast_if.true_arm.nodes = self.expand_nodes(
+                    report,
+                    std::mem::take(&mut ast_if.true_arm.nodes),
+                    substs)?;
+
+                if let Some(false_arm) = &mut ast_if.false_arm
+                {
+                    false_arm.nodes = self.expand_nodes(
+                        report,
+                        std::mem::take(&mut false_arm.nodes),
+                        substs)?;
+                }
+            }
+
+            asm::parser::substitution::substitute_node_shallow(
+                report,
+                &mut node,
+                substs)?;
+
+            if let Some(macro_expansions) = node.macro_expansions_mut()
+            {
+                *macro_expansions = self.expansions.clone();
+            }
+
+            result.push(node);
+        }
+
+        Ok(result)
+    }
+
+
+    fn find_invocation(
+        &self,
+        ast_instr: &asm::AstInstruction)
+        -> Option<AstDirectiveMacro>
+    {
+        if self.macros.len() == 0
+        {
+            return None;
+        }
+
+        let walker = make_instruction_walker(ast_instr);
+
+        let tk_name = walker.next_nth_useful_token(0);
+        if tk_name.kind != syntax::TokenKind::Identifier
+        {
+            return None;
+        }
+
+        let name = walker.get_span_excerpt(tk_name.span);
+
+        self.macros.get(name).cloned()
+    }
+
+
+    fn expand_invocation(
+        &mut self,
+        report: &mut diagn::Report,
+        ast_instr: &asm::AstInstruction,
+        ast_macro: AstDirectiveMacro,
+        substs: &asm::parser::substitution::SubstitutionMap)
+        -> Result<Vec<asm::AstAny>, ()>
+    {
+        let args = parse_invocation_args(
+            report,
+            ast_instr)?;
+
+        if args.len() != ast_macro.params.len()
+        {
+            let plural = {
+                if ast_macro.params.len() != 1
+                    { "s" }
+                else
+                    { "" }
+            };
+
+            report.push_parent(
+                format!(
+                    "macro expected {} argument{} (but got {})",
+                    ast_macro.params.len(),
+                    plural,
+                    args.len()),
+                ast_instr.span);
+
+            report.note_span(
+                "macro declared here",
+                ast_macro.name_span);
+
+            report.pop_parent();
+
+            return Err(());
+        }
+
+        let first_msg = report.len();
+
+        let expansion = AstMacroExpansion {
+            span: ast_instr.span,
+            name: ast_macro.name.clone(),
+        };
+
+        let result = self.expand_body(
+            report,
+            ast_instr,
+            ast_macro,
+            args,
+            substs);
+
+        note_expansions(
+            report,
+            first_msg,
+            &[expansion]);
+
+        result
+    }
+
+
+    fn expand_body(
+        &mut self,
+        report: &mut diagn::Report,
+        ast_instr: &asm::AstInstruction,
+        ast_macro: AstDirectiveMacro,
+        args: Vec<MacroArgument>,
+        substs: &asm::parser::substitution::SubstitutionMap)
+        -> Result<Vec<asm::AstAny>, ()>
+    {
+        if self.expansions.len() >= EXPANSION_DEPTH_MAX
+        {
+            report.error_span(
+                "recursion depth limit reached",
+                ast_instr.span);
+
+            return Err(());
+        }
+
+        self.expansion_count += 1;
+
+        let mut inner_substs = asm::parser::substitution::SubstitutionMap::new();
+
+        for (param, arg) in ast_macro.params.iter().zip(args.into_iter())
+        {
+            let text = asm::parser::substitution::substitute_text(&arg.text, substs);
+
+            let expr = match arg.expr
+            {
+                Some(mut expr) =>
+                {
+                    asm::parser::substitution::substitute_expr(report, &mut expr, substs)?;
+                    Some(expr)
+                }
+                None => None,
+            };
+
+            inner_substs.insert(
+                param.name.clone(),
+                asm::parser::substitution::Substitution::Argument {
+                    text,
+                    expr,
+                });
+        }
+
+        let mut local_names = Vec::new();
+        asm::parser::substitution::collect_local_symbols(&ast_macro.body, &mut local_names);
+
+        for name in local_names
+        {
+            let unique_name = format!(
+                "__macro_{}_{}_{}",
+                ast_macro.name,
+                self.expansion_count,
+                name);
+
+            inner_substs.insert(
+                name,
+                asm::parser::substitution::Substitution::Symbol(unique_name));
+        }
+
+        self.expansions.push(AstMacroExpansion {
+            span: ast_instr.span,
+            name: ast_macro.name.clone(),
+        });
+
+        let result = self.expand_nodes(
+            report,
+            ast_macro.body.nodes,
+            &inner_substs);
+
+        self.expansions.pop();
+
+        result
+    }
+}
+
+
+fn make_instruction_walker(
+    ast_instr: &asm::AstInstruction)
+    -> syntax::Walker<'_>
+{
+    let span_offset = ast_instr.span
+        .location()
+        .map(|loc| loc.0)
+        .unwrap_or(0);
+
+    syntax::Walker::new(
+        &ast_instr.src,
+        ast_instr.span.file_handle,
+        span_offset)
+}
+
+
+fn parse_invocation_args(
+    report: &mut diagn::Report,
+    ast_instr: &asm::AstInstruction)
+    -> Result<Vec<MacroArgument>, ()>
+{
+    let mut walker = make_instruction_walker(ast_instr);
+
+    walker.expect(report, syntax::TokenKind::Identifier)?;
+    walker.skip_ignorable();
+
+    let mut args = Vec::new();
+
+    while !walker.is_over()
+    {
+        let mut nesting = 0;
+        let mut arg_span: Option<diagn::Span> = None;
+
+        while !walker.is_over()
+        {
+            let token = walker.next_token();
+
+            match token.kind
+            {
+                syntax::TokenKind::Comma if nesting == 0 => break,
+
+                syntax::TokenKind::ParenOpen |
+                syntax::TokenKind::BracketOpen |
+                syntax::TokenKind::BraceOpen =>
+                    nesting += 1,
+
+                syntax::TokenKind::ParenClose |
+                syntax::TokenKind::BracketClose |
+                syntax::TokenKind::BraceClose =>
+                    nesting -= 1,
+
+                _ => {}
+            }
+
+            if !token.kind.is_ignorable()
+            {
+                arg_span = Some(arg_span
+                    .map_or(token.span, |s| s.join(token.span)));
+            }
+
+            walker.advance_to_token_end(&token);
+        }
+
+        let Some(arg_span) = arg_span
+            else {
+                report.error_span(
+                    "expected macro argument",
+                    walker.get_cursor_span());
+
+                return Err(());
+            };
+
+        let text = walker.get_span_excerpt(arg_span).to_string();
+
+        let expr = {
+            let mut arg_walker = syntax::Walker::new(
+                &text,
+                arg_span.file_handle,
+                arg_span.location().unwrap().0);
+
+            expr::parse_optional(&mut arg_walker)
+                .filter(|_| {
+                    arg_walker.skip_ignorable();
+                    arg_walker.is_over()
+                })
+        };
+
+        args.push(MacroArgument {
+            text,
+            expr,
+        });
+
+        if walker.maybe_expect(syntax::TokenKind::Comma).is_some()
+        {
+            walker.skip_ignorable();
+
+            if walker.is_over()
+            {
+                report.error_span(
+                    "expected macro argument",
+                    walker.get_cursor_span());
+
+                return Err(());
+            }
+        }
+    }
+
+    Ok(args)
+}
@@ -0,0 +1,179 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveNamespace
+{
+    pub header_span: diagn::Span,
+    pub name_span: diagn::Span,
+    pub name: String,
+    pub end_span: diagn::Span,
+    pub kind: AstNamespaceKind,
+
+    /// Left empty once spliced into the enclosing
+    /// top-level, along with the exports
+    pub body: asm::AstTopLevel,
+    pub exports: Vec<asm::AstSymbol>,
+
+    #[serde(skip)]
+
+    pub item_ref: Option<util::ItemRef::<asm::Symbol>>,
+}
+
+
+/// `#enum` and `#struct` are namespaces
+/// holding a generated constant per entry
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum AstNamespaceKind
+{
+    Namespace,
+    Enum,
+    Struct(AstNamespaceStruct),
+}
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstNamespaceStruct
+{
+    /// A zero sliced to the total size of the fields,
+    /// so that `Name.size` and `$sizeof(Name)` give it in bits
+    pub value_expr: expr::Expr,
+}
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveNamespaceEnd
+{
+    pub span: diagn::Span,
+}
+
+
+pub fn parse(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveNamespace, ()>
+{
+    let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let name = walker.get_span_excerpt(tk_name.span).to_string();
+
+    walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let mut nodes = Vec::new();
+    let mut exports = Vec::new();
+
+    while !walker.is_over() &&
+        !walker.next_useful_is(0, syntax::TokenKind::BraceClose)
+    {
+        if is_export(walker)
+        {
+            parse_export(
+                report,
+                walker,
+                tk_name.span,
+                &name,
+                &mut exports)?;
+        }
+        else if let Some(node) = asm::parser::parse_line(report, opts, walker)?
+        {
+            asm::parser::push_flattened(&mut nodes, node);
+        }
+    }
+
+    let tk_brace_close = walker.expect(report, syntax::TokenKind::BraceClose)?;
+
+    Ok(AstDirectiveNamespace {
+        header_span: header_span.join(tk_name.span),
+        name_span: tk_name.span,
+        name,
+        end_span: tk_brace_close.span,
+        kind: AstNamespaceKind::Namespace,
+
+        body: asm::AstTopLevel {
+            nodes,
+        },
+        exports,
+
+        item_ref: None,
+    })
+}
+
+
+fn is_export(walker: &syntax::Walker) -> bool
+{
+    let tk_hash = walker.next_nth_useful_token(0);
+    let tk_name = walker.next_nth_useful_token(1);
+
+    tk_hash.kind == syntax::TokenKind::Hash &&
+        tk_name.kind == syntax::TokenKind::Identifier &&
+        walker.get_span_excerpt(tk_name.span).eq_ignore_ascii_case("export")
+}
+
+
+/// Parses `#export a, b`, turning each name into a constant
+/// declared after the namespace, like `a = namespace.a`.
+fn parse_export(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    namespace_span: diagn::Span,
+    namespace_name: &str,
+    exports: &mut Vec<asm::AstSymbol>)
+    -> Result<(), ()>
+{
+    walker.expect(report, syntax::TokenKind::Hash)?;
+    walker.expect(report, syntax::TokenKind::Identifier)?;
+
+    loop
+    {
+        let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+        let name = walker.get_span_excerpt(tk_name.span).to_string();
+
+        let mut export = make_constant(
+            tk_name.span,
+            0,
+            name.clone(),
+            expr::Expr::MemberAccess {
+                span: tk_name.span,
+                lhs: Box::new(expr::Expr::Variable(
+                    namespace_span,
+                    namespace_name.to_string())),
+                member_name: name,
+            });
+
+        export.keeps_ctx = true;
+        exports.push(export);
+
+        if walker.maybe_expect(syntax::TokenKind::Comma).is_none()
+        {
+            break;
+        }
+    }
+
+    walker.expect_linebreak(report)?;
+    Ok(())
+}
+
+
+pub fn make_constant(
+    decl_span: diagn::Span,
+    hierarchy_level: usize,
+    name: String,
+    expr: expr::Expr)
+    -> asm::AstSymbol
+{
+    asm::AstSymbol {
+        decl_span,
+        hierarchy_level,
+        name,
+        kind: asm::AstSymbolKind::Constant(asm::AstSymbolConstant {
+            expr,
+        }),
+        no_emit: false,
+        keeps_ctx: false,
+        is_anonymous: false,
+        macro_expansions: Vec::new(),
+
+        item_ref: None,
+    }
+}
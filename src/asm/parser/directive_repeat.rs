@@ -0,0 +1,259 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveRepeat
+{
+    pub header_span: diagn::Span,
+    pub index_name: Option<String>,
+    pub start_expr: Option<expr::Expr>,
+    pub end_expr: expr::Expr,
+
+    pub body: asm::AstTopLevel,
+}
+
+
+pub fn parse_repeat(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveRepeat, ()>
+{
+    let count_expr = expr::parse(report, walker)?;
+
+    let body = parse_braced_block(report, opts, walker)?;
+
+    Ok(AstDirectiveRepeat {
+        header_span,
+        index_name: None,
+        start_expr: None,
+        end_expr: count_expr,
+
+        body,
+    })
+}
+
+
+pub fn parse_for(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveRepeat, ()>
+{
+    let tk_index_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let index_name = walker.get_span_excerpt(tk_index_name.span).to_string();
+
+    let tk_in = walker.expect(report, syntax::TokenKind::Identifier)?;
+    if walker.get_span_excerpt(tk_in.span) != "in"
+    {
+        report.error_span(
+            "expected `in`",
+            tk_in.span);
+
+        return Err(());
+    }
+
+    // Find the `..` separator first, since the expression
+    // parser would otherwise take it as a member access
+    let tk_dots = find_range_separator(report, walker)?;
+
+    let mut start_walker = walker.slice(
+        walker.get_cursor_index(),
+        walker.get_index_at_span_start(tk_dots.span));
+
+    let start_expr = expr::parse(report, &mut start_walker)?;
+
+    start_walker.skip_ignorable();
+    if !start_walker.is_over()
+    {
+        report.error_span(
+            "expected `..`",
+            start_walker.get_cursor_span());
+
+        return Err(());
+    }
+
+    walker.advance_to_token_end(&tk_dots);
+
+    let end_expr = expr::parse(report, walker)?;
+
+    let body = parse_braced_block(report, opts, walker)?;
+
+    Ok(AstDirectiveRepeat {
+        header_span,
+        index_name: Some(index_name),
+        start_expr: Some(start_expr),
+        end_expr,
+
+        body,
+    })
+}
+
+
+/// Returns a token spanning both dots of the separator.
+fn find_range_separator(
+    report: &mut diagn::Report,
+    walker: &syntax::Walker)
+    -> Result<syntax::Token, ()>
+{
+    let mut nth = 0;
+
+    loop
+    {
+        let token = walker.next_nth_token(nth);
+
+        match token.kind
+        {
+            syntax::TokenKind::Dot =>
+            {
+                let next_token = walker.next_nth_token(nth + 1);
+
+                if next_token.kind == syntax::TokenKind::Dot
+                {
+                    return Ok(syntax::Token {
+                        kind: syntax::TokenKind::Dot,
+                        span: token.span.join(next_token.span),
+                    });
+                }
+            }
+
+            syntax::TokenKind::BraceOpen |
+            syntax::TokenKind::LineBreak =>
+            {
+                report.error_span(
+                    "expected `..`",
+                    token.span);
+
+                return Err(());
+            }
+
+            _ => {}
+        }
+
+        nth += 1;
+    }
+}
+
+
+fn parse_braced_block(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    walker: &mut syntax::Walker)
+    -> Result<asm::AstTopLevel, ()>
+{
+    walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let block = asm::parser::parse_nested_toplevel(
+        report,
+        opts,
+        walker)?;
+
+    walker.expect(report, syntax::TokenKind::BraceClose)?;
+
+    check_body(report, &block)?;
+
+    Ok(block)
+}
+
+
+/// Symbols declared inside a loop are local to each
+/// iteration, so only top-level ones are accepted.
+fn check_body(
+    report: &mut diagn::Report,
+    body: &asm::AstTopLevel)
+    -> Result<(), ()>
+{
+    for node in &body.nodes
+    {
+        match node
+        {
+            asm::AstAny::Symbol(ast_symbol) =>
+            {
+                if ast_symbol.hierarchy_level != 0
+                {
+                    report.error_span(
+                        "only top-level symbols are permitted in loops",
+                        ast_symbol.decl_span);
+
+                    return Err(());
+                }
+            }
+
+            asm::AstAny::DirectiveIf(ast_if) =>
+            {
+                check_body(report, &ast_if.true_arm)?;
+
+                if let Some(false_arm) = &ast_if.false_arm
+                {
+                    check_body(report, false_arm)?;
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+
+impl AstDirectiveRepeat
+{
+    /// Produces a copy of the body for a single iteration,
+    /// with the loop index bound to its value and every symbol
+    /// renamed to one local to the iteration.
+    pub fn instantiate(
+        &self,
+        report: &mut diagn::Report,
+        iteration: usize,
+        index: &util::BigInt)
+        -> Result<Vec<asm::AstAny>, ()>
+    {
+        let mut substs = asm::parser::substitution::SubstitutionMap::new();
+
+        if let Some(ref index_name) = self.index_name
+        {
+            let index_expr = expr::Expr::Literal(
+                self.header_span,
+                expr::Value::make_integer(index.clone())
+                    .statically_known());
+
+            substs.insert(
+                index_name.clone(),
+                asm::parser::substitution::Substitution::Argument {
+                    text: index.to_str_radix(10),
+                    expr: Some(index_expr),
+                });
+        }
+
+        let mut local_names = Vec::new();
+        asm::parser::substitution::collect_local_symbols(
+            &self.body,
+            &mut local_names);
+
+        for name in local_names
+        {
+            let unique_name = format!(
+                "__repeat_{}_{}_{}_{}",
+                self.header_span.file_handle,
+                self.header_span.location().map_or(0, |loc| loc.0),
+                iteration,
+                name);
+
+            substs.insert(
+                name,
+                asm::parser::substitution::Substitution::Symbol(unique_name));
+        }
+
+        let mut nodes = self.body.nodes.clone();
+
+        asm::parser::substitution::substitute_nodes(
+            report,
+            &mut nodes,
+            &substs)?;
+
+        Ok(nodes)
+    }
+}
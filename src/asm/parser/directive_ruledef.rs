@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstDirectiveRuledef
 {
     pub header_span: diagn::Span,
@@ -10,11 +10,13 @@ pub struct AstDirectiveRuledef
     pub name: Option<String>,
     pub rules: Vec<AstRule>,
 
+    #[serde(skip)]
+
     pub item_ref: Option<util::ItemRef::<asm::Ruledef>>,
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstRule
 {
     pub pattern_span: diagn::Span,
@@ -23,7 +25,7 @@ pub struct AstRule
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum AstRulePatternPart
 {
     Whitespace,
@@ -32,7 +34,7 @@ pub enum AstRulePatternPart
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstRuleParameter
 {
     pub name_span: diagn::Span,
@@ -42,7 +44,7 @@ pub struct AstRuleParameter
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum AstRuleParameterType
 {
     Unspecified,
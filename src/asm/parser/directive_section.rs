@@ -0,0 +1,36 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveSection
+{
+    pub header_span: diagn::Span,
+    pub name_span: diagn::Span,
+    pub name: String,
+    
+    #[serde(skip)]
+    
+    pub item_ref: Option<util::ItemRef::<asm::Bankdef>>,
+}
+
+
+pub fn parse(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveSection, ()>
+{
+    let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let name = walker.get_span_excerpt(tk_name.span).to_string();
+    let name_span = tk_name.span;
+
+    walker.expect_linebreak(report)?;
+
+    Ok(AstDirectiveSection {
+        header_span,
+        name_span,
+        name,
+
+        item_ref: None,
+    })
+}
@@ -0,0 +1,110 @@
+use crate::*;
+
+
+/// Parses `#struct Name { field: 16, other: 8 }` into a namespace
+/// holding a constant per field with its offset in bits,
+/// each with a `size` child constant with its width in bits.
+///
+/// Like `$sizeof`, `Name.size` is in bits, while `Name.units`
+/// is in address units of the current bank, as needed by
+/// `#res Name.units` or when indexing arrays of the struct.
+pub fn parse(
+    report: &mut diagn::Report,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<asm::AstDirectiveNamespace, ()>
+{
+    let tk_name = walker.expect(report, syntax::TokenKind::Identifier)?;
+    let name = walker.get_span_excerpt(tk_name.span).to_string();
+
+    walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let mut nodes = Vec::new();
+    let mut end_offset = expr::Expr::Literal(
+        tk_name.span,
+        expr::Value::make_integer(0));
+
+    while !walker.next_useful_is(0, syntax::TokenKind::BraceClose)
+    {
+        let tk_field = walker.expect(report, syntax::TokenKind::Identifier)?;
+        let field_name = walker.get_span_excerpt(tk_field.span).to_string();
+
+        if field_name == "size" || field_name == "units"
+        {
+            report.error_span(
+                format!(
+                    "field name `{}` is reserved for the size of the struct",
+                    field_name),
+                tk_field.span);
+
+            return Err(());
+        }
+
+        walker.expect(report, syntax::TokenKind::Colon)?;
+        let size_expr = expr::parse(report, walker)?;
+
+        nodes.push(asm::AstAny::Symbol(
+            asm::parser::directive_namespace::make_constant(
+                tk_field.span,
+                0,
+                field_name.clone(),
+                end_offset)));
+
+        nodes.push(asm::AstAny::Symbol(
+            asm::parser::directive_namespace::make_constant(
+                size_expr.span(),
+                1,
+                "size".to_string(),
+                size_expr)));
+
+        // The next field starts at `field + field.size`
+        let field_var = expr::Expr::Variable(
+            tk_field.span,
+            field_name);
+
+        end_offset = expr::Expr::BinaryOp(
+            tk_field.span,
+            tk_field.span,
+            expr::BinaryOp::Add,
+            Box::new(field_var.clone()),
+            Box::new(expr::Expr::MemberAccess {
+                span: tk_field.span,
+                lhs: Box::new(field_var),
+                member_name: "size".to_string(),
+            }));
+
+        if walker.maybe_expect(syntax::TokenKind::Comma).is_none() &&
+            walker.maybe_expect_linebreak().is_none()
+        {
+            break;
+        }
+    }
+
+    let tk_brace_close = walker.expect(report, syntax::TokenKind::BraceClose)?;
+    walker.expect_linebreak(report)?;
+
+    let value_expr = expr::Expr::SliceShort(
+        tk_name.span,
+        tk_name.span,
+        Box::new(end_offset),
+        Box::new(expr::Expr::Literal(
+            tk_name.span,
+            expr::Value::make_integer(0))));
+
+    Ok(asm::AstDirectiveNamespace {
+        header_span: header_span.join(tk_name.span),
+        name_span: tk_name.span,
+        name,
+        end_span: tk_brace_close.span,
+        kind: asm::AstNamespaceKind::Struct(asm::AstNamespaceStruct {
+            value_expr,
+        }),
+
+        body: asm::AstTopLevel {
+            nodes,
+        },
+        exports: Vec::new(),
+
+        item_ref: None,
+    })
+}
@@ -0,0 +1,49 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct AstDirectiveTest
+{
+    pub header_span: diagn::Span,
+    pub name_span: diagn::Span,
+    pub name: String,
+
+    /// Spans the braces, for finding the
+    /// expectations written in comments
+    pub body_span: diagn::Span,
+    pub body: asm::AstTopLevel,
+}
+
+
+pub fn parse(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    walker: &mut syntax::Walker,
+    header_span: diagn::Span)
+    -> Result<AstDirectiveTest, ()>
+{
+    let tk_name = walker.expect(report, syntax::TokenKind::String)?;
+
+    let name = syntax::excerpt_as_string_contents(
+        report,
+        tk_name.span,
+        walker.get_span_excerpt(tk_name.span))?;
+
+    let tk_brace_open = walker.expect(report, syntax::TokenKind::BraceOpen)?;
+
+    let body = asm::parser::parse_nested_toplevel(
+        report,
+        opts,
+        walker)?;
+
+    let tk_brace_close = walker.expect(report, syntax::TokenKind::BraceClose)?;
+
+    Ok(AstDirectiveTest {
+        header_span: header_span.join(tk_name.span),
+        name_span: tk_name.span,
+        name,
+
+        body_span: tk_brace_open.span.join(tk_brace_close.span),
+        body,
+    })
+}
@@ -1,11 +1,14 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstInstruction
 {
     pub span: diagn::Span,
     pub src: String,
+    pub macro_expansions: Vec<asm::AstMacroExpansion>,
+
+    #[serde(skip)]
 
     pub item_ref: Option<util::ItemRef<asm::Instruction>>,
 }
@@ -25,6 +28,7 @@ pub fn parse(
     Ok(AstInstruction {
         span: line.get_full_span(),
         src: line.get_full_excerpt().to_string(),
+        macro_expansions: Vec::new(),
 
         item_ref: None,
     })
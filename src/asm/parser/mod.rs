@@ -26,6 +26,8 @@ mod directive_const;
 mod directive_data;
 pub use directive_data::AstDirectiveData;
 
+mod directive_enum;
+
 mod directive_fn;
 pub use directive_fn::{
     AstDirectiveFn,
@@ -41,12 +43,40 @@ pub use directive_include::AstDirectiveInclude;
 mod directive_labelalign;
 pub use directive_labelalign::AstDirectiveLabelAlign;
 
+mod directive_layout;
+pub use directive_layout::{
+    AstDirectiveLayout,
+    AstLayoutEntry,
+    AstLayoutBank,
+};
+
+mod directive_macro;
+pub use directive_macro::{
+    AstDirectiveMacro,
+    AstMacroParameter,
+    AstMacroExpansion,
+    expand_macros,
+    note_expansions,
+    clamp_expanded_spans,
+};
+
+mod directive_namespace;
+pub use directive_namespace::{
+    AstDirectiveNamespace,
+    AstDirectiveNamespaceEnd,
+    AstNamespaceKind,
+    AstNamespaceStruct,
+};
+
 mod directive_noemit;
 pub use directive_noemit::AstDirectiveNoEmit;
 
 mod directive_once;
 pub use directive_once::AstDirectiveOnce;
 
+mod directive_repeat;
+pub use directive_repeat::AstDirectiveRepeat;
+
 mod directive_res;
 pub use directive_res::AstDirectiveRes;
 
@@ -59,6 +89,14 @@ pub use directive_ruledef::{
     AstRuleParameterType,
 };
 
+mod directive_section;
+pub use directive_section::AstDirectiveSection;
+
+mod directive_struct;
+
+mod directive_test;
+pub use directive_test::AstDirectiveTest;
+
 mod fields;
 pub use fields::{
     AstFields,
@@ -68,6 +106,8 @@ pub use fields::{
 mod instruction;
 pub use instruction::AstInstruction;
 
+mod substitution;
+
 mod symbol;
 pub use symbol::{
     AstSymbol,
@@ -76,7 +116,7 @@ pub use symbol::{
 };
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum AstAny
 {
     DirectiveAddr(AstDirectiveAddr),
@@ -90,16 +130,23 @@ pub enum AstAny
     DirectiveIf(AstDirectiveIf),
     DirectiveInclude(AstDirectiveInclude),
     DirectiveLabelAlign(AstDirectiveLabelAlign),
+    DirectiveLayout(AstDirectiveLayout),
+    DirectiveMacro(AstDirectiveMacro),
+    DirectiveNamespace(AstDirectiveNamespace),
+    DirectiveNamespaceEnd(AstDirectiveNamespaceEnd),
     DirectiveNoEmit(AstDirectiveNoEmit),
     DirectiveOnce(AstDirectiveOnce),
+    DirectiveRepeat(AstDirectiveRepeat),
     DirectiveRes(AstDirectiveRes),
     DirectiveRuledef(AstDirectiveRuledef),
+    DirectiveSection(AstDirectiveSection),
+    DirectiveTest(AstDirectiveTest),
     Instruction(AstInstruction),
     Symbol(AstSymbol),
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstTopLevel
 {
     pub nodes: Vec<AstAny>,
@@ -166,12 +213,12 @@ pub fn parse_and_resolve_includes<S>(
         span,
         file_handle)?;
 
-    let mut walker = syntax::Walker::new(
-        &src,
+    let mut root_ast = parse_file_cached(
+        report,
+        opts,
+        root_filename.borrow(),
         file_handle,
-        0);
-
-    let mut root_ast = parse(report, opts, &mut walker)?;
+        &src)?;
 
     // Check presence of an #once directive
     if root_ast.nodes.iter().any(|n| matches!(n, AstAny::DirectiveOnce(_)))
@@ -188,8 +235,10 @@ pub fn parse_and_resolve_includes<S>(
 
         if let AstAny::DirectiveInclude(ast_include) = node
         {
-            let included_filename = util::filename_navigate(
+            let included_filename = find_included_file(
                 report,
+                opts,
+                fileserver,
                 ast_include.filename_span,
                 root_filename.borrow(),
                 &ast_include.filename)?;
@@ -238,6 +287,121 @@ pub fn parse_and_resolve_includes<S>(
 }
 
 
+/// Resolves an `#include` relative to the including file,
+/// or else to the first of `opts.include_paths` that has it,
+/// noting every directory searched if none do.
+fn find_included_file(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    span: diagn::Span,
+    current_filename: &str,
+    filename: &str)
+    -> Result<String, ()>
+{
+    let local_filename = util::filename_navigate(
+        report,
+        span,
+        current_filename,
+        filename)?;
+
+    if util::is_std_path(&local_filename) ||
+        fileserver.get_handle(&mut diagn::Report::new(), None, &local_filename).is_ok()
+    {
+        return Ok(local_filename);
+    }
+
+    for include_path in &opts.include_paths
+    {
+        let candidate = format!(
+            "{}/{}",
+            include_path.trim_end_matches(['/', '\\']),
+            filename.replace("\\", "/").trim_start_matches("./"));
+
+        if fileserver.get_handle(&mut diagn::Report::new(), None, &candidate).is_ok()
+        {
+            return Ok(candidate);
+        }
+    }
+
+    if opts.include_paths.len() == 0
+    {
+        return Ok(local_filename);
+    }
+
+    let local_dir = std::path::Path::new(current_filename)
+        .parent()
+        .map(|dir| dir.to_string_lossy().replace("\\", "/"))
+        .filter(|dir| dir.len() > 0)
+        .unwrap_or(".".to_string());
+
+    let searched_dirs = std::iter::once(&local_dir)
+        .chain(opts.include_paths.iter())
+        .map(|dir| format!("`{}`", dir))
+        .collect::<Vec<_>>();
+
+    report.push_parent(
+        format!("file not found: `{}`", filename),
+        span);
+
+    report.note(format!(
+        "searched in: {}",
+        searched_dirs.join(", ")));
+
+    report.pop_parent();
+
+    Err(())
+}
+
+
+/// Parses a whole file, reusing the AST from
+/// `opts.cache` if the file hasn't changed.
+fn parse_file_cached(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    filename: &str,
+    file_handle: util::FileServerHandle,
+    src: &str)
+    -> Result<AstTopLevel, ()>
+{
+    if let Some(cache) = &opts.cache
+    {
+        let cached_ast = cache
+            .lock()
+            .unwrap()
+            .get_parsed_file(opts, filename, file_handle, src);
+
+        if let Some(ast) = cached_ast
+        {
+            return Ok(ast);
+        }
+    }
+
+    let mut walker = syntax::Walker::new(
+        src,
+        file_handle,
+        0);
+
+    let messages_before = report.len();
+
+    let ast = parse(report, opts, &mut walker)?;
+
+    // Warnings wouldn't be reported again on a cache hit
+    if let Some(cache) = &opts.cache
+    {
+        if report.len() == messages_before
+        {
+            cache
+                .lock()
+                .unwrap()
+                .insert_parsed_file(opts, filename, file_handle, src, &ast);
+        }
+    }
+
+    Ok(ast)
+}
+
+
 pub fn parse(
     report: &mut diagn::Report,
     opts: &asm::AssemblyOptions,
@@ -250,7 +414,7 @@ pub fn parse(
     {
         if let Some(node) = parse_line(report, opts, walker)?
         {
-            nodes.push(node);
+            push_flattened(&mut nodes, node);
         }
     }
 
@@ -273,7 +437,7 @@ pub fn parse_nested_toplevel(
     {
         if let Some(node) = parse_line(report, opts, walker)?
         {
-            nodes.push(node);
+            push_flattened(&mut nodes, node);
         }
     }
 
@@ -283,6 +447,34 @@ pub fn parse_nested_toplevel(
 }
 
 
+/// Pushes a node, splicing in the body of a `#namespace`
+/// between its opening and closing nodes, followed by
+/// the constants it exports.
+fn push_flattened(
+    nodes: &mut Vec<AstAny>,
+    node: AstAny)
+{
+    let AstAny::DirectiveNamespace(mut ast_namespace) = node
+    else
+    {
+        nodes.push(node);
+        return;
+    };
+
+    let body = std::mem::take(&mut ast_namespace.body.nodes);
+    let exports = std::mem::take(&mut ast_namespace.exports);
+
+    let ast_end = AstDirectiveNamespaceEnd {
+        span: ast_namespace.end_span,
+    };
+
+    nodes.push(AstAny::DirectiveNamespace(ast_namespace));
+    nodes.extend(body);
+    nodes.push(AstAny::DirectiveNamespaceEnd(ast_end));
+    nodes.extend(exports.into_iter().map(AstAny::Symbol));
+}
+
+
 fn parse_line(
     report: &mut diagn::Report,
     opts: &asm::AssemblyOptions,
@@ -295,9 +487,11 @@ fn parse_line(
         Ok(Some(directive::parse(report, opts, walker)?))
     }
 
-    // Global labels (identifiers followed by colons)
+    // Global labels (identifiers followed by colons,
+    // but not by anonymous label references like `:-`)
     else if walker.next_useful_is(0, syntax::TokenKind::Identifier) &&
-        walker.next_useful_is(1, syntax::TokenKind::Colon)
+        walker.next_useful_is(1, syntax::TokenKind::Colon) &&
+        !is_anonymous_label_ref(walker, 1)
     {
         Ok(Some(symbol::parse(report, walker)?))
     }
@@ -315,6 +509,12 @@ fn parse_line(
         Ok(Some(symbol::parse(report, walker)?))
     }
 
+    // Anonymous labels (lone colons)
+    else if walker.next_useful_is(0, syntax::TokenKind::Colon)
+    {
+        Ok(Some(symbol::parse(report, walker)?))
+    }
+
     // Empty lines
     else if walker.maybe_expect_linebreak().is_some()
     {
@@ -330,6 +530,23 @@ fn parse_line(
 }
 
 
+/// Whether the nth useful token is a colon immediately
+/// followed by a `-` or `+` sign.
+fn is_anonymous_label_ref(
+    walker: &syntax::Walker,
+    nth: usize)
+    -> bool
+{
+    let tk_colon = walker.next_nth_useful_token(nth);
+    let tk_sign = walker.next_nth_useful_token(nth + 1);
+
+    (tk_sign.kind == syntax::TokenKind::Minus ||
+        tk_sign.kind == syntax::TokenKind::Plus) &&
+        tk_colon.span.location().map(|l| l.1) ==
+            tk_sign.span.location().map(|l| l.0)
+}
+
+
 impl AstAny
 {
     pub fn span(&self) -> diagn::Span
@@ -347,12 +564,37 @@ impl AstAny
             AstAny::DirectiveIf(node) => node.header_span,
             AstAny::DirectiveInclude(node) => node.header_span,
             AstAny::DirectiveLabelAlign(node) => node.header_span,
+            AstAny::DirectiveLayout(node) => node.header_span,
+            AstAny::DirectiveMacro(node) => node.header_span,
+            AstAny::DirectiveNamespace(node) => node.header_span,
+            AstAny::DirectiveNamespaceEnd(node) => node.span,
             AstAny::DirectiveNoEmit(node) => node.header_span,
             AstAny::DirectiveOnce(node) => node.header_span,
+            AstAny::DirectiveRepeat(node) => node.header_span,
             AstAny::DirectiveRes(node) => node.header_span,
             AstAny::DirectiveRuledef(node) => node.header_span,
+            AstAny::DirectiveSection(node) => node.header_span,
+            AstAny::DirectiveTest(node) => node.header_span,
             AstAny::Instruction(node) => node.span,
             AstAny::Symbol(node) => node.decl_span,
         }
     }
+
+
+    /// The macro invocations this node was expanded from,
+    /// for nodes that can report errors after expansion.
+    pub fn macro_expansions_mut(&mut self) -> Option<&mut Vec<AstMacroExpansion>>
+    {
+        match self
+        {
+            AstAny::DirectiveAddr(node) => Some(&mut node.macro_expansions),
+            AstAny::DirectiveAlign(node) => Some(&mut node.macro_expansions),
+            AstAny::DirectiveAssert(node) => Some(&mut node.macro_expansions),
+            AstAny::DirectiveData(node) => Some(&mut node.macro_expansions),
+            AstAny::DirectiveRes(node) => Some(&mut node.macro_expansions),
+            AstAny::Instruction(node) => Some(&mut node.macro_expansions),
+            AstAny::Symbol(node) => Some(&mut node.macro_expansions),
+            _ => None,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,431 @@
+use crate::*;
+
+
+pub enum Substitution
+{
+    /// A macro argument or loop index, kept both as
+    /// source text, for instructions, and as a parsed
+    /// expression, for everything else
+    Argument {
+        text: String,
+        expr: Option<expr::Expr>,
+    },
+
+    /// A symbol local to the current expansion or
+    /// iteration, renamed to a unique name
+    Symbol(String),
+}
+
+
+impl Substitution
+{
+    pub fn text(&self) -> &str
+    {
+        match self
+        {
+            Substitution::Argument { text, .. } => text,
+            Substitution::Symbol(name) => name,
+        }
+    }
+}
+
+
+pub type SubstitutionMap = std::collections::HashMap<String, Substitution>;
+
+
+pub fn collect_local_symbols(
+    ast: &asm::AstTopLevel,
+    names: &mut Vec<String>)
+{
+    for node in &ast.nodes
+    {
+        match node
+        {
+            asm::AstAny::Symbol(ast_symbol) =>
+            {
+                if !ast_symbol.is_anonymous
+                {
+                    names.push(ast_symbol.name.clone());
+                }
+            }
+
+            asm::AstAny::DirectiveIf(ast_if) =>
+            {
+                collect_local_symbols(&ast_if.true_arm, names);
+
+                if let Some(false_arm) = &ast_if.false_arm
+                {
+                    collect_local_symbols(false_arm, names);
+                }
+            }
+
+            asm::AstAny::DirectiveRepeat(ast_repeat) =>
+                collect_local_symbols(&ast_repeat.body, names),
+
+            _ => {}
+        }
+    }
+}
+
+
+/// Replaces identifiers in the source text of an
+/// instruction, skipping over member accesses.
+pub fn substitute_text(
+    src: &str,
+    substs: &SubstitutionMap)
+    -> String
+{
+    if substs.len() == 0
+    {
+        return src.to_string();
+    }
+
+    let mut result = String::new();
+    let mut prev_kind = None;
+    let mut index = 0;
+
+    while index < src.len()
+    {
+        let (kind, length) = syntax::decide_next_token(&src[index..]);
+        let excerpt = &src[index..(index + length)];
+
+        match substs.get(excerpt)
+        {
+            Some(subst) if kind == syntax::TokenKind::Identifier &&
+                prev_kind != Some(syntax::TokenKind::Dot) =>
+                result.push_str(subst.text()),
+
+            _ =>
+                result.push_str(excerpt),
+        }
+
+        if !kind.is_ignorable()
+        {
+            prev_kind = Some(kind);
+        }
+
+        index += length;
+    }
+
+    result
+}
+
+
+pub fn substitute_nodes(
+    report: &mut diagn::Report,
+    nodes: &mut [asm::AstAny],
+    substs: &SubstitutionMap)
+    -> Result<(), ()>
+{
+    for node in nodes
+    {
+        substitute_node(report, node, substs)?;
+    }
+
+    Ok(())
+}
+
+
+/// Like `substitute_node`, but leaves the nested blocks
+/// of `#if` and `#repeat` directives untouched.
+pub fn substitute_node_shallow(
+    report: &mut diagn::Report,
+    node: &mut asm::AstAny,
+    substs: &SubstitutionMap)
+    -> Result<(), ()>
+{
+    match node
+    {
+        asm::AstAny::DirectiveIf(ast_if) =>
+            substitute_expr(report, &mut ast_if.condition_expr, substs),
+
+        asm::AstAny::DirectiveRepeat(ast_repeat) =>
+            substitute_repeat_exprs(report, ast_repeat, substs),
+
+        _ => substitute_node(report, node, substs),
+    }
+}
+
+
+fn substitute_repeat_exprs(
+    report: &mut diagn::Report,
+    ast_repeat: &mut asm::AstDirectiveRepeat,
+    substs: &SubstitutionMap)
+    -> Result<(), ()>
+{
+    if let Some(start_expr) = &mut ast_repeat.start_expr
+    {
+        substitute_expr(report, start_expr, substs)?;
+    }
+
+    substitute_expr(report, &mut ast_repeat.end_expr, substs)
+}
+
+
+pub fn substitute_node(
+    report: &mut diagn::Report,
+    node: &mut asm::AstAny,
+    substs: &SubstitutionMap)
+    -> Result<(), ()>
+{
+    if substs.len() == 0
+    {
+        return Ok(());
+    }
+
+    match node
+    {
+        asm::AstAny::DirectiveAddr(ast_addr) =>
+            substitute_expr(report, &mut ast_addr.expr, substs),
+
+        asm::AstAny::DirectiveAlign(ast_align) =>
+            substitute_expr(report, &mut ast_align.expr, substs),
+
+        asm::AstAny::DirectiveAssert(ast_assert) =>
+            substitute_expr(report, &mut ast_assert.condition_expr, substs),
+
+        asm::AstAny::DirectiveBank(ast_bank) =>
+        {
+            if let Some(subst) = substs.get(&ast_bank.name)
+            {
+                ast_bank.name = subst.text().to_string();
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::DirectiveBankdef(ast_bankdef) =>
+        {
+            let exprs = [
+                &mut ast_bankdef.addr_unit,
+                &mut ast_bankdef.label_align,
+                &mut ast_bankdef.addr_start,
+                &mut ast_bankdef.addr_end,
+                &mut ast_bankdef.addr_size,
+                &mut ast_bankdef.output_offset,
+                &mut ast_bankdef.userdata,
+            ];
+
+            for maybe_expr in exprs
+            {
+                if let Some(expr) = maybe_expr
+                {
+                    substitute_expr(report, expr, substs)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::DirectiveBits(ast_bits) =>
+            substitute_expr(report, &mut ast_bits.expr, substs),
+
+        asm::AstAny::DirectiveData(ast_data) =>
+        {
+            for elem in &mut ast_data.elems
+            {
+                substitute_expr(report, elem, substs)?;
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::DirectiveIf(ast_if) =>
+        {
+            substitute_expr(report, &mut ast_if.condition_expr, substs)?;
+            substitute_nodes(report, &mut ast_if.true_arm.nodes, substs)?;
+
+            if let Some(false_arm) = &mut ast_if.false_arm
+            {
+                substitute_nodes(report, &mut false_arm.nodes, substs)?;
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::DirectiveLabelAlign(ast_labelalign) =>
+            substitute_expr(report, &mut ast_labelalign.expr, substs),
+
+        asm::AstAny::DirectiveLayout(ast_layout) =>
+        {
+            for entry in &mut ast_layout.entries
+            {
+                if let Some(expr) = &mut entry.align
+                {
+                    substitute_expr(report, expr, substs)?;
+                }
+
+                if let Some(expr) = &mut entry.keep
+                {
+                    substitute_expr(report, expr, substs)?;
+                }
+
+                if let Some(expr) = &mut entry.ordered
+                {
+                    substitute_expr(report, expr, substs)?;
+                }
+
+                if let Some(expr) = &mut entry.discard
+                {
+                    substitute_expr(report, expr, substs)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::DirectiveRepeat(ast_repeat) =>
+        {
+            substitute_repeat_exprs(report, ast_repeat, substs)?;
+            substitute_nodes(report, &mut ast_repeat.body.nodes, substs)
+        }
+
+        asm::AstAny::DirectiveRes(ast_res) =>
+            substitute_expr(report, &mut ast_res.expr, substs),
+
+        asm::AstAny::DirectiveSection(ast_section) =>
+        {
+            if let Some(subst) = substs.get(&ast_section.name)
+            {
+                ast_section.name = subst.text().to_string();
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::Instruction(ast_instr) =>
+        {
+            ast_instr.src = substitute_text(&ast_instr.src, substs);
+            Ok(())
+        }
+
+        asm::AstAny::Symbol(ast_symbol) =>
+        {
+            if let Some(Substitution::Symbol(unique_name)) = substs.get(&ast_symbol.name)
+            {
+                ast_symbol.name = unique_name.clone();
+                ast_symbol.no_emit = true;
+                ast_symbol.keeps_ctx = true;
+            }
+
+            if let asm::AstSymbolKind::Constant(ast_const) = &mut ast_symbol.kind
+            {
+                substitute_expr(report, &mut ast_const.expr, substs)?;
+            }
+
+            Ok(())
+        }
+
+        asm::AstAny::DirectiveFn(..) |
+        asm::AstAny::DirectiveInclude(..) |
+        asm::AstAny::DirectiveMacro(..) |
+        asm::AstAny::DirectiveNamespace(..) |
+        asm::AstAny::DirectiveNamespaceEnd(..) |
+        asm::AstAny::DirectiveNoEmit(..) |
+        asm::AstAny::DirectiveOnce(..) |
+        asm::AstAny::DirectiveRuledef(..) |
+        asm::AstAny::DirectiveTest(..) =>
+            Ok(()),
+    }
+}
+
+
+pub fn substitute_expr(
+    report: &mut diagn::Report,
+    expr: &mut expr::Expr,
+    substs: &SubstitutionMap)
+    -> Result<(), ()>
+{
+    match expr
+    {
+        expr::Expr::Variable(span, name) =>
+        {
+            match substs.get(name.as_str())
+            {
+                Some(Substitution::Symbol(unique_name)) =>
+                    *name = unique_name.clone(),
+
+                Some(Substitution::Argument { expr: Some(arg_expr), .. }) =>
+                    *expr = arg_expr.clone(),
+
+                Some(Substitution::Argument { text, expr: None }) =>
+                {
+                    report.error_span(
+                        format!("macro argument `{}` is not a valid expression", text),
+                        *span);
+
+                    return Err(());
+                }
+
+                None => {}
+            }
+        }
+
+        expr::Expr::Literal(..) |
+        expr::Expr::NestingLevel { .. } |
+        expr::Expr::AnonymousLabel { .. } => {}
+
+        expr::Expr::StructInit { members_init, .. } =>
+        {
+            for member_init in members_init
+            {
+                substitute_expr(report, &mut member_init.value, substs)?;
+            }
+        }
+
+        expr::Expr::MemberAccess { lhs, .. } =>
+            substitute_expr(report, lhs, substs)?,
+
+        expr::Expr::UnaryOp(_, _, _, inner) =>
+            substitute_expr(report, inner, substs)?,
+
+        expr::Expr::BinaryOp(_, _, _, lhs, rhs) =>
+        {
+            substitute_expr(report, lhs, substs)?;
+            substitute_expr(report, rhs, substs)?;
+        }
+
+        expr::Expr::TernaryOp(_, cond, true_branch, false_branch) =>
+        {
+            substitute_expr(report, cond, substs)?;
+            substitute_expr(report, true_branch, substs)?;
+            substitute_expr(report, false_branch, substs)?;
+        }
+
+        expr::Expr::Slice(_, _, left, right, inner) =>
+        {
+            substitute_expr(report, left, substs)?;
+            substitute_expr(report, right, substs)?;
+            substitute_expr(report, inner, substs)?;
+        }
+
+        expr::Expr::SliceShort(_, _, size, inner) =>
+        {
+            substitute_expr(report, size, substs)?;
+            substitute_expr(report, inner, substs)?;
+        }
+
+        expr::Expr::Block(_, exprs) =>
+        {
+            for inner in exprs
+            {
+                substitute_expr(report, inner, substs)?;
+            }
+        }
+
+        expr::Expr::Call(_, func, args) =>
+        {
+            substitute_expr(report, func, substs)?;
+
+            for arg in args
+            {
+                substitute_expr(report, arg, substs)?;
+            }
+        }
+
+        expr::Expr::Asm(_, ast) =>
+            substitute_nodes(report, &mut ast.nodes, substs)?,
+    }
+
+    Ok(())
+}
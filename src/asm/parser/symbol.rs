@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstSymbol
 {
     pub decl_span: diagn::Span,
@@ -9,12 +9,21 @@ pub struct AstSymbol
     pub name: String,
     pub kind: AstSymbolKind,
     pub no_emit: bool,
+    /// Whether declaring this symbol leaves the current label
+    /// context untouched, as with symbols local to a macro
+    pub keeps_ctx: bool,
+    /// Whether this is an anonymous label, declared with a lone
+    /// colon, and referenced relatively with `:-` or `:+`
+    pub is_anonymous: bool,
+    pub macro_expansions: Vec<asm::AstMacroExpansion>,
+    
+    #[serde(skip)]
     
     pub item_ref: Option<util::ItemRef::<asm::Symbol>>,
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum AstSymbolKind
 {
     Constant(AstSymbolConstant),
@@ -22,7 +31,7 @@ pub enum AstSymbolKind
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AstSymbolConstant
 {
     pub expr: expr::Expr,
@@ -34,6 +43,22 @@ pub fn parse(
     walker: &mut syntax::Walker)
     -> Result<asm::AstAny, ()>
 {
+    if let Some(tk_colon) = walker.maybe_expect(syntax::TokenKind::Colon)
+    {
+        return Ok(asm::AstAny::Symbol(AstSymbol {
+            decl_span: tk_colon.span,
+            hierarchy_level: 0,
+            name: "".to_string(),
+            kind: AstSymbolKind::Label,
+            no_emit: true,
+            keeps_ctx: true,
+            is_anonymous: true,
+            macro_expansions: Vec::new(),
+
+            item_ref: None,
+        }));
+    }
+
     let mut decl_span = diagn::Span::new_dummy();
     let mut hierarchy_level = 0;
     
@@ -61,6 +86,9 @@ pub fn parse(
                 expr,
             }),
             no_emit: false,
+            keeps_ctx: false,
+            is_anonymous: false,
+            macro_expansions: Vec::new(),
 
             item_ref: None,
         }))
@@ -76,6 +104,9 @@ pub fn parse(
             name,
             kind: AstSymbolKind::Label,
             no_emit: false,
+            keeps_ctx: false,
+            is_anonymous: false,
+            macro_expansions: Vec::new(),
 
             item_ref: None,
         }))
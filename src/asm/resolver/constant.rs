@@ -20,6 +20,28 @@ pub fn resolve_constants_simple(
 
     while let Some(ctx) = iter.next_simple(report, decls, defs)?
     {
+        if let asm::ResolverNode::Namespace(ast_namespace) = ctx.node
+        {
+            let asm::AstNamespaceKind::Struct(ref ast_struct) = ast_namespace.kind
+                else { continue };
+
+            let resolution_state = resolve_struct_simple(
+                report,
+                opts,
+                ast_namespace,
+                ast_struct,
+                decls,
+                defs,
+                ctx.anonymous_label_count)?;
+
+            if let asm::ResolutionState::Resolved = resolution_state
+            {
+                resolved_count += 1;
+            }
+
+            continue;
+        }
+
         let asm::ResolverNode::Symbol(ast_symbol) = ctx.node
             else { continue };
         
@@ -31,7 +53,8 @@ pub fn resolve_constants_simple(
             opts,
             ast_symbol,
             decls,
-            defs)?;
+            defs,
+            ctx.anonymous_label_count)?;
 
         if let asm::ResolutionState::Resolved = resolution_state
         {
@@ -48,7 +71,8 @@ fn resolve_constant_simple(
     opts: &asm::AssemblyOptions,
     ast_symbol: &asm::AstSymbol,
     decls: &asm::ItemDecls,
-    defs: &mut asm::ItemDefs)
+    defs: &mut asm::ItemDefs,
+    anonymous_label_count: usize)
     -> Result<asm::ResolutionState, ()>
 {
     let item_ref = ast_symbol.item_ref.unwrap();
@@ -96,6 +120,7 @@ fn resolve_constant_simple(
         opts,
         decls,
         defs,
+        Some(anonymous_label_count),
         &ast_const.expr)?;
 
     let symbol = defs.symbols.get_mut(item_ref);
@@ -170,4 +195,106 @@ pub fn resolve_constant(
         "constant value",
         Some(&ast_symbol.name),
         &symbol.value)
-}
\ No newline at end of file
+}
+
+fn resolve_struct_simple(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    ast_namespace: &asm::AstDirectiveNamespace,
+    ast_struct: &asm::AstNamespaceStruct,
+    decls: &asm::ItemDecls,
+    defs: &mut asm::ItemDefs,
+    anonymous_label_count: usize)
+    -> Result<asm::ResolutionState, ()>
+{
+    let item_ref = ast_namespace.item_ref.unwrap();
+
+    let symbol = defs.symbols.get(item_ref);
+
+    if symbol.resolved && opts.optimize_statically_known
+    {
+        return Ok(asm::ResolutionState::Resolved);
+    }
+
+    let value = asm::resolver::eval_simple(
+        report,
+        opts,
+        decls,
+        defs,
+        Some(anonymous_label_count),
+        &ast_struct.value_expr)?;
+
+    let symbol = defs.symbols.get_mut(item_ref);
+    let is_stable = value.is_stable(&symbol.value);
+    symbol.value = value;
+    symbol.value.get_mut_metadata().symbol_ref = Some(item_ref);
+
+    asm::resolver::handle_value_resolution(
+        opts,
+        report,
+        ast_namespace.header_span,
+        true,
+        symbol.value.is_guess(),
+        is_stable,
+        &mut symbol.resolved,
+        false,
+        "struct",
+        "struct size",
+        Some(&ast_namespace.name),
+        &symbol.value)
+}
+
+
+/// Resolves the size of a `#struct` from its fields,
+/// which are visible from the namespace's context
+pub fn resolve_struct(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    ast_namespace: &asm::AstDirectiveNamespace,
+    decls: &asm::ItemDecls,
+    defs: &mut asm::ItemDefs,
+    ctx: &asm::ResolverContext)
+    -> Result<asm::ResolutionState, ()>
+{
+    let asm::AstNamespaceKind::Struct(ref ast_struct) = ast_namespace.kind
+        else { return Ok(asm::ResolutionState::Resolved) };
+
+    let item_ref = ast_namespace.item_ref.unwrap();
+
+    let symbol = defs.symbols.get(item_ref);
+
+    if symbol.resolved && opts.optimize_statically_known
+    {
+        return Ok(asm::ResolutionState::Resolved);
+    }
+
+    let value = asm::resolver::eval(
+        report,
+        fileserver,
+        opts,
+        decls,
+        defs,
+        ctx,
+        &mut expr::EvalContext::new(opts),
+        &ast_struct.value_expr)?;
+
+    let symbol = defs.symbols.get_mut(item_ref);
+    let is_stable = value.is_stable(&symbol.value);
+    symbol.value = value;
+    symbol.value.get_mut_metadata().symbol_ref = Some(item_ref);
+
+    asm::resolver::handle_value_resolution(
+        opts,
+        report,
+        ast_namespace.header_span,
+        ctx.can_guess(),
+        symbol.value.is_guess(),
+        is_stable,
+        &mut symbol.resolved,
+        false,
+        "struct",
+        "struct size",
+        Some(&ast_namespace.name),
+        &symbol.value)
+}
@@ -37,7 +37,8 @@ pub fn resolve_data_element(
 
     report.pop_parent();
 
-    let value = maybe_value?;
+    // Strings are emitted as their UTF-8 encoding
+    let value = maybe_value?.coerce_to_integer();
 
     let data_elem = defs.data_elems.get_mut(item_ref);
     let is_stable = value.is_stable(&data_elem.encoding);
@@ -12,6 +12,11 @@ pub fn resolve_ifs(
 {
     let mut resolved_count = 0;
 
+    // Stays valid while going backwards, since
+    // only the nodes after `n` are replaced
+    let anonymous_label_counts = asm::resolver::count_anonymous_labels(
+        &ast.nodes);
+
 
     for n in (0..ast.nodes.len()).rev()
     {
@@ -24,6 +29,7 @@ pub fn resolve_ifs(
                 opts,
                 decls,
                 defs,
+                Some(anonymous_label_counts[n]),
                 &node.condition_expr)?;
 
         let expr::Value::Bool(_, condition_result) = condition_result
@@ -67,7 +73,10 @@ pub fn check_leftover_ifs(
     defs: &asm::ItemDefs)
     -> Result<(), ()>
 {
-    for node in &ast.nodes
+    let anonymous_label_counts = asm::resolver::count_anonymous_labels(
+        &ast.nodes);
+
+    for (n, node) in ast.nodes.iter().enumerate()
     {
         let asm::AstAny::DirectiveIf(node) = node
             else { continue };
@@ -82,6 +91,7 @@ pub fn check_leftover_ifs(
                 opts,
                 decls,
                 defs,
+                Some(anonymous_label_counts[n]),
                 &node.condition_expr);
 
         report.pop_parent();
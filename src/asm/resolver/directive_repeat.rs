@@ -0,0 +1,359 @@
+use crate::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepeatRange
+{
+    pub start: util::BigInt,
+    pub count: usize,
+}
+
+
+/// Bounds the iterations of a single loop, and the size of
+/// the whole program once loops are expanded, so that huge
+/// or nested ranges fail instead of running out of memory.
+const REPEAT_COUNT_MAX: usize = 1 << 16;
+const EXPANDED_NODES_MAX: usize = 1 << 20;
+
+
+/// A loop whose range couldn't be resolved early,
+/// and was expanded with a guessed range instead.
+#[derive(Clone, Debug)]
+pub struct RepeatGuess
+{
+    pub header_span: diagn::Span,
+    pub start_expr: Option<expr::Expr>,
+    pub end_expr: expr::Expr,
+    pub range: RepeatRange,
+    /// The last anonymous label before the loop, which
+    /// locates it for `:-` and `:+` even after more
+    /// nodes have been spliced in
+    pub prev_anonymous_label: Option<util::ItemRef<asm::Symbol>>,
+}
+
+
+pub fn resolve_repeats(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    ast: &mut asm::AstTopLevel,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs)
+    -> Result<usize, ()>
+{
+    let mut resolved_count = 0;
+
+    // Stays valid while going backwards, since
+    // only the nodes after `n` are replaced
+    let anonymous_label_counts = asm::resolver::count_anonymous_labels(
+        &ast.nodes);
+
+
+    for n in (0..ast.nodes.len()).rev()
+    {
+        let asm::AstAny::DirectiveRepeat(node) = &ast.nodes[n]
+            else { continue };
+
+        let maybe_range = eval_range(
+            report,
+            opts,
+            decls,
+            defs,
+            anonymous_label_counts[n],
+            node.start_expr.as_ref(),
+            &node.end_expr,
+            false)?;
+
+        let Some(range) = maybe_range
+            else { continue };
+
+        if opts.debug_iterations
+        {
+            println!("  #repeat: {} = {}",
+                fileserver.get_excerpt(node.end_expr.span()),
+                range.count);
+        }
+
+        let asm::AstAny::DirectiveRepeat(node) = ast.nodes.remove(n)
+            else { unreachable!() };
+
+        let nodes = instantiate(
+            report,
+            &node,
+            &range,
+            ast.nodes.len())?;
+
+        ast.nodes.splice(
+            n..n,
+            nodes);
+
+        resolved_count += 1;
+    }
+
+    Ok(resolved_count)
+}
+
+
+/// Expands every leftover loop using the range observed
+/// for it in the previous round, or an empty range if
+/// there's none.
+pub fn guess_repeats(
+    report: &mut diagn::Report,
+    ast: &mut asm::AstTopLevel,
+    prev_guesses: &[RepeatGuess],
+    guesses: &mut Vec<RepeatGuess>)
+    -> Result<usize, ()>
+{
+    let mut guessed_count = 0;
+
+    let mut prev_anonymous_labels = Vec::with_capacity(ast.nodes.len());
+    let mut prev_anonymous_label = None;
+
+    for node in &ast.nodes
+    {
+        prev_anonymous_labels.push(prev_anonymous_label);
+
+        if let asm::AstAny::Symbol(ast_symbol @ asm::AstSymbol { is_anonymous: true, .. }) = node
+        {
+            prev_anonymous_label = ast_symbol.item_ref;
+        }
+    }
+
+
+    for n in (0..ast.nodes.len()).rev()
+    {
+        let asm::AstAny::DirectiveRepeat(_) = &ast.nodes[n]
+            else { continue };
+
+        let asm::AstAny::DirectiveRepeat(node) = ast.nodes.remove(n)
+            else { unreachable!() };
+
+        let range = prev_guesses
+            .get(guesses.len())
+            .filter(|g| g.header_span == node.header_span)
+            .map(|g| g.range.clone())
+            .unwrap_or(RepeatRange {
+                start: util::BigInt::from(0),
+                count: 0,
+            });
+
+        let nodes = instantiate(
+            report,
+            &node,
+            &range,
+            ast.nodes.len())?;
+
+        ast.nodes.splice(
+            n..n,
+            nodes);
+
+        guesses.push(RepeatGuess {
+            header_span: node.header_span,
+            start_expr: node.start_expr,
+            end_expr: node.end_expr,
+            range,
+            prev_anonymous_label: prev_anonymous_labels[n],
+        });
+
+        guessed_count += 1;
+    }
+
+    Ok(guessed_count)
+}
+
+
+/// Re-evaluates the ranges of guessed loops with the final
+/// symbol values, updating each guess in place.
+/// Returns the span of the first loop whose guess was wrong.
+pub fn check_repeat_guesses(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    guesses: &mut [RepeatGuess])
+    -> Result<Option<diagn::Span>, ()>
+{
+    let mut mismatch_span = None;
+
+    for guess in guesses
+    {
+        report.push_parent(
+            "unresolved repeat count",
+            guess.header_span);
+
+        let anonymous_label_count = guess.prev_anonymous_label
+            .and_then(|item_ref| decls.anonymous_labels
+                .iter()
+                .position(|&r| r == item_ref))
+            .map_or(0, |index| index + 1);
+
+        let maybe_range = eval_range(
+            report,
+            opts,
+            decls,
+            defs,
+            anonymous_label_count,
+            guess.start_expr.as_ref(),
+            &guess.end_expr,
+            true);
+
+        report.pop_parent();
+
+        let range = maybe_range?.unwrap();
+
+        if range != guess.range
+        {
+            guess.range = range;
+
+            if mismatch_span.is_none()
+            {
+                mismatch_span = Some(guess.header_span);
+            }
+        }
+    }
+
+    Ok(mismatch_span)
+}
+
+
+fn eval_range(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    anonymous_label_count: usize,
+    start_expr: Option<&expr::Expr>,
+    end_expr: &expr::Expr,
+    certain: bool)
+    -> Result<Option<RepeatRange>, ()>
+{
+    let eval = |report: &mut diagn::Report, expr: &expr::Expr|
+    {
+        if certain
+        {
+            asm::resolver::eval_certain(
+                report,
+                opts,
+                decls,
+                defs,
+                Some(anonymous_label_count),
+                expr)
+        }
+        else
+        {
+            asm::resolver::eval_simple(
+                report,
+                opts,
+                decls,
+                defs,
+                Some(anonymous_label_count),
+                expr)
+        }
+    };
+
+    let end_value = eval(report, end_expr)?;
+
+    let Some(start_expr) = start_expr
+    else
+    {
+        if end_value.is_unknown()
+        {
+            return Ok(None);
+        }
+
+        let count = end_value.expect_usize(
+            report,
+            end_expr.span())?;
+
+        return Ok(Some(RepeatRange {
+            start: util::BigInt::from(0),
+            count,
+        }));
+    };
+
+    let start_value = eval(report, start_expr)?;
+
+    if start_value.is_unknown() || end_value.is_unknown()
+    {
+        return Ok(None);
+    }
+
+    let start = start_value.expect_bigint(
+        report,
+        start_expr.span())?
+        .clone();
+
+    let end = end_value.expect_bigint(
+        report,
+        end_expr.span())?;
+
+    if *end <= start
+    {
+        return Ok(Some(RepeatRange {
+            start,
+            count: 0,
+        }));
+    }
+
+    let count = end
+        .checked_sub(report, end_expr.span(), &start)?
+        .checked_into::<usize>(report, end_expr.span())?;
+
+    Ok(Some(RepeatRange {
+        start,
+        count,
+    }))
+}
+
+
+fn instantiate(
+    report: &mut diagn::Report,
+    node: &asm::AstDirectiveRepeat,
+    range: &RepeatRange,
+    ast_len: usize)
+    -> Result<Vec<asm::AstAny>, ()>
+{
+    if range.count > REPEAT_COUNT_MAX
+    {
+        report.error_span(
+            format!(
+                "exceeded repeat limit ({} iterations, maximum is {})",
+                range.count,
+                REPEAT_COUNT_MAX),
+            node.header_span);
+
+        return Err(());
+    }
+
+    let expanded_len = range.count
+        .checked_mul(node.body.nodes.len())
+        .and_then(|len| len.checked_add(ast_len));
+
+    if expanded_len.map_or(true, |len| len > EXPANDED_NODES_MAX)
+    {
+        report.error_span(
+            format!(
+                "exceeded repeat limit (program would have over {} nodes)",
+                EXPANDED_NODES_MAX),
+            node.header_span);
+
+        return Err(());
+    }
+
+    let mut nodes = Vec::new();
+
+    for i in 0..range.count
+    {
+        let index = range.start.checked_add(
+            report,
+            node.header_span,
+            &util::BigInt::from(i))?;
+
+        nodes.extend(node.instantiate(
+            report,
+            i,
+            &index)?);
+    }
+
+    Ok(nodes)
+}
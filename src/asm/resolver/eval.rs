@@ -30,6 +30,13 @@ pub fn eval(
                     ctx,
                     query_ctxlabel),
                     
+            expr::EvalQuery::AnonymousLabel(query_anon) =>
+                asm::resolver::eval_anonymous_label(
+                    decls,
+                    defs,
+                    ctx.anonymous_label_count,
+                    query_anon),
+                    
             expr::EvalQuery::Variable(query_var) =>
                 asm::resolver::eval_variable(
                     decls,
@@ -74,11 +81,16 @@ pub fn eval(
 /// Evaluates an expression without relying on
 /// addresses, banks, user-defined functions,
 /// or user-defined instructions.
+///
+/// `anonymous_label_count` is the number of anonymous
+/// labels before the expression, if it has a place
+/// in the source order, for resolving `:-` and `:+`.
 pub fn eval_simple(
     report: &mut diagn::Report,
     opts: &asm::AssemblyOptions,
     decls: &asm::ItemDecls,
     defs: &asm::ItemDefs,
+    anonymous_label_count: Option<usize>,
     expr: &expr::Expr)
     -> Result<expr::Value, ()>
 {
@@ -89,6 +101,20 @@ pub fn eval_simple(
             expr::EvalQuery::CtxLabel(_) =>
                 Ok(expr::Value::make_unknown()),
                 
+            expr::EvalQuery::AnonymousLabel(query_anon) =>
+            {
+                match anonymous_label_count
+                {
+                    Some(count) => asm::resolver::eval_anonymous_label(
+                        decls,
+                        defs,
+                        count,
+                        query_anon),
+                    
+                    None => Ok(expr::Value::make_unknown()),
+                }
+            }
+                
             expr::EvalQuery::Variable(query_var) =>
                 asm::resolver::eval_variable_simple(
                     decls,
@@ -133,6 +159,7 @@ pub fn eval_certain(
     opts: &asm::AssemblyOptions,
     decls: &asm::ItemDecls,
     defs: &asm::ItemDefs,
+    anonymous_label_count: Option<usize>,
     expr: &expr::Expr)
     -> Result<expr::Value, ()>
 {
@@ -143,6 +170,13 @@ pub fn eval_certain(
             expr::EvalQuery::CtxLabel(_) =>
                 Ok(expr::Value::make_unknown()),
                 
+            expr::EvalQuery::AnonymousLabel(query_anon) =>
+                asm::resolver::eval_anonymous_label_certain(
+                    decls,
+                    defs,
+                    anonymous_label_count,
+                    query_anon),
+                
             expr::EvalQuery::Variable(query_var) =>
                 asm::resolver::eval_variable_certain(
                     decls,
@@ -205,6 +239,7 @@ pub fn eval_ctxlabel(
         query.nesting_level)?;
 
     let symbol = defs.symbols.get(symbol_ref);
+    asm::resolver::note_symbol_use(defs, ctx, symbol);
 
     Ok(symbol.value.clone())
 }
@@ -230,6 +265,24 @@ pub fn eval_variable(
         {
             return Ok(builtin);
         }
+
+        // In object files, unknown global symbols are external,
+        // and get resolved later by the linker. The current address
+        // stands in for them, keeping relative offsets small.
+        if query.opts.allow_external_symbols &&
+            decls.symbols.try_get_by_name(
+                ctx.symbol_ctx,
+                0,
+                query.hierarchy).is_none()
+        {
+            let addr = ctx.eval_address(
+                query.report,
+                query.span,
+                defs,
+                ctx.can_guess())?;
+
+            return Ok(addr.with_bank_ref(ctx.bank_ref));
+        }
     }
 
     let symbol_ref = decls.symbols.get_by_name(
@@ -240,6 +293,7 @@ pub fn eval_variable(
         query.hierarchy)?;
 
     let symbol = defs.symbols.get(symbol_ref);
+    asm::resolver::note_symbol_use(defs, ctx, symbol);
 
     Ok(symbol.value.clone())
 }
@@ -407,7 +461,7 @@ fn eval_builtin_symbol(
 pub fn eval_member(
     decls: &asm::ItemDecls,
     defs: &asm::ItemDefs,
-    _ctx: Option<&asm::ResolverContext>,
+    ctx: Option<&asm::ResolverContext>,
     query: &mut expr::EvalMemberQuery)
     -> Result<expr::Value, ()>
 {
@@ -422,11 +476,22 @@ pub fn eval_member(
         if let Some(subsymbol_ref) = subsymbol_ref
         {
             let subsymbol = defs.symbols.get(subsymbol_ref);
+
+            if let Some(ctx) = ctx
+            {
+                asm::resolver::note_symbol_use(defs, ctx, subsymbol);
+            }
+
             return Ok(subsymbol.value.clone());
         }
     }
 
-    if let Some(value) = eval_member_bankdef(decls, defs, _ctx, query)?
+    if let Some(value) = eval_member_bankdef(decls, defs, ctx, query)?
+    {
+        return Ok(value);
+    }
+
+    if let Some(value) = eval_member_struct(decls, defs, ctx, query)?
     {
         return Ok(value);
     }
@@ -446,6 +511,62 @@ pub fn eval_member(
 }
 
 
+/// Gives the size of a `#struct` in address units of the
+/// current bank as `Name.units`, complementing `Name.size`,
+/// which is in bits like `$sizeof`.
+pub fn eval_member_struct(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    ctx: Option<&asm::ResolverContext>,
+    query: &mut expr::EvalMemberQuery)
+    -> Result<Option<expr::Value>, ()>
+{
+    if query.member_name != "units"
+    {
+        return Ok(None);
+    }
+
+    let Some(symbol_ref) = query.value.get_metadata().symbol_ref
+        else { return Ok(None) };
+
+    let util::SymbolKind::Namespace = decls.symbols.get(symbol_ref).kind
+        else { return Ok(None) };
+
+    let expr::Value::Integer(_, bigint) = &query.value
+        else { return Ok(None) };
+
+    let Some(size) = bigint.size
+        else { return Ok(None) };
+
+    let Some(ctx) = ctx
+        else {
+            query.report.error_span(
+                "cannot get the address unit in this context",
+                query.span);
+
+            return Err(());
+        };
+
+    let addr_unit = defs.bankdefs.get(ctx.bank_ref).addr_unit;
+    let is_static = asm::Section::find(defs, ctx.bank_ref).is_none();
+
+    if size % addr_unit != 0
+    {
+        query.report.error_span(
+            format!(
+                "struct size of {} bits isn't a whole number of {}-bit address units",
+                size,
+                addr_unit),
+            query.span);
+
+        return Err(());
+    }
+
+    Ok(Some(expr::Value::make_integer(size / addr_unit)
+        .statically_known_if(is_static)))
+}
+
+
 pub fn eval_member_bankdef(
     _decls: &asm::ItemDecls,
     defs: &asm::ItemDefs,
@@ -457,14 +578,15 @@ pub fn eval_member_bankdef(
         else { return Ok(None); };
 
     let bank = defs.bankdefs.get(bank_ref);
+    let is_static = asm::Section::find(defs, bank_ref).is_none();
 
     match query.member_name
     {
-        "bits" => Ok(Some(expr::Value::make_integer(bank.addr_unit).statically_known())),
-        "addr" => Ok(Some(expr::Value::make_integer(bank.addr_start.clone()).statically_known())),
-        "outp" => Ok(Some(expr::Value::make_maybe_integer(bank.output_offset).statically_known())),
-        "size" => Ok(Some(expr::Value::make_maybe_integer(bank.size_in_units).statically_known())),
-        "size_b" => Ok(Some(expr::Value::make_maybe_integer(bank.size_in_bits).statically_known())),
+        "bits" => Ok(Some(expr::Value::make_integer(bank.addr_unit).statically_known_if(is_static))),
+        "addr" => Ok(Some(expr::Value::make_integer(bank.addr_start.clone()).statically_known_if(is_static))),
+        "outp" => Ok(Some(expr::Value::make_maybe_integer(bank.output_offset).statically_known_if(is_static))),
+        "size" => Ok(Some(expr::Value::make_maybe_integer(bank.size_in_units).statically_known_if(is_static))),
+        "size_b" => Ok(Some(expr::Value::make_maybe_integer(bank.size_in_bits).statically_known_if(is_static))),
         "data" => Ok(Some(bank.userdata.clone())),
         _ => Ok(None)
     }
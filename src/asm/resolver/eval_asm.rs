@@ -55,6 +55,15 @@ pub fn eval_asm(
                 return Err(());
             }
 
+            if ast_symbol.is_anonymous
+            {
+                query.report.error_span(
+                    "anonymous labels are not permitted in `asm` blocks",
+                    node.span());
+
+                return Err(());
+            }
+
             if ast_symbol.hierarchy_level != 0
             {
                 query.report.error_span(
@@ -7,6 +7,8 @@ pub enum AsmBuiltinFn
     Incbin,
     Incbinstr,
     Inchexstr,
+    Incihex,
+    Incsrec,
     Bankof,
 }
 
@@ -30,6 +32,8 @@ pub fn resolve_builtin_fn(
         "$incbin" => Some(AsmBuiltinFn::Incbin),
         "$incbinstr" => Some(AsmBuiltinFn::Incbinstr),
         "$inchexstr" => Some(AsmBuiltinFn::Inchexstr),
+        "$incihex" => Some(AsmBuiltinFn::Incihex),
+        "$incsrec" => Some(AsmBuiltinFn::Incsrec),
         "$bankof" => Some(AsmBuiltinFn::Bankof),
         _ => {
             if opts.use_legacy_behavior
@@ -60,6 +64,8 @@ pub fn get_builtin_fn_eval(
         AsmBuiltinFn::Incbin => eval_builtin_incbin,
         AsmBuiltinFn::Incbinstr => eval_builtin_incbinstr,
         AsmBuiltinFn::Inchexstr => eval_builtin_inchexstr,
+        AsmBuiltinFn::Incihex => eval_builtin_incihex,
+        AsmBuiltinFn::Incsrec => eval_builtin_incsrec,
         AsmBuiltinFn::Bankof => eval_builtin_bankof,
     }
 }
@@ -417,6 +423,201 @@ fn eval_builtin_incstr(
 }
 
 
+fn eval_builtin_incihex(
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    ctx: &asm::ResolverContext,
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    eval_builtin_inchex(
+        fileserver,
+        util::parse_intelhex,
+        decls,
+        defs,
+        ctx,
+        query,
+        "incihex")
+}
+
+
+fn eval_builtin_incsrec(
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    ctx: &asm::ResolverContext,
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    eval_builtin_inchex(
+        fileserver,
+        util::parse_srec,
+        decls,
+        defs,
+        ctx,
+        query,
+        "incsrec")
+}
+
+
+/// Gaps between records are filled in, so a file with data at
+/// distant addresses would otherwise produce a huge value.
+const INCHEX_SIZE_MAX: u64 = 1 << 24;
+
+
+/// Includes the contents of an addressed hex file,
+/// either over the whole span of addresses it covers,
+/// or over the range given by a start address and size.
+/// Gaps are filled with zeroes.
+fn eval_builtin_inchex(
+    fileserver: &mut dyn util::FileServer,
+    parse_fn: fn(&mut diagn::Report, diagn::Span, &str) -> Result<util::HexImportData, ()>,
+    _decls: &asm::ItemDecls,
+    _defs: &asm::ItemDefs,
+    ctx: &asm::ResolverContext,
+    query: &mut expr::EvalFunctionQuery,
+    funcname: &str)
+    -> Result<expr::Value, ()>
+{
+    query.ensure_min_max_arg_number(1, 3)?;
+
+    let relative_filename = query.args[0].value.expect_string(
+        query.report,
+        query.args[0].span)?;
+
+    let filename_ctx = fileserver.get_filename(
+        ctx.file_handle_ctx.unwrap());
+    
+    let absolute_filename = util::filename_navigate(
+        query.report,
+        query.args[0].span,
+        filename_ctx,
+        &relative_filename)?;
+
+    let file_handle = fileserver.get_handle(
+        query.report,
+        Some(query.args[0].span),
+        &absolute_filename)?;
+    
+    let contents = fileserver.get_str(
+        query.report,
+        Some(query.args[0].span),
+        file_handle)?;
+
+    let data = parse_fn(
+        query.report,
+        query.args[0].span,
+        &contents)?;
+
+    let first_addr = data
+        .keys()
+        .next()
+        .copied()
+        .unwrap_or(0);
+
+    let last_addr = data
+        .keys()
+        .next_back()
+        .map(|a| a + 1)
+        .unwrap_or(0);
+
+    let start = {
+        if query.args.len() >= 2
+        {
+            query.args[1].value.expect_usize(
+                query.report,
+                query.args[1].span)? as u64
+        }
+        else
+        {
+            first_addr
+        }
+    };
+
+    let end = {
+        if query.args.len() >= 3
+        {
+            let size = query.args[2].value.expect_usize(
+                query.report,
+                query.args[2].span)?;
+
+            let Some(end) = start.checked_add(size as u64)
+            else
+            {
+                query.report.error_span(
+                    format!(
+                        "`{}` range end is outside the supported range",
+                        funcname),
+                    query.args[2].span);
+
+                return Err(());
+            };
+
+            end
+        }
+        else
+        {
+            last_addr.max(start)
+        }
+    };
+
+    if query.args.len() >= 2 &&
+        start < end &&
+        data.range(start..end).next().is_none()
+    {
+        query.report.error_span(
+            format!(
+                "`{}` range contains no data",
+                funcname),
+            query.args[1].span);
+        return Err(());
+    }
+
+    if end.saturating_sub(start) > INCHEX_SIZE_MAX
+    {
+        let span = query.args
+            .get(2)
+            .map_or(query.span, |arg| arg.span);
+
+        query.report.push_parent(
+            format!(
+                "`{}` range is too large (0x{:x} bytes, maximum is 0x{:x})",
+                funcname,
+                end - start,
+                INCHEX_SIZE_MAX),
+            span);
+
+        if query.args.len() < 2
+        {
+            query.report.note(format!(
+                "data spans from 0x{:x} to 0x{:x}; \
+                give a start address and size to include part of it",
+                first_addr,
+                last_addr));
+        }
+
+        query.report.pop_parent();
+        return Err(());
+    }
+
+    let bytes = (start..end)
+        .map(|addr| data.get(&addr).copied().unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    let mut result = expr::Value::make_integer(
+            util::BigInt::from_bytes_be(&bytes))
+        .statically_known();
+
+    for arg in &query.args
+    {
+        result.mark_derived_from(&arg.value)
+    }
+
+    Ok(result)
+}
+
+
 fn eval_builtin_bankof(
     _fileserver: &mut dyn util::FileServer,
     decls: &asm::ItemDecls,
@@ -609,10 +609,12 @@ fn check_and_constrain_value_for_integer_type(
     span: diagn::Span,
     size: usize,
     typename_prefix: &'static str,
-    mut value: expr::Value,
+    value: expr::Value,
     failure_check: impl Fn(&util::BigInt) -> bool)
     -> Result<expr::Value, ()>
 {
+    let mut value = value.coerce_to_integer();
+
     let bigint = value
         .expect_bigint(report, span)?;
 
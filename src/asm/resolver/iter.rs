@@ -21,6 +21,9 @@ pub struct ResolveIterator<'ast, 'decls>
     is_first_iteration: bool,
     is_last_iteration: bool,
     symbol_ctx: &'decls util::SymbolContext,
+    /// Contexts to restore when leaving each `#namespace`
+    namespace_ctx_stack: Vec<&'decls util::SymbolContext>,
+    anonymous_label_count: usize,
     bank_ref: util::ItemRef<asm::Bankdef>,
     bank_data: Vec<BankData>,
 }
@@ -45,6 +48,8 @@ pub enum ResolverNode<'ast>
     Align(&'ast asm::AstDirectiveAlign),
     Addr(&'ast asm::AstDirectiveAddr),
     Assert(&'ast asm::AstDirectiveAssert),
+    Namespace(&'ast asm::AstDirectiveNamespace),
+    NamespaceEnd(&'ast asm::AstDirectiveNamespaceEnd),
 }
 
 
@@ -56,11 +61,35 @@ pub struct ResolverContext<'iter, 'ast, 'decls>
     pub is_last_iteration: bool,
     pub file_handle_ctx: Option<util::FileServerHandle>,
     pub symbol_ctx: &'decls util::SymbolContext,
+    /// How many anonymous labels appear up to this node,
+    /// for resolving references like `:-` and `:+`
+    pub anonymous_label_count: usize,
     pub bank_ref: util::ItemRef<asm::Bankdef>,
     pub bank_data: &'iter BankData,
 }
 
 
+impl<'ast> ResolverNode<'ast>
+{
+    pub fn macro_expansions(&self) -> &'ast [asm::AstMacroExpansion]
+    {
+        match self
+        {
+            ResolverNode::Symbol(node) => &node.macro_expansions,
+            ResolverNode::Instruction(node) => &node.macro_expansions,
+            ResolverNode::DataElement(node, _) => &node.macro_expansions,
+            ResolverNode::Res(node) => &node.macro_expansions,
+            ResolverNode::Align(node) => &node.macro_expansions,
+            ResolverNode::Addr(node) => &node.macro_expansions,
+            ResolverNode::Assert(node) => &node.macro_expansions,
+            ResolverNode::None |
+            ResolverNode::Namespace(..) |
+            ResolverNode::NamespaceEnd(..) => &[],
+        }
+    }
+}
+
+
 impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
 {
     pub fn new<'defs>(
@@ -89,6 +118,8 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
             is_first_iteration,
             is_last_iteration,
             symbol_ctx: &GLOBAL_SYMBOL_CTX,
+            namespace_ctx_stack: Vec::new(),
+            anonymous_label_count: 0,
             bank_ref: util::ItemRef::new(0),
             bank_data,
         }
@@ -130,6 +161,15 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
                 file_handle_ctx = Some(ast_bank.header_span.file_handle);
             }
 
+            asm::AstAny::DirectiveSection(ast_section) =>
+            {
+                self.bank_ref = ast_section.item_ref.unwrap();
+
+                self.index += 1;
+                node = ResolverNode::None;
+                file_handle_ctx = Some(ast_section.header_span.file_handle);
+            }
+
             asm::AstAny::DirectiveBankdef(ast_bankdef) =>
             {
                 self.bank_ref = ast_bankdef.item_ref.unwrap();
@@ -144,7 +184,15 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
                 let item_ref = ast_symbol.item_ref.unwrap();
                 let decl = decls.symbols.get(item_ref);
 
-                self.symbol_ctx = &decl.ctx;
+                if !ast_symbol.keeps_ctx
+                {
+                    self.symbol_ctx = &decl.ctx;
+                }
+
+                if ast_symbol.is_anonymous
+                {
+                    self.anonymous_label_count += 1;
+                }
 
                 // Honor `labelalign`
                 let bankdef = defs.bankdefs.get(self.bank_ref);
@@ -181,6 +229,24 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
                 file_handle_ctx = Some(ast_symbol.decl_span.file_handle);
             }
 
+            asm::AstAny::DirectiveNamespace(ast_namespace) =>
+            {
+                self.enter_namespace(decls, ast_namespace);
+
+                self.index += 1;
+                node = ResolverNode::Namespace(ast_namespace);
+                file_handle_ctx = Some(ast_namespace.header_span.file_handle);
+            }
+
+            asm::AstAny::DirectiveNamespaceEnd(ast_end) =>
+            {
+                self.exit_namespace();
+
+                self.index += 1;
+                node = ResolverNode::NamespaceEnd(ast_end);
+                file_handle_ctx = Some(ast_end.span.file_handle);
+            }
+
             asm::AstAny::Instruction(ast_instr) =>
             {
                 self.index += 1;
@@ -236,9 +302,13 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
             asm::AstAny::DirectiveIf(..) |
             asm::AstAny::DirectiveInclude(..) |
             asm::AstAny::DirectiveLabelAlign(..) |
+            asm::AstAny::DirectiveLayout(..) |
+            asm::AstAny::DirectiveMacro(..) |
             asm::AstAny::DirectiveNoEmit(..) |
             asm::AstAny::DirectiveOnce(..) |
-            asm::AstAny::DirectiveRuledef(..) =>
+            asm::AstAny::DirectiveRepeat(..) |
+            asm::AstAny::DirectiveRuledef(..) |
+            asm::AstAny::DirectiveTest(..) =>
             {
                 self.index += 1;
                 node = ResolverNode::None;
@@ -252,6 +322,7 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
             is_last_iteration: self.is_last_iteration,
             file_handle_ctx,
             symbol_ctx: self.symbol_ctx,
+            anonymous_label_count: self.anonymous_label_count,
             bank_ref: self.bank_ref,
             bank_data: &self.bank_data[self.bank_ref.0],
         }))
@@ -285,13 +356,39 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
                 let item_ref = ast_symbol.item_ref.unwrap();
                 let decl = decls.symbols.get(item_ref);
 
-                self.symbol_ctx = &decl.ctx;
+                if !ast_symbol.keeps_ctx
+                {
+                    self.symbol_ctx = &decl.ctx;
+                }
+
+                if ast_symbol.is_anonymous
+                {
+                    self.anonymous_label_count += 1;
+                }
 
                 self.index += 1;
                 node = ResolverNode::Symbol(ast_symbol);
                 file_handle_ctx = Some(ast_symbol.decl_span.file_handle);
             }
 
+            asm::AstAny::DirectiveNamespace(ast_namespace) =>
+            {
+                self.enter_namespace(decls, ast_namespace);
+
+                self.index += 1;
+                node = ResolverNode::Namespace(ast_namespace);
+                file_handle_ctx = Some(ast_namespace.header_span.file_handle);
+            }
+
+            asm::AstAny::DirectiveNamespaceEnd(ast_end) =>
+            {
+                self.exit_namespace();
+
+                self.index += 1;
+                node = ResolverNode::NamespaceEnd(ast_end);
+                file_handle_ctx = Some(ast_end.span.file_handle);
+            }
+
             _ =>
             {
                 self.index += 1;
@@ -311,12 +408,37 @@ impl<'ast, 'decls> ResolveIterator<'ast, 'decls>
             is_last_iteration: self.is_last_iteration,
             file_handle_ctx,
             symbol_ctx: self.symbol_ctx,
+            anonymous_label_count: self.anonymous_label_count,
             bank_ref: self.bank_ref,
             bank_data: &DUMMY_BANK_DATA,
         }))
     }
 
 
+    fn enter_namespace(
+        &mut self,
+        decls: &'decls asm::ItemDecls,
+        ast_namespace: &asm::AstDirectiveNamespace)
+    {
+        let decl = decls.symbols.get(ast_namespace.item_ref.unwrap());
+
+        self.namespace_ctx_stack.push(self.symbol_ctx);
+        self.symbol_ctx = &decl.ctx;
+    }
+
+
+    fn exit_namespace(&mut self)
+    {
+        self.symbol_ctx = self.namespace_ctx_stack.pop().unwrap();
+    }
+
+
+    pub fn get_bank_data(&self) -> &[BankData]
+    {
+        &self.bank_data
+    }
+
+
     fn advance_address(
         &mut self,
         report: &mut diagn::Report,
@@ -561,7 +683,13 @@ impl<'iter, 'ast, 'decls> ResolverContext<'iter, 'ast, 'decls>
                 span,
                 &bankdef.addr_start)?;
 
+        // Section addresses can move until placement settles
+        let is_section = asm::Section::find(defs, self.bank_ref).is_some();
+
         Ok(expr::Value::make_integer(addr)
-            .statically_known_if(self.bank_data.cur_position_resolved && !has_excess_bits))
+            .statically_known_if(
+                self.bank_data.cur_position_resolved &&
+                !has_excess_bits &&
+                !is_section))
     }
 }
\ No newline at end of file
@@ -46,4 +46,103 @@ pub fn resolve_label(
         "label address",
         Some(&ast_symbol.name),
         &symbol.value)
-}
\ No newline at end of file
+}
+
+/// Resolves a relative reference like `:-` or `:++` to the
+/// anonymous label that many places before or after the
+/// position where `anonymous_label_count` labels have
+/// been seen, in source order.
+pub fn eval_anonymous_label(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    anonymous_label_count: usize,
+    query: &mut expr::EvalAnonymousLabelQuery)
+    -> Result<expr::Value, ()>
+{
+    let index = {
+        if query.offset < 0
+            { anonymous_label_count.checked_sub(query.offset.unsigned_abs()) }
+        else
+            { Some(anonymous_label_count + query.offset as usize - 1) }
+    };
+
+    let maybe_symbol_ref = index
+        .and_then(|i| decls.anonymous_labels.get(i));
+
+    let Some(&symbol_ref) = maybe_symbol_ref
+    else
+    {
+        query.report.error_span(
+            "unmatched anonymous label reference",
+            query.span);
+
+        return Err(());
+    };
+
+    let symbol = defs.symbols.get(symbol_ref);
+
+    Ok(symbol.value.clone())
+}
+
+
+/// Like `eval_anonymous_label`, but fails if the
+/// label's address isn't known yet.
+pub fn eval_anonymous_label_certain(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    anonymous_label_count: Option<usize>,
+    query: &mut expr::EvalAnonymousLabelQuery)
+    -> Result<expr::Value, ()>
+{
+    let Some(anonymous_label_count) = anonymous_label_count
+    else
+    {
+        query.report.error_span(
+            "cannot reference anonymous labels in this context",
+            query.span);
+
+        return Err(());
+    };
+
+    let value = eval_anonymous_label(
+        decls,
+        defs,
+        anonymous_label_count,
+        query)?;
+
+    if value.is_unknown()
+    {
+        query.report.error_span(
+            "unresolved anonymous label",
+            query.span);
+
+        return Err(());
+    }
+
+    Ok(value)
+}
+
+
+/// Gives the number of anonymous labels before each node,
+/// plus a last entry with the total.
+pub fn count_anonymous_labels(
+    nodes: &[asm::AstAny])
+    -> Vec<usize>
+{
+    let mut counts = Vec::with_capacity(nodes.len() + 1);
+    let mut count = 0;
+
+    counts.push(count);
+
+    for node in nodes
+    {
+        if let asm::AstAny::Symbol(asm::AstSymbol { is_anonymous: true, .. }) = node
+        {
+            count += 1;
+        }
+
+        counts.push(count);
+    }
+
+    counts
+}
@@ -13,12 +13,20 @@ mod constant;
 pub use constant::{
     resolve_constants_simple,
     resolve_constant,
+    resolve_struct,
 };
 
 mod label;
+pub use label::{
+    eval_anonymous_label,
+    eval_anonymous_label_certain,
+    count_anonymous_labels,
+};
+
 mod instruction;
 pub use instruction::{
     finalize_instruction,
+    resolve_encoding,
 };
 
 mod data_block;
@@ -30,6 +38,8 @@ mod res;
 mod align;
 mod addr;
 mod assert;
+mod section;
+pub use section::note_symbol_use;
 
 mod directive_if;
 pub use directive_if::{
@@ -37,6 +47,15 @@ pub use directive_if::{
     check_leftover_ifs,
 };
 
+mod directive_repeat;
+pub use directive_repeat::{
+    RepeatGuess,
+    RepeatRange,
+    resolve_repeats,
+    guess_repeats,
+    check_repeat_guesses,
+};
+
 mod eval;
 pub use eval::{
     eval,
@@ -225,142 +244,32 @@ pub fn resolve_once(
 
     while let Some(ctx) = iter.next(report, decls, defs)?
     {
-        match ctx.node
-        {
-            asm::ResolverNode::None => {}
-            
-            asm::ResolverNode::Symbol(ast_symbol) =>
-            {
-                match ast_symbol.kind
-                {
-                    asm::AstSymbolKind::Constant(_) =>
-                        resolution_state.merge(
-                            resolve_constant(
-                                report,
-                                opts,
-                                fileserver,
-                                ast_symbol,
-                                decls,
-                                defs,
-                                &ctx)?),
-
-                    asm::AstSymbolKind::Label =>
-                        resolution_state.merge(
-                            label::resolve_label(
-                                report,
-                                opts,
-                                ast_symbol,
-                                decls,
-                                defs,
-                                &ctx)?),
-                }
-            }
-        
-            asm::ResolverNode::Instruction(ast_instr) =>
-            {
-                resolution_state.merge(
-                    instruction::resolve_instruction(
-                        report,
-                        opts,
-                        fileserver,
-                        ast_instr,
-                        decls,
-                        defs,
-                        &ctx)?);
-            }
-        
-            asm::ResolverNode::DataElement(ast_data, elem_index) =>
-            {
-                resolution_state.merge(
-                    data_block::resolve_data_element(
-                        report,
-                        opts,
-                        fileserver,
-                        ast_data,
-                        elem_index,
-                        decls,
-                        defs,
-                        &ctx)?);
-            }
-        
-            asm::ResolverNode::Res(ast_res) =>
-            {
-                resolution_state.merge(
-                    res::resolve_res(
-                        report,
-                        opts,
-                        fileserver,
-                        ast_res,
-                        decls,
-                        defs,
-                        &ctx)?);
-
-                if is_last_iteration {
-                    res::finalize_res(
-                        report,
-                        ast_res,
-                        defs,
-                        &ctx)?;
-                }
-            }
-        
-            asm::ResolverNode::Align(ast_align) =>
-            {
-                resolution_state.merge(
-                    align::resolve_align(
-                        report,
-                        opts,
-                        fileserver,
-                        ast_align,
-                        decls,
-                        defs,
-                        &ctx)?);
-
-                if is_last_iteration {
-                    align::finalize_align(
-                        report,
-                        ast_align,
-                        defs,
-                        &ctx)?;
-                }
-            }
-        
-            asm::ResolverNode::Addr(ast_addr) =>
-            {
-                resolution_state.merge(
-                    addr::resolve_addr(
-                        report,
-                        opts,
-                        fileserver,
-                        ast_addr,
-                        decls,
-                        defs,
-                        &ctx)?);
-
-                if is_last_iteration {
-                    addr::finalize_addr(
-                        report,
-                        ast_addr,
-                        defs,
-                        &ctx)?;
-                }
-            }
-        
-            asm::ResolverNode::Assert(ast_assert) =>
-            {
-                resolution_state.merge(
-                    assert::resolve_assert(
-                        report,
-                        opts,
-                        fileserver,
-                        ast_assert,
-                        decls,
-                        defs,
-                        &ctx)?);
-            }
-        }
+        let first_msg = report.len();
+
+        let maybe_state = resolve_node(
+            report,
+            opts,
+            fileserver,
+            decls,
+            defs,
+            &ctx);
+
+        asm::parser::note_expansions(
+            report,
+            first_msg,
+            ctx.node.macro_expansions());
+
+        resolution_state.merge(maybe_state?);
     }
 
+    resolution_state.merge(
+        section::place_sections(
+            report,
+            decls,
+            defs,
+            iter.get_bank_data(),
+            is_last_iteration)?);
+
     if opts.debug_iterations
     {
         println!(
@@ -372,6 +281,178 @@ pub fn resolve_once(
 }
 
 
+fn resolve_node(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &mut asm::ItemDefs,
+    ctx: &ResolverContext)
+    -> Result<asm::ResolutionState, ()>
+{
+    let mut state = asm::ResolutionState::Resolved;
+
+    match ctx.node
+    {
+        asm::ResolverNode::None |
+        asm::ResolverNode::NamespaceEnd(..) => {}
+
+        asm::ResolverNode::Namespace(ast_namespace) =>
+        {
+            state.merge(
+                resolve_struct(
+                    report,
+                    opts,
+                    fileserver,
+                    ast_namespace,
+                    decls,
+                    defs,
+                    ctx)?);
+        }
+        
+        asm::ResolverNode::Symbol(ast_symbol) =>
+        {
+            match ast_symbol.kind
+            {
+                asm::AstSymbolKind::Constant(_) =>
+                    state.merge(
+                        resolve_constant(
+                            report,
+                            opts,
+                            fileserver,
+                            ast_symbol,
+                            decls,
+                            defs,
+                            ctx)?),
+
+                asm::AstSymbolKind::Label =>
+                    state.merge(
+                        label::resolve_label(
+                            report,
+                            opts,
+                            ast_symbol,
+                            decls,
+                            defs,
+                            ctx)?),
+            }
+        }
+    
+        asm::ResolverNode::Instruction(ast_instr) =>
+        {
+            let first_msg = report.len();
+
+            let maybe_state = instruction::resolve_instruction(
+                report,
+                opts,
+                fileserver,
+                ast_instr,
+                decls,
+                defs,
+                ctx);
+
+            asm::parser::clamp_expanded_spans(
+                report,
+                first_msg,
+                ast_instr);
+
+            state.merge(maybe_state?);
+        }
+    
+        asm::ResolverNode::DataElement(ast_data, elem_index) =>
+        {
+            state.merge(
+                data_block::resolve_data_element(
+                    report,
+                    opts,
+                    fileserver,
+                    ast_data,
+                    elem_index,
+                    decls,
+                    defs,
+                    ctx)?);
+        }
+    
+        asm::ResolverNode::Res(ast_res) =>
+        {
+            state.merge(
+                res::resolve_res(
+                    report,
+                    opts,
+                    fileserver,
+                    ast_res,
+                    decls,
+                    defs,
+                    ctx)?);
+
+            if ctx.is_last_iteration {
+                res::finalize_res(
+                    report,
+                    ast_res,
+                    defs,
+                    ctx)?;
+            }
+        }
+    
+        asm::ResolverNode::Align(ast_align) =>
+        {
+            state.merge(
+                align::resolve_align(
+                    report,
+                    opts,
+                    fileserver,
+                    ast_align,
+                    decls,
+                    defs,
+                    ctx)?);
+
+            if ctx.is_last_iteration {
+                align::finalize_align(
+                    report,
+                    ast_align,
+                    defs,
+                    ctx)?;
+            }
+        }
+    
+        asm::ResolverNode::Addr(ast_addr) =>
+        {
+            state.merge(
+                addr::resolve_addr(
+                    report,
+                    opts,
+                    fileserver,
+                    ast_addr,
+                    decls,
+                    defs,
+                    ctx)?);
+
+            if ctx.is_last_iteration {
+                addr::finalize_addr(
+                    report,
+                    ast_addr,
+                    defs,
+                    ctx)?;
+            }
+        }
+    
+        asm::ResolverNode::Assert(ast_assert) =>
+        {
+            state.merge(
+                assert::resolve_assert(
+                    report,
+                    opts,
+                    fileserver,
+                    ast_assert,
+                    decls,
+                    defs,
+                    ctx)?);
+        }
+    }
+
+    Ok(state)
+}
+
+
 pub fn handle_value_resolution(
     opts: &asm::AssemblyOptions,
     report: &mut diagn::Report,
@@ -0,0 +1,241 @@
+use crate::*;
+
+
+/// Records that a symbol was used from the current bank,
+/// so that sections with `keep = false` get placed
+/// whenever something placed references their contents.
+pub fn note_symbol_use(
+    defs: &asm::ItemDefs,
+    ctx: &asm::ResolverContext,
+    symbol: &asm::Symbol)
+{
+    let Some(bank_ref) = symbol.bankdef_ref
+        else { return };
+
+    if bank_ref == ctx.bank_ref || defs.sections.is_empty()
+    {
+        return;
+    }
+
+    defs.bank_references
+        .lock()
+        .unwrap()
+        .insert((ctx.bank_ref.0, bank_ref.0));
+}
+
+
+/// Assigns an address and output offset to every section,
+/// using the sizes reached in the last resolution pass.
+///
+/// Sections are placed in `#layout` order, each into the
+/// first of its banks with enough free space left after
+/// the bank's own contents and previously placed sections.
+/// Sections with `ordered = true` skip the banks listed before
+/// the one the previous section was placed into.
+/// Sections with `keep = false` are dropped unless referenced
+/// from a bank, or from a section that gets placed.
+pub fn place_sections(
+    report: &mut diagn::Report,
+    decls: &asm::ItemDecls,
+    defs: &mut asm::ItemDefs,
+    bank_data: &[asm::resolver::BankData],
+    is_last_iteration: bool)
+    -> Result<asm::ResolutionState, ()>
+{
+    let mut free_positions = bank_data
+        .iter()
+        .map(|b| b.cur_position)
+        .collect::<Vec<_>>();
+
+    let mut resolution_state = asm::ResolutionState::Resolved;
+
+    let referenced = find_referenced_banks(defs);
+
+    for section in &mut defs.sections
+    {
+        let unreferenced = !section.keep && !referenced[section.bank_ref.0];
+
+        if section.unreferenced != unreferenced
+        {
+            if is_last_iteration
+            {
+                report.error_span(
+                    "section placement did not converge",
+                    section.span);
+
+                return Err(());
+            }
+
+            section.unreferenced = unreferenced;
+            resolution_state = asm::ResolutionState::Unresolved;
+        }
+    }
+
+    let mut prev_bank_ref = None;
+
+    for section_index in 0..defs.sections.len()
+    {
+        let section = &defs.sections[section_index];
+
+        if section.discard || section.unreferenced
+        {
+            continue;
+        }
+
+        let span = section.span;
+        let section_ref = section.bank_ref;
+        let size = bank_data[section_ref.0].cur_position;
+
+        let mut placement = None;
+
+        let first_candidate = match prev_bank_ref
+        {
+            Some(prev_bank_ref) if section.ordered =>
+                section.candidate_refs
+                    .iter()
+                    .position(|&c| c == prev_bank_ref)
+                    .unwrap_or(0),
+            _ => 0,
+        };
+
+        for &candidate_ref in &section.candidate_refs[first_candidate..]
+        {
+            let bankdef = defs.bankdefs.get(candidate_ref);
+            let addr_unit = bankdef.addr_unit;
+
+            let free_units = free_positions[candidate_ref.0].div_ceil(addr_unit);
+
+            let free_addr = bankdef.addr_start.checked_add(
+                report,
+                span,
+                &util::BigInt::from(free_units))?;
+
+            let excess = free_addr
+                .checked_mod(
+                    report,
+                    span,
+                    &util::BigInt::from(section.align.max(1)))?
+                .checked_into::<usize>(report, span)?;
+
+            let offset_units = {
+                if excess == 0
+                    { free_units }
+                else
+                    { free_units + section.align - excess }
+            };
+
+            let offset = offset_units * addr_unit;
+
+            let fits = bankdef.size_in_bits
+                .map_or(true, |bank_size| offset + size <= bank_size);
+
+            if fits || placement.is_none()
+            {
+                placement = Some((candidate_ref, offset_units, offset, fits));
+            }
+
+            if fits
+            {
+                break;
+            }
+        }
+
+        let Some((bank_ref, offset_units, offset, fits)) = placement
+            else { continue };
+
+        if !fits && is_last_iteration
+        {
+            report.error_span(
+                format!(
+                    "section `{}` does not fit in any of its banks",
+                    decls.bankdefs.get(section_ref).name),
+                span);
+
+            return Err(());
+        }
+
+        free_positions[bank_ref.0] = offset + size;
+
+        let bankdef = defs.bankdefs.get(bank_ref);
+        let addr_unit = bankdef.addr_unit;
+
+        let addr_start = bankdef.addr_start.checked_add(
+            report,
+            span,
+            &util::BigInt::from(offset_units))?;
+
+        let output_offset = bankdef.output_offset
+            .map(|output_offset| output_offset + offset);
+
+        let section_bankdef = defs.bankdefs.get_mut(section_ref);
+
+        if section_bankdef.addr_start != addr_start ||
+            section_bankdef.output_offset != output_offset ||
+            section_bankdef.addr_unit != addr_unit
+        {
+            if is_last_iteration
+            {
+                report.error_span(
+                    "section placement did not converge",
+                    span);
+
+                return Err(());
+            }
+
+            resolution_state = asm::ResolutionState::Unresolved;
+        }
+
+        section_bankdef.addr_unit = addr_unit;
+        section_bankdef.addr_start = addr_start;
+        section_bankdef.size_in_units = Some(size.div_ceil(addr_unit));
+        section_bankdef.size_in_bits = Some(size);
+        section_bankdef.output_offset = output_offset;
+
+        defs.sections[section_index].placed_in = Some(bank_ref);
+        prev_bank_ref = Some(bank_ref);
+    }
+
+    Ok(resolution_state)
+}
+
+
+/// Finds which banks are reachable through symbol references
+/// starting from the banks that are always output: every
+/// non-section bank, and every kept section not discarded.
+fn find_referenced_banks(defs: &asm::ItemDefs) -> Vec<bool>
+{
+    let mut referenced = vec![false; defs.bankdefs.len()];
+    let mut pending = Vec::new();
+
+    for (bank_index, is_referenced) in referenced.iter_mut().enumerate()
+    {
+        *is_referenced = match defs.sections
+            .iter()
+            .find(|s| s.bank_ref.0 == bank_index)
+        {
+            Some(section) => section.keep && !section.discard,
+            None => true,
+        };
+
+        if *is_referenced
+        {
+            pending.push(bank_index);
+        }
+    }
+
+    let bank_references = defs.bank_references.lock().unwrap();
+
+    while let Some(from_index) = pending.pop()
+    {
+        for &(from, to) in bank_references.iter()
+        {
+            if from == from_index && !referenced[to]
+            {
+                referenced[to] = true;
+                pending.push(to);
+            }
+        }
+    }
+
+    referenced
+}
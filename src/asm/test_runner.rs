@@ -0,0 +1,257 @@
+use crate::*;
+
+
+pub struct TestResult
+{
+    pub name: String,
+    pub passed: bool,
+}
+
+
+struct TestExpectations
+{
+    encoding: Option<util::BitVec>,
+    errors: Vec<TestErrorExpectation>,
+}
+
+
+struct TestErrorExpectation
+{
+    span: diagn::Span,
+    line: usize,
+    excerpt: String,
+}
+
+
+/// Runs every top-level `#test` block in the given files.
+/// Each block is assembled on its own, along with the ruledefs,
+/// functions, macros, and `#bits` directives outside of tests,
+/// and then checked against the `; =` and `; error:` comments
+/// written inside it. Failed tests are reported as errors.
+pub fn run_tests<S>(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    root_filenames: &[S])
+    -> Result<Vec<TestResult>, ()>
+    where S: std::borrow::Borrow<str>
+{
+    let ast = asm::parser::parse_many_and_resolve_includes(
+        report,
+        opts,
+        fileserver,
+        root_filenames)?;
+
+    let shared_nodes = ast.nodes
+        .iter()
+        .filter(|node| matches!(node,
+            asm::AstAny::DirectiveBits(..) |
+            asm::AstAny::DirectiveFn(..) |
+            asm::AstAny::DirectiveMacro(..) |
+            asm::AstAny::DirectiveRuledef(..)))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+
+    for node in &ast.nodes
+    {
+        let asm::AstAny::DirectiveTest(ast_test) = node
+            else { continue };
+
+        let passed = run_test(
+            report,
+            opts,
+            fileserver,
+            &shared_nodes,
+            ast_test)?;
+
+        results.push(TestResult {
+            name: ast_test.name.clone(),
+            passed,
+        });
+    }
+
+    Ok(results)
+}
+
+
+fn run_test(
+    report: &mut diagn::Report,
+    opts: &asm::AssemblyOptions,
+    fileserver: &mut dyn util::FileServer,
+    shared_nodes: &[asm::AstAny],
+    ast_test: &asm::AstDirectiveTest)
+    -> Result<bool, ()>
+{
+    let expectations = extract_expectations(
+        report,
+        fileserver,
+        ast_test.body_span)?;
+
+    let mut ast = asm::AstTopLevel {
+        nodes: shared_nodes.to_vec(),
+    };
+
+    ast.nodes.extend(ast_test.body.nodes.iter().cloned());
+
+    let mut test_report = diagn::Report::new();
+    let mut assembly = asm::AssemblyResult::new();
+
+    if let Ok(()) = asm::parser::expand_macros(&mut test_report, &mut ast)
+    {
+        let _ = asm::assemble_rounds(
+            &mut test_report,
+            opts,
+            fileserver,
+            &mut assembly,
+            ast);
+    }
+
+    let filename = fileserver
+        .get_filename(ast_test.body_span.file_handle)
+        .to_string();
+
+    let mut failures = Vec::new();
+
+    for error in &expectations.errors
+    {
+        if !test_report.has_message_at(
+            fileserver,
+            &filename,
+            diagn::MessageKind::Error,
+            error.line,
+            &error.excerpt)
+        {
+            failures.push(diagn::Message::note_span(
+                format!("expected error `{}`", error.excerpt),
+                error.span));
+        }
+    }
+
+    let has_unexpected_errors = test_report
+        .messages()
+        .iter()
+        .filter(|msg| msg.kind == diagn::MessageKind::Error)
+        .any(|msg| !expectations.errors
+            .iter()
+            .any(|error| test_report.msg_has_error_at(
+                msg,
+                fileserver,
+                &filename,
+                diagn::MessageKind::Error,
+                error.line,
+                &error.excerpt)));
+
+    if let (Some(expected), false) = (&expectations.encoding, has_unexpected_errors)
+    {
+        match &assembly.output
+        {
+            Some(output) =>
+            {
+                if format!("{:x}", output) != format!("{:x}", expected)
+                {
+                    failures.push(diagn::Message::note(format!(
+                        "expected encoding 0x{:x}, got 0x{:x}",
+                        expected,
+                        output)));
+                }
+            }
+
+            None =>
+            {
+                failures.push(diagn::Message::note(format!(
+                    "expected encoding 0x{:x}, got no output",
+                    expected)));
+            }
+        }
+    }
+
+    if failures.len() == 0 && !has_unexpected_errors
+    {
+        return Ok(true);
+    }
+
+    report.push_parent(
+        format!("test `{}` failed", ast_test.name),
+        ast_test.header_span);
+
+    for failure in failures
+    {
+        report.message(failure);
+    }
+
+    for msg in test_report.messages()
+    {
+        report.message(msg.clone());
+    }
+
+    report.pop_parent();
+
+    Ok(false)
+}
+
+
+fn extract_expectations(
+    report: &mut diagn::Report,
+    fileserver: &dyn util::FileServer,
+    body_span: diagn::Span)
+    -> Result<TestExpectations, ()>
+{
+    let mut expectations = TestExpectations {
+        encoding: None,
+        errors: Vec::new(),
+    };
+
+    let src = fileserver.get_str(
+        report,
+        Some(body_span),
+        body_span.file_handle)?;
+
+    let counter = util::CharCounter::new(&src);
+    let location = body_span.location().unwrap();
+    let (line_start, _) = counter.get_line_column_at_byte_index(location.0);
+    let (line_end, _) = counter.get_line_column_at_byte_index(location.1);
+
+    for line in line_start..=line_end
+    {
+        let (byte_start, byte_end) = counter.get_byte_range_of_line(line);
+        let text = &src[byte_start..byte_end];
+
+        if let Some(index) = text.find("; =")
+        {
+            let mut encoding = expectations.encoding
+                .unwrap_or_else(|| util::BitVec::new());
+
+            let value_str = text[(index + 3)..].trim();
+            if value_str != "0x"
+            {
+                let value = syntax::excerpt_as_bigint(
+                    Some(report),
+                    diagn::Span::new(
+                        body_span.file_handle,
+                        byte_start + index + 3,
+                        byte_end),
+                    value_str)?;
+
+                let bit_index = encoding.len();
+                encoding.write_bigint(bit_index, &value);
+            }
+
+            expectations.encoding = Some(encoding);
+        }
+        else if let Some(index) = text.find("; error:")
+        {
+            expectations.errors.push(TestErrorExpectation {
+                span: diagn::Span::new(
+                    body_span.file_handle,
+                    byte_start + index,
+                    byte_end),
+                line,
+                excerpt: text[(index + 8)..].trim().to_string(),
+            });
+        }
+    }
+
+    Ok(expectations)
+}
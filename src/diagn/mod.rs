@@ -6,4 +6,6 @@ pub use self::report::{
     Report,
     Message,
     MessageKind,
-};
\ No newline at end of file
+};
+
+mod report_format;
\ No newline at end of file
@@ -166,7 +166,7 @@ impl Message
 
 impl MessageKind
 {
-	fn get_label(&self) -> &'static str
+	pub fn get_label(&self) -> &'static str
 	{
 		match self
 		{
@@ -462,6 +462,18 @@ impl Report
 	{
 		self.messages.len()
 	}
+
+
+	pub fn messages(&self) -> &[Message]
+	{
+		&self.messages
+	}
+
+
+	pub fn messages_mut(&mut self) -> &mut [Message]
+	{
+		&mut self.messages
+	}
 	
 	
 	pub fn len_with_inner(&self) -> usize
@@ -521,7 +533,7 @@ impl Report
 	}
 	
 	
-	fn msg_has_error_at(
+	pub fn msg_has_error_at(
 		&self,
 		msg: &Message,
 		fileserver: &mut dyn util::FileServer,
@@ -0,0 +1,246 @@
+use crate::*;
+
+
+/// A position in a source file, with
+/// one-based line and column numbers.
+struct Position
+{
+	line: usize,
+	column: usize,
+}
+
+
+struct Location
+{
+	filename: String,
+	range: Option<(Position, Position)>,
+}
+
+
+impl diagn::Report
+{
+	/// Writes every message as a single JSON document,
+	/// keeping the nesting of inner messages.
+	pub fn print_all_json(
+		&self,
+		writer: &mut dyn std::io::Write,
+		fileserver: &dyn util::FileServer)
+	{
+		let messages = self.messages()
+			.iter()
+			.map(|msg| message_to_json(fileserver, msg))
+			.collect::<Vec<_>>();
+
+		let json = serde_json::json!({
+			"messages": messages,
+		});
+
+		writeln!(writer, "{}", json).unwrap();
+	}
+
+
+	/// Writes every message as a SARIF v2.1.0 log, with
+	/// one result per top-level message. Inner messages
+	/// become related locations.
+	pub fn print_all_sarif(
+		&self,
+		writer: &mut dyn std::io::Write,
+		fileserver: &dyn util::FileServer)
+	{
+		let results = self.messages()
+			.iter()
+			.map(|msg| message_to_sarif(fileserver, msg))
+			.collect::<Vec<_>>();
+
+		let json = serde_json::json!({
+			"$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+			"version": "2.1.0",
+			"runs": [{
+				"tool": {
+					"driver": {
+						"name": "customasm",
+						"version": env!("CUSTOMASM_VERSION"),
+						"informationUri": "https://github.com/hlorenzi/customasm",
+					},
+				},
+				"results": results,
+			}],
+		});
+
+		writeln!(writer, "{}", json).unwrap();
+	}
+}
+
+
+fn get_location(
+	fileserver: &dyn util::FileServer,
+	span: &diagn::Span)
+	-> Location
+{
+	let filename = fileserver
+		.get_filename(span.file_handle)
+		.to_string();
+
+	let range = span.location().and_then(|(start, end)| {
+		let src = fileserver.get_str(
+			&mut diagn::Report::new(),
+			None,
+			span.file_handle)
+			.ok()?;
+
+		let counter = util::CharCounter::new(&src);
+		let (line1, col1) = counter.get_line_column_at_byte_index(start);
+		let (line2, col2) = counter.get_line_column_at_byte_index(end);
+
+		Some((
+			Position { line: line1 + 1, column: col1 + 1 },
+			Position { line: line2 + 1, column: col2 + 1 }))
+	});
+
+	Location {
+		filename,
+		range,
+	}
+}
+
+
+fn message_to_json(
+	fileserver: &dyn util::FileServer,
+	msg: &diagn::Message)
+	-> serde_json::Value
+{
+	let mut json = serde_json::json!({
+		"kind": msg.kind.get_label(),
+		"description": msg.descr,
+	});
+
+	if let Some(span) = &msg.span
+	{
+		let location = get_location(fileserver, span);
+
+		json["file"] = serde_json::json!(location.filename);
+
+		if let Some((start, end)) = location.range
+		{
+			json["range"] = serde_json::json!({
+				"start": { "line": start.line, "column": start.column },
+				"end": { "line": end.line, "column": end.column },
+			});
+		}
+	}
+
+	json["inner"] = msg.inner
+		.iter()
+		.map(|inner| message_to_json(fileserver, inner))
+		.collect();
+
+	json
+}
+
+
+fn message_to_sarif(
+	fileserver: &dyn util::FileServer,
+	msg: &diagn::Message)
+	-> serde_json::Value
+{
+	let level = {
+		match msg.kind
+		{
+			diagn::MessageKind::Error => "error",
+			diagn::MessageKind::Warning => "warning",
+			diagn::MessageKind::Note => "note",
+		}
+	};
+
+	let mut text = msg.descr.clone();
+	let mut related = Vec::new();
+
+	collect_sarif_related(
+		fileserver,
+		&msg.inner,
+		&mut text,
+		&mut related);
+
+	let locations = {
+		match &msg.span
+		{
+			Some(span) => vec![sarif_location(fileserver, span)],
+			None => Vec::new(),
+		}
+	};
+
+	serde_json::json!({
+		"level": level,
+		"message": { "text": text },
+		"locations": locations,
+		"relatedLocations": related,
+	})
+}
+
+
+/// Flattens the inner-message tree. Messages with
+/// a span become related locations, and the others
+/// are appended to the result's text.
+fn collect_sarif_related(
+	fileserver: &dyn util::FileServer,
+	inner: &[diagn::Message],
+	text: &mut String,
+	related: &mut Vec<serde_json::Value>)
+{
+	for msg in inner
+	{
+		match &msg.span
+		{
+			Some(span) =>
+			{
+				let mut location = sarif_location(fileserver, span);
+
+				location["id"] = serde_json::json!(related.len());
+				location["message"] = serde_json::json!({
+					"text": format!("{}: {}", msg.kind.get_label(), msg.descr),
+				});
+
+				related.push(location);
+			}
+
+			None =>
+			{
+				text.push_str("\n");
+				text.push_str(&msg.descr);
+			}
+		}
+
+		collect_sarif_related(
+			fileserver,
+			&msg.inner,
+			text,
+			related);
+	}
+}
+
+
+fn sarif_location(
+	fileserver: &dyn util::FileServer,
+	span: &diagn::Span)
+	-> serde_json::Value
+{
+	let location = get_location(fileserver, span);
+
+	let mut physical = serde_json::json!({
+		"artifactLocation": { "uri": location.filename.replace("\\", "/") },
+	});
+
+	if let Some((start, end)) = location.range
+	{
+		physical["region"] = serde_json::json!({
+			"startLine": start.line,
+			"startColumn": start.column,
+			"endLine": end.line,
+			"endColumn": end.column,
+		});
+	}
+
+	serde_json::json!({
+		"physicalLocation": physical,
+	})
+}
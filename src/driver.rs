@@ -6,8 +6,16 @@ pub struct Command
 	pub input_filenames: Vec<String>,
 	pub output_groups: Vec<CommandOutput>,
 	pub opts: asm::AssemblyOptions,
+	pub disassemble_filename: Option<String>,
+	pub link: bool,
+	pub test: bool,
+	pub depfile_filename: Option<String>,
+	pub depfile_per_output: bool,
+	pub size_report: bool,
+	pub watch: bool,
 	pub quiet: bool,
 	pub use_colors: bool,
+	pub diagnostics_format: DiagnosticsFormat,
 	pub show_version: bool,
 	pub show_help: bool,
 }
@@ -21,10 +29,20 @@ pub struct CommandOutput
 }
 
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DiagnosticsFormat
+{
+	Human,
+	Json,
+	Sarif,
+}
+
+
 pub enum OutputFormat
 {
 	Binary,
 	Annotated(util::FormatAnnotatedOptions),
+	Listing,
 	BinStr,
 	HexStr,
 	BinDump,
@@ -33,6 +51,9 @@ pub enum OutputFormat
 	IntelHex {
 		address_unit: usize,
 	},
+	SRecord {
+		addr_bytes: usize,
+	},
 	ReadMemB(util::FormatReadMemOptions),
 	ReadMemH(util::FormatReadMemOptions),
 	List(util::FormatListOptions),
@@ -50,8 +71,21 @@ pub enum OutputFormat
 		group: usize,
 	},
 
+	Elf(util::FormatElfOptions),
+
+	Object,
+
+	Map,
+
 	Symbols,
+	SymbolsC,
+	SymbolsRust,
+	SymbolsPython,
 	SymbolsMesenMlb,
+	SymbolsRgbdsSym,
+	SymbolsViceLabels,
+	SymbolsMameScript,
+	SymbolsNocashSym,
 }
 
 
@@ -66,19 +100,31 @@ pub fn drive_from_commandline(
 
 	let maybe_command = parse_command(
 		&mut report,
-		args);
+		args,
+		fileserver);
 
 	if let Ok(command) = maybe_command
 	{
+		// Track every file opened, for `--watch`
+		let mut recorder = util::FileServerRecorder::new(fileserver);
+
 		let maybe_result = assemble_with_command(
 			&mut report,
-			fileserver,
+			&mut recorder,
 			&command);
 
-		report.print_all(
-			&mut std::io::stderr(),
-			fileserver,
-			command.use_colors);
+		print_report(
+			&report,
+			&recorder,
+			&command);
+
+		if command.watch && is_assembling(&command)
+		{
+			watch_with_command(
+				report,
+				&mut recorder,
+				&command);
+		}
 
 		maybe_result.map(|_| ())
 	}
@@ -94,6 +140,238 @@ pub fn drive_from_commandline(
 }
 
 
+fn print_report(
+	report: &diagn::Report,
+	fileserver: &dyn util::FileServer,
+	command: &Command)
+{
+	match command.diagnostics_format
+	{
+		DiagnosticsFormat::Human =>
+			report.print_all(
+				&mut std::io::stderr(),
+				fileserver,
+				command.use_colors),
+
+		DiagnosticsFormat::Json =>
+			report.print_all_json(
+				&mut std::io::stderr(),
+				fileserver),
+
+		DiagnosticsFormat::Sarif =>
+			report.print_all_sarif(
+				&mut std::io::stderr(),
+				fileserver),
+	}
+}
+
+
+/// Whether the command reads any files, as opposed to
+/// just printing help or version information, which
+/// `--watch` would otherwise wait on forever.
+fn is_assembling(command: &Command) -> bool
+{
+	!command.show_help &&
+		!command.show_version &&
+		command.input_filenames.len() > 0
+}
+
+
+const WATCH_POLL_INTERVAL: std::time::Duration =
+	std::time::Duration::from_millis(250);
+
+
+/// Polls the modification times of every file read so far,
+/// and assembles again whenever any of them changes.
+/// Only returns when interrupted.
+fn watch_with_command(
+	mut report: diagn::Report,
+	fileserver: &mut util::FileServerRecorder,
+	command: &Command)
+{
+	loop
+	{
+		let filenames = get_watched_filenames(
+			command,
+			fileserver);
+
+		if !command.quiet
+		{
+			println!(
+				"watching {} file{} for changes...",
+				filenames.len(),
+				if filenames.len() == 1 { "" } else { "s" });
+		}
+
+		let mtimes = get_modified_times(&filenames);
+
+		while get_modified_times(&filenames) == mtimes
+		{
+			std::thread::sleep(WATCH_POLL_INTERVAL);
+		}
+
+		if !command.quiet
+		{
+			println!("");
+		}
+
+		let mut new_report = diagn::Report::new();
+
+		let _ = assemble_with_command(
+			&mut new_report,
+			fileserver,
+			command);
+
+		print_report(
+			&new_report,
+			fileserver,
+			command);
+
+		print_watch_summary(
+			&report,
+			&new_report,
+			fileserver);
+
+		report = new_report;
+	}
+}
+
+
+fn get_watched_filenames(
+	command: &Command,
+	fileserver: &util::FileServerRecorder)
+	-> Vec<String>
+{
+	let mut filenames = command.input_filenames.clone();
+
+	for filename in fileserver.get_opened_filenames()
+	{
+		if !filenames.contains(filename)
+		{
+			filenames.push(filename.clone());
+		}
+	}
+
+	filenames
+}
+
+
+/// Gets the modification time of each file, or `None` for
+/// files that can't be read, so that creating or deleting
+/// a file also counts as a change.
+pub fn get_modified_times(
+	filenames: &[String])
+	-> Vec<Option<std::time::SystemTime>>
+{
+	filenames
+		.iter()
+		.map(|filename| std::fs::metadata(filename)
+			.and_then(|metadata| metadata.modified())
+			.ok())
+		.collect()
+}
+
+
+fn print_watch_summary(
+	old_report: &diagn::Report,
+	new_report: &diagn::Report,
+	fileserver: &dyn util::FileServer)
+{
+	for line in get_watch_summary(old_report, new_report, fileserver)
+	{
+		println!("{}", line);
+	}
+}
+
+
+/// Describes each diagnostic that wasn't there in the
+/// previous run and each one that went away, followed
+/// by a line with the totals.
+pub fn get_watch_summary(
+	old_report: &diagn::Report,
+	new_report: &diagn::Report,
+	fileserver: &dyn util::FileServer)
+	-> Vec<String>
+{
+	let old_summaries = old_report
+		.messages()
+		.iter()
+		.map(|msg| get_message_summary(msg, fileserver))
+		.collect::<Vec<_>>();
+
+	let new_summaries = new_report
+		.messages()
+		.iter()
+		.map(|msg| get_message_summary(msg, fileserver))
+		.collect::<Vec<_>>();
+
+	let added = new_summaries
+		.iter()
+		.filter(|s| !old_summaries.contains(s))
+		.collect::<Vec<_>>();
+
+	let fixed = old_summaries
+		.iter()
+		.filter(|s| !new_summaries.contains(s))
+		.collect::<Vec<_>>();
+
+	let mut lines = Vec::new();
+
+	for summary in &added
+	{
+		lines.push(format!("  new: {}", summary));
+	}
+
+	for summary in &fixed
+	{
+		lines.push(format!("  fixed: {}", summary));
+	}
+
+	lines.push(format!(
+		"{} new, {} fixed, {} remaining",
+		added.len(),
+		fixed.len(),
+		new_summaries.len() - added.len()));
+
+	lines
+}
+
+
+/// Describes a diagnostic in a single line, without line numbers,
+/// so it can be matched across edits that shift code around.
+fn get_message_summary(
+	msg: &diagn::Message,
+	fileserver: &dyn util::FileServer)
+	-> String
+{
+	let mut descr = msg.descr.clone();
+	let mut innermost = msg;
+
+	while let Some(inner) = innermost.inner
+		.iter()
+		.find(|inner| inner.kind == innermost.kind)
+	{
+		descr.push_str(": ");
+		descr.push_str(&inner.descr);
+		innermost = inner;
+	}
+
+	match innermost.span.or(msg.span)
+	{
+		Some(span) => format!(
+			"{}: {}: {}",
+			msg.kind.get_label(),
+			fileserver.get_filename(span.file_handle),
+			descr),
+
+		None => format!(
+			"{}: {}",
+			msg.kind.get_label(),
+			descr),
+	}
+}
+
+
 pub fn drive(
 	report: &mut diagn::Report,
 	args: &Vec<String>,
@@ -102,7 +380,8 @@ pub fn drive(
 {
 	let command = parse_command(
 		report,
-		args)?;
+		args,
+		fileserver)?;
 
 	let maybe_result = assemble_with_command(
 		report,
@@ -123,7 +402,8 @@ pub fn drive_with_modified_command<F>(
 {
 	let mut command = parse_command(
 		report,
-		args)?;
+		args,
+		fileserver)?;
 
 	modify_command(&mut command);
 
@@ -166,48 +446,222 @@ fn assemble_with_command(
 
 		for filename in &command.input_filenames
 		{
-			println!("assembling `{}`...", filename);
+			if command.link
+			{
+				println!("linking `{}`...", filename);
+			}
+			else if command.test
+			{
+				println!("testing `{}`...", filename);
+			}
+			else
+			{
+				println!("assembling `{}`...", filename);
+			}
 		}
 	}
 
-	let assembly = asm::assemble(
-		report,
-		&command.opts,
-		fileserver,
-		&command.input_filenames);
+	if command.test
+	{
+		return test_with_command(
+			report,
+			fileserver,
+			command);
+	}
+
+	// Track every file opened, for the depfile
+	let mut recorder = util::FileServerRecorder::new(fileserver);
+	let fileserver: &mut dyn util::FileServer = &mut recorder;
+
+	let assembly = {
+		if command.link
+		{
+			asm::link(
+				report,
+				&command.opts,
+				fileserver,
+				&command.input_filenames)
+		}
+		else
+		{
+			asm::assemble(
+				report,
+				&command.opts,
+				fileserver,
+				&command.input_filenames)
+		}
+	};
+
+	if let Some(cache) = &command.opts.cache
+	{
+		cache
+			.lock()
+			.unwrap()
+			.save(report)?;
+	}
 
 	let output = assembly.output
 		.as_ref()
 		.ok_or(())?;
 
+	let ast = assembly.ast.as_ref().unwrap();
 	let decls = assembly.decls.as_ref().unwrap();
 	let defs = assembly.defs.as_ref().unwrap();
 	let iterations_taken = assembly.iterations_taken.unwrap();
 
-	for output_group in &command.output_groups
+	if let Some(ref disasm_filename) = command.disassemble_filename
 	{
-		if let Some(format) = &output_group.format
+		disassemble_with_command(
+			report,
+			fileserver,
+			command,
+			defs,
+			disasm_filename)?;
+	}
+	else
+	{
+		for output_group in &command.output_groups
 		{
-			let formatted = format_output(
-				report,
-				fileserver,
-				decls,
-				defs,
-				output,
-				format);
-
-			if output_group.printout
+			if let Some(format) = &output_group.format
 			{
-				if !command.quiet
+				let formatted = format_output(
+					report,
+					fileserver,
+					&command.opts,
+					ast,
+					decls,
+					defs,
+					output,
+					format)?;
+
+				if output_group.printout
 				{
-					println!("");
+					if !command.quiet
+					{
+						println!("");
+					}
+
+					println!(
+						"{}",
+						String::from_utf8_lossy(&formatted));
 				}
+				else if let Some(ref output_filename) = output_group.output_filename
+				{
+					if !command.quiet
+					{
+						println!("writing `{}`...", &output_filename);
+					}
 
-				println!(
-					"{}",
-					String::from_utf8_lossy(&formatted));
+					fileserver.write_bytes(
+						report,
+						None,
+						&output_filename,
+						&formatted)?;
+				}
 			}
-			else if let Some(ref output_filename) = output_group.output_filename
+		}
+	}
+
+	if command.size_report &&
+		command.disassemble_filename.is_none()
+	{
+		if !command.quiet
+		{
+			println!("");
+		}
+
+		print!("{}", util::format_map(decls, defs, output));
+	}
+
+	write_depfiles_with_command(
+		report,
+		&mut recorder,
+		command)?;
+
+	if !command.quiet
+	{
+		println!(
+			"resolved in {} iteration{}",
+			iterations_taken,
+			if iterations_taken == 1 { "" } else { "s" });
+	}
+
+	Ok(assembly)
+}
+
+
+fn test_with_command(
+	report: &mut diagn::Report,
+	fileserver: &mut dyn util::FileServer,
+	command: &Command)
+	-> Result<asm::AssemblyResult, ()>
+{
+	let results = asm::run_tests(
+		report,
+		&command.opts,
+		fileserver,
+		&command.input_filenames)?;
+
+	for result in &results
+	{
+		println!(
+			"test `{}`... {}",
+			result.name,
+			if result.passed { "ok" } else { "FAILED" });
+	}
+
+	let failed_count = results
+		.iter()
+		.filter(|r| !r.passed)
+		.count();
+
+	println!(
+		"{} passed, {} failed",
+		results.len() - failed_count,
+		failed_count);
+
+	if failed_count > 0
+	{
+		return Err(());
+	}
+
+	Ok(asm::AssemblyResult::new())
+}
+
+
+fn disassemble_with_command(
+	report: &mut diagn::Report,
+	fileserver: &mut dyn util::FileServer,
+	command: &Command,
+	defs: &asm::ItemDefs,
+	disasm_filename: &str)
+	-> Result<(), ()>
+{
+	if !command.quiet
+	{
+		println!("disassembling `{}`...", disasm_filename);
+	}
+
+	let file_handle = fileserver.get_handle(
+		report,
+		None,
+		disasm_filename)?;
+
+	let data = fileserver.get_bytes(
+		report,
+		None,
+		file_handle)?;
+
+	let disassembly = asm::disassemble(
+		report,
+		defs,
+		&data)?;
+
+	for output_group in &command.output_groups
+	{
+		match output_group.output_filename
+		{
+			Some(ref output_filename) if !output_group.printout =>
 			{
 				if !command.quiet
 				{
@@ -218,20 +672,141 @@ fn assemble_with_command(
 					report,
 					None,
 					&output_filename,
-					&formatted)?;
+					&disassembly.as_bytes().to_vec())?;
+			}
+
+			_ =>
+			{
+				if !command.quiet
+				{
+					println!("");
+				}
+
+				print!("{}", disassembly);
 			}
 		}
 	}
 
-	if !command.quiet
+	Ok(())
+}
+
+
+fn write_depfiles_with_command(
+	report: &mut diagn::Report,
+	fileserver: &mut util::FileServerRecorder,
+	command: &Command)
+	-> Result<(), ()>
+{
+	use util::FileServer;
+
+	if command.depfile_filename.is_none() && !command.depfile_per_output
 	{
-		println!(
-			"resolved in {} iteration{}",
-			iterations_taken,
-			if iterations_taken == 1 { "" } else { "s" });
+		return Ok(());
 	}
 
-	Ok(assembly)
+	let deps = fileserver
+		.get_opened_filenames()
+		.to_vec();
+
+	let targets = command.output_groups
+		.iter()
+		.filter(|group| !group.printout)
+		.filter_map(|group| group.output_filename.as_ref())
+		.collect::<Vec<_>>();
+
+	let mut depfiles = Vec::new();
+
+	if let Some(ref depfile_filename) = command.depfile_filename
+	{
+		if targets.len() == 0
+		{
+			report.error("no output files to write dependencies for");
+			return Err(());
+		}
+
+		depfiles.push((
+			depfile_filename.clone(),
+			format_depfile(&targets, &deps)));
+	}
+
+	if command.depfile_per_output
+	{
+		for target in &targets
+		{
+			depfiles.push((
+				format!("{}.d", target),
+				format_depfile(&[target], &deps)));
+		}
+	}
+
+	for (depfile_filename, contents) in depfiles
+	{
+		if !command.quiet
+		{
+			println!("writing `{}`...", &depfile_filename);
+		}
+
+		fileserver.write_bytes(
+			report,
+			None,
+			&depfile_filename,
+			&contents.into_bytes())?;
+	}
+
+	Ok(())
+}
+
+
+/// Formats a Makefile rule for each target, followed by
+/// an empty rule for each dependency, so that deleted or
+/// virtual files (like `<std>/...`) don't break the build.
+fn format_depfile(
+	targets: &[&String],
+	deps: &[String])
+	-> String
+{
+	let deps_str = deps
+		.iter()
+		.map(|dep| format!(" {}", escape_depfile_path(dep)))
+		.collect::<String>();
+
+	let mut result = String::new();
+
+	for target in targets
+	{
+		result.push_str(&format!(
+			"{}:{}\n",
+			escape_depfile_path(target),
+			deps_str));
+	}
+
+	for dep in deps
+	{
+		result.push_str(&format!(
+			"\n{}:\n",
+			escape_depfile_path(dep)));
+	}
+
+	result
+}
+
+
+fn escape_depfile_path(path: &str) -> String
+{
+	let mut result = String::new();
+
+	for c in path.replace("\\", "/").chars()
+	{
+		match c
+		{
+			' ' => result.push_str("\\ "),
+			'#' => result.push_str("\\#"),
+			'$' => result.push_str("$$"),
+			_ => result.push(c),
+		}
+	}
+
+	result
 }
 
 
@@ -263,6 +838,42 @@ fn make_opts() -> getopts::Options
 		getopts::HasArg::Maybe,
 		getopts::Occur::Optional);
 
+	opts.optopt(
+		"", "disassemble",
+		"Disassemble the given binary file using the ruledefs\n\
+		from the input files, instead of producing an output.",
+		"FILE");
+
+	opts.optopt(
+		"", "depfile",
+		"Write a Makefile rule listing every file read\n\
+		during assembly as a dependency of the outputs.",
+		"FILE");
+
+	opts.optflag(
+		"", "MD",
+		"Write a Makefile dependency rule next to each output file.");
+
+	opts.optopt(
+		"", "cache-dir",
+		"Keep instruction matching results in the given directory,\n\
+		so that later runs only redo work for edited code.",
+		"DIR");
+
+	opts.optflag(
+		"", "watch",
+		"Keep running, and assemble again whenever\n\
+		one of the files read during assembly changes.");
+
+	opts.optflag(
+		"", "size-report",
+		"Print how much of each bank is used, and the size of each label.");
+
+	opts.optflag(
+		"c", "object",
+		"Assemble into a relocatable object file, allowing\n\
+		references to symbols defined in other objects.");
+
 	opts.optflag(
 		"p", "print",
 		"Print the output to the screen instead of writing to a file.");
@@ -278,6 +889,23 @@ fn make_opts() -> getopts::Options
 		getopts::HasArg::Yes,
 		getopts::Occur::Multi);
 
+	opts.opt(
+		"I", "include-path",
+		"Search the given directory for included files.",
+		"DIR",
+		getopts::HasArg::Yes,
+		getopts::Occur::Multi);
+
+	opts.optopt(
+		"", "project",
+		"Read options from the given project file.",
+		"FILE");
+
+	opts.optopt(
+		"", "profile",
+		"Apply the defines of a profile from the project file.",
+		"NAME");
+
 	opts.opt(
 		"", "color",
 		"Style the output with colors. [on/off]",
@@ -285,6 +913,13 @@ fn make_opts() -> getopts::Options
 		getopts::HasArg::Maybe,
 		getopts::Occur::Optional);
 
+	opts.opt(
+		"", "diagnostics-format",
+		"The format of error and warning messages. [human/json/sarif]",
+		"FORMAT",
+		getopts::HasArg::Yes,
+		getopts::Occur::Optional);
+
 	opts.opt(
 		"", "legacy",
 		"Use legacy behavior. [on/off]",
@@ -304,6 +939,10 @@ fn make_opts() -> getopts::Options
 		"", "debug-no-optimize-matcher",
 		"Prevent optimization of the instruction matcher algorithm.");
 
+	opts.optflag(
+		"", "debug-no-parallel-matcher",
+		"Prevent matching instructions on multiple threads.");
+
     opts.optflag(
 		"v", "version",
 		"Display version information.");
@@ -318,9 +957,25 @@ fn make_opts() -> getopts::Options
 
 fn parse_command(
 	report: &mut diagn::Report,
-	args: &Vec<String>)
+	args: &Vec<String>,
+	fileserver: &mut dyn util::FileServer)
 	-> Result<Command, ()>
 {
+	// getopts only accepts single-letter short options
+	let mut args = args
+		.iter()
+		.map(|arg| if arg == "-MD" { "--MD".to_string() } else { arg.clone() })
+		.collect::<Vec<_>>();
+
+	// Detect the `link` and `test` subcommands
+	let link = args.get(1).map(|arg| arg.as_ref()) == Some("link");
+	let test = args.get(1).map(|arg| arg.as_ref()) == Some("test");
+
+	if link || test
+	{
+		args.remove(1);
+	}
+
 	let args_groups = args[1..]
 		.split(|arg| arg == "--")
 		.collect::<Vec<_>>();
@@ -329,12 +984,24 @@ fn parse_command(
 		input_filenames: Vec::new(),
 		output_groups: Vec::new(),
 		opts: asm::AssemblyOptions::new(),
+		disassemble_filename: None,
+		link,
+		test,
+		depfile_filename: None,
+		depfile_per_output: false,
+		size_report: false,
+		watch: false,
 		quiet: false,
 		use_colors: true,
+		diagnostics_format: DiagnosticsFormat::Human,
 		show_version: false,
 		show_help: false,
 	};
 
+	let mut project_filename = None;
+	let mut profile_name = None;
+	let mut iters_given = false;
+
 	let parse_opts = make_opts();
 
 	for arg_group in args_groups
@@ -372,13 +1039,50 @@ fn parse_command(
 				output_filename.clone());
 		}
 
-		group.printout |= parsed.opt_present("p");
+		group.printout |= parsed.opt_present("p");
+
+
+		// Parse global command options
+		command.quiet |= parsed.opt_present("q");
+		command.show_version |= parsed.opt_present("v");
+		command.show_help |= parsed.opt_present("h");
+
+		if let Some(disasm_filename) = parsed.opt_str("disassemble")
+		{
+			command.disassemble_filename = Some(disasm_filename);
+		}
+
+		if let Some(depfile_filename) = parsed.opt_str("depfile")
+		{
+			command.depfile_filename = Some(depfile_filename);
+		}
+
+		command.depfile_per_output |= parsed.opt_present("MD");
+
+		command.size_report |= parsed.opt_present("size-report");
+
+		command.watch |= parsed.opt_present("watch");
+
+		if let Some(cache_dir) = parsed.opt_str("cache-dir")
+		{
+			command.opts.cache = Some(std::sync::Arc::new(std::sync::Mutex::new(
+				asm::AssemblyCache::new_with_dir(cache_dir))));
+		}
+
+		command.opts.allow_external_symbols |= parsed.opt_present("c");
 
+		command.opts.include_paths.extend(
+			parsed.opt_strs("I"));
 
-		// Parse global command options
-		command.quiet |= parsed.opt_present("q");
-		command.show_version |= parsed.opt_present("v");
-		command.show_help |= parsed.opt_present("h");
+		if let Some(filename) = parsed.opt_str("project")
+		{
+			project_filename = Some(filename);
+		}
+
+		if let Some(name) = parsed.opt_str("profile")
+		{
+			profile_name = Some(name);
+		}
 
 		for define_arg in parsed.opt_strs("d")
 		{
@@ -414,6 +1118,11 @@ fn parse_command(
 		command.opts.optimize_instruction_matching &=
 			!parsed.opt_present("debug-no-optimize-matcher");
 
+		if parsed.opt_present("debug-no-parallel-matcher")
+		{
+			command.opts.matcher_thread_count = 1;
+		}
+
 		if parsed.opt_present("color")
 		{
 			command.use_colors = {
@@ -431,8 +1140,26 @@ fn parse_command(
 			};
 		}
 
+		if let Some(format) = parsed.opt_str("diagnostics-format")
+		{
+			command.diagnostics_format = {
+				match format.as_ref()
+				{
+					"human" => DiagnosticsFormat::Human,
+					"json" => DiagnosticsFormat::Json,
+					"sarif" => DiagnosticsFormat::Sarif,
+					_ =>
+					{
+						report.error("invalid argument for `--diagnostics-format`");
+						return Err(());
+					}
+				}
+			};
+		}
+
 		if let Some(t) = parsed.opt_str("t")
 		{
+			iters_given = true;
 			command.opts.max_iterations = {
 				match t.parse::<usize>()
 				{
@@ -458,6 +1185,15 @@ fn parse_command(
 	}
 
 
+	apply_project_file(
+		report,
+		fileserver,
+		&mut command,
+		project_filename,
+		profile_name,
+		iters_given)?;
+
+
 	// Set the default format for each group,
 	// if none were specified
 	for group in &mut command.output_groups
@@ -473,14 +1209,26 @@ fn parse_command(
 					display_labels: true,
 				}));
 			}
+			else if command.opts.allow_external_symbols
+			{
+				group.format = Some(OutputFormat::Object);
+			}
 			else
 			{
 				group.format = Some(OutputFormat::Binary);
 			}
 		}
 
+		if command.opts.allow_external_symbols &&
+			!matches!(group.format, Some(OutputFormat::Object))
+		{
+			report.error("`--object` can only be used with the `object` format");
+			return Err(());
+		}
+
 		if !group.printout &&
 			group.output_filename.is_none() &&
+			command.disassemble_filename.is_none() &&
 			command.input_filenames.len() >= 1
 		{
 			group.output_filename = Some(derive_output_filename(
@@ -491,10 +1239,447 @@ fn parse_command(
 	}
 
 
+	// Rebuilds in watch mode reuse unchanged work in memory
+	if command.watch && command.opts.cache.is_none()
+	{
+		command.opts.cache = Some(std::sync::Arc::new(std::sync::Mutex::new(
+			asm::AssemblyCache::new())));
+	}
+
+
+	if command.link && command.opts.allow_external_symbols
+	{
+		report.error("`--object` cannot be used with `link`");
+		return Err(());
+	}
+
+
+	if command.test && command.opts.allow_external_symbols
+	{
+		report.error("`--object` cannot be used with `test`");
+		return Err(());
+	}
+
+
 	Ok(command)
 }
 
 
+const PROJECT_FILENAME: &str = "customasm.toml";
+
+
+/// Fills in whatever wasn't given on the command-line
+/// from a project file, named by `--project` or else
+/// found in the working directory.
+fn apply_project_file(
+	report: &mut diagn::Report,
+	fileserver: &mut dyn util::FileServer,
+	command: &mut Command,
+	project_filename: Option<String>,
+	profile_name: Option<String>,
+	iters_given: bool)
+	-> Result<(), ()>
+{
+	let project_filename = {
+		match project_filename
+		{
+			Some(filename) => filename,
+			None =>
+			{
+				let found = fileserver.get_handle(
+					&mut diagn::Report::new(),
+					None,
+					PROJECT_FILENAME);
+
+				if found.is_err()
+				{
+					if profile_name.is_some()
+					{
+						report.error(
+							format!(
+								"`--profile` needs a project file, but `{}` was not found",
+								PROJECT_FILENAME));
+
+						return Err(());
+					}
+
+					return Ok(());
+				}
+
+				PROJECT_FILENAME.to_string()
+			}
+		}
+	};
+
+	let file_handle = fileserver.get_handle(
+		report,
+		None,
+		&project_filename)?;
+
+	let src = fileserver.get_str(
+		report,
+		None,
+		file_handle)?;
+
+	let project = {
+		match src.parse::<toml::Table>()
+		{
+			Ok(project) => project,
+			Err(err) =>
+			{
+				let descr = format!(
+					"invalid project file: {}",
+					err.message());
+
+				match err.span()
+				{
+					Some(range) => report.error_span(
+						descr,
+						diagn::Span::new(file_handle, range.start, range.end)),
+
+					None => report.error(descr),
+				}
+
+				return Err(());
+			}
+		}
+	};
+
+	// Paths are relative to the project file
+	let project_dir = std::path::Path::new(&project_filename)
+		.parent()
+		.map(|dir| dir.to_string_lossy().replace("\\", "/"))
+		.filter(|dir| dir.len() > 0);
+
+	let resolve_path = |path: &str| {
+		// Also takes `/dir` as absolute on Windows,
+		// where it's relative to the current drive
+		let is_absolute =
+			std::path::Path::new(path).is_absolute() ||
+			std::path::Path::new(path).has_root();
+
+		match &project_dir
+		{
+			Some(dir) if !is_absolute => format!("{}/{}", dir, path),
+			_ => path.to_string(),
+		}
+	};
+
+	let mut defines = Vec::new();
+
+	for (key, value) in &project
+	{
+		match key.as_ref()
+		{
+			"input" =>
+			{
+				let filenames = get_project_strings(
+					report,
+					&project_filename,
+					key,
+					value)?;
+
+				if command.input_filenames.len() == 0
+				{
+					command.input_filenames = filenames
+						.iter()
+						.map(|f| resolve_path(f))
+						.collect();
+				}
+			}
+
+			"include-paths" =>
+			{
+				let paths = get_project_strings(
+					report,
+					&project_filename,
+					key,
+					value)?;
+
+				command.opts.include_paths.extend(paths
+					.iter()
+					.map(|p| resolve_path(p)));
+			}
+
+			"iters" =>
+			{
+				let iters = value
+					.as_integer()
+					.filter(|i| *i > 0);
+
+				let Some(iters) = iters
+					else {
+						return error_project_value(report, &project_filename, key);
+					};
+
+				if !iters_given
+				{
+					command.opts.max_iterations = iters as usize;
+				}
+			}
+
+			"defines" =>
+			{
+				add_project_defines(
+					report,
+					&project_filename,
+					key,
+					value,
+					&mut defines)?;
+			}
+
+			"outputs" =>
+			{
+				let Some(outputs) = value.as_table()
+					else {
+						return error_project_value(report, &project_filename, key);
+					};
+
+				let cli_has_outputs = command.output_groups
+					.iter()
+					.any(|g| g.format.is_some() || g.output_filename.is_some() || g.printout);
+
+				if !cli_has_outputs
+				{
+					command.output_groups.clear();
+
+					for (name, output) in outputs
+					{
+						command.output_groups.push(parse_project_output(
+							report,
+							&project_filename,
+							&format!("outputs.{}", name),
+							output,
+							&resolve_path)?);
+					}
+				}
+			}
+
+			"profiles" => {}
+
+			_ =>
+			{
+				report.error(
+					format!(
+						"unknown key `{}` in `{}`",
+						key,
+						project_filename));
+
+				return Err(());
+			}
+		}
+	}
+
+	if let Some(profile_name) = profile_name
+	{
+		let profile = project
+			.get("profiles")
+			.and_then(|p| p.as_table())
+			.and_then(|p| p.get(&profile_name));
+
+		let Some(profile) = profile
+			else {
+				report.error(
+					format!(
+						"unknown profile `{}` in `{}`",
+						profile_name,
+						project_filename));
+
+				return Err(());
+			};
+
+		let key = format!("profiles.{}", profile_name);
+
+		let Some(profile) = profile.as_table()
+			else {
+				return error_project_value(report, &project_filename, &key);
+			};
+
+		for (profile_key, value) in profile
+		{
+			if profile_key != "defines"
+			{
+				report.error(
+					format!(
+						"unknown key `{}.{}` in `{}`",
+						key,
+						profile_key,
+						project_filename));
+
+				return Err(());
+			}
+
+			add_project_defines(
+				report,
+				&project_filename,
+				&format!("{}.defines", key),
+				value,
+				&mut defines)?;
+		}
+	}
+
+	// Defines from the command-line take precedence
+	for define in defines
+	{
+		if !command.opts.driver_symbol_defs
+			.iter()
+			.any(|d| d.name == define.name)
+		{
+			command.opts.driver_symbol_defs.push(define);
+		}
+	}
+
+	Ok(())
+}
+
+
+fn error_project_value<T>(
+	report: &mut diagn::Report,
+	project_filename: &str,
+	key: &str)
+	-> Result<T, ()>
+{
+	report.error(
+		format!(
+			"invalid value for `{}` in `{}`",
+			key,
+			project_filename));
+
+	Err(())
+}
+
+
+/// Accepts either a single string or an array of strings.
+fn get_project_strings(
+	report: &mut diagn::Report,
+	project_filename: &str,
+	key: &str,
+	value: &toml::Value)
+	-> Result<Vec<String>, ()>
+{
+	if let Some(s) = value.as_str()
+	{
+		return Ok(vec![s.to_string()]);
+	}
+
+	let strings = value
+		.as_array()
+		.and_then(|array| array
+			.iter()
+			.map(|v| v.as_str().map(|s| s.to_string()))
+			.collect::<Option<Vec<_>>>());
+
+	match strings
+	{
+		Some(strings) => Ok(strings),
+		None => error_project_value(report, project_filename, key),
+	}
+}
+
+
+/// Adds the entries of a `defines` table, replacing
+/// earlier ones with the same name.
+fn add_project_defines(
+	report: &mut diagn::Report,
+	project_filename: &str,
+	key: &str,
+	value: &toml::Value,
+	defines: &mut Vec<asm::DriverSymbolDef>)
+	-> Result<(), ()>
+{
+	let Some(table) = value.as_table()
+		else {
+			return error_project_value(report, project_filename, key);
+		};
+
+	for (name, value) in table
+	{
+		let value_str = {
+			match value
+			{
+				toml::Value::Boolean(b) => format!("{}", b),
+				toml::Value::Integer(i) => format!("{}", i),
+				toml::Value::String(s) => s.clone(),
+				_ =>
+				{
+					return error_project_value(
+						report,
+						project_filename,
+						&format!("{}.{}", key, name));
+				}
+			}
+		};
+
+		let define = parse_define_arg(
+			report,
+			&format!("{}={}", name, value_str))?;
+
+		defines.retain(|d| d.name != define.name);
+		defines.push(define);
+	}
+
+	Ok(())
+}
+
+
+fn parse_project_output(
+	report: &mut diagn::Report,
+	project_filename: &str,
+	key: &str,
+	value: &toml::Value,
+	resolve_path: &dyn Fn(&str) -> String)
+	-> Result<CommandOutput, ()>
+{
+	let Some(table) = value.as_table()
+		else {
+			return error_project_value(report, project_filename, key);
+		};
+
+	let mut group = CommandOutput {
+		format: None,
+		output_filename: None,
+		printout: false,
+	};
+
+	for (output_key, value) in table
+	{
+		let full_key = format!("{}.{}", key, output_key);
+
+		match (output_key.as_ref(), value)
+		{
+			("format", toml::Value::String(format_str)) =>
+				group.format = Some(parse_output_format(
+					report,
+					format_str)?),
+
+			("output", toml::Value::String(filename)) =>
+				group.output_filename = Some(resolve_path(filename)),
+
+			("print", toml::Value::Boolean(printout)) =>
+				group.printout = *printout,
+
+			("format", _) |
+			("output", _) |
+			("print", _) =>
+				return error_project_value(report, project_filename, &full_key),
+
+			_ =>
+			{
+				report.error(
+					format!(
+						"unknown key `{}` in `{}`",
+						full_key,
+						project_filename));
+
+				return Err(());
+			}
+		}
+	}
+
+	Ok(group)
+}
+
+
 fn derive_output_filename(
 	report: &mut diagn::Report,
 	format: &OutputFormat,
@@ -505,7 +1690,17 @@ fn derive_output_filename(
 		match format
 		{
 			OutputFormat::Binary => "bin",
+			OutputFormat::Elf(_) => "elf",
+			OutputFormat::Listing => "lst",
+			OutputFormat::Object => "o",
+			OutputFormat::Map => "map",
+			OutputFormat::SymbolsC => "h",
+			OutputFormat::SymbolsRust => "rs",
+			OutputFormat::SymbolsPython => "py",
 			OutputFormat::SymbolsMesenMlb => "mlb",
+			OutputFormat::SymbolsRgbdsSym => "sym",
+			OutputFormat::SymbolsViceLabels => "lbl",
+			OutputFormat::SymbolsNocashSym => "sym",
 			_ => "txt",
 		}
 	};
@@ -708,6 +1903,21 @@ pub fn parse_output_format(
 		[8, 16, 32].contains(&base)
 	};
 
+	let check_2_3_or_4 = &mut |value: usize| -> bool
+	{
+		[2, 3, 4].contains(&value)
+	};
+
+	let check_32_or_64 = &mut |base: usize| -> bool
+	{
+		[32, 64].contains(&base)
+	};
+
+	let check_u16 = &mut |value: usize| -> bool
+	{
+		value <= u16::MAX as usize
+	};
+
 	let format = {
 		match format_id
 		{
@@ -734,6 +1944,8 @@ pub fn parse_output_format(
 				display_labels: true,
 			}),
 
+			"listing" => OutputFormat::Listing,
+
 			"binstr" => OutputFormat::BinStr,
 			"hexstr" => OutputFormat::HexStr,
 
@@ -745,6 +1957,10 @@ pub fn parse_output_format(
 				address_unit: get_arg_usize(&mut params, report, "addr_unit", 8, check_8_16_or_32)?,
 			},
 
+			"srec" => OutputFormat::SRecord {
+				addr_bytes: get_arg_usize(&mut params, report, "addr_bytes", 4, check_2_3_or_4)?,
+			},
+
 			"readmemb" => OutputFormat::ReadMemB(util::FormatReadMemOptions {
 			    width: get_arg_usize(&mut params, report, "width", 8, check_nonzero)?
 			}),
@@ -786,8 +2002,43 @@ pub fn parse_output_format(
 				group: 8,
 			},
 
+			"elf" => OutputFormat::Elf(util::FormatElfOptions {
+				machine: get_arg_usize(&mut params, report, "machine", 0, check_u16)? as u16,
+				is_64bit: get_arg_usize(&mut params, report, "class", 32, check_32_or_64)? == 64,
+				big_endian: {
+					match get_arg_str(&mut params, report, "endian", "little")?.as_ref()
+					{
+						"little" => false,
+						"big" => true,
+						value =>
+						{
+							report.error(
+								format!(
+									"invalid format argument `{},endian:{}`",
+									format_id,
+									value));
+
+							return Err(());
+						}
+					}
+				},
+				entry: get_arg_str(&mut params, report, "entry", "")?,
+			}),
+
+			"object" => OutputFormat::Object,
+
+			"map" => OutputFormat::Map,
+
 			"symbols" => OutputFormat::Symbols,
+			"symbols-c" => OutputFormat::SymbolsC,
+			"symbols-rust" => OutputFormat::SymbolsRust,
+			"symbols-python" => OutputFormat::SymbolsPython,
 			"mesen-mlb" => OutputFormat::SymbolsMesenMlb,
+			"rgbds-sym" => OutputFormat::SymbolsRgbdsSym,
+			"bgb-sym" => OutputFormat::SymbolsRgbdsSym,
+			"vice-labels" => OutputFormat::SymbolsViceLabels,
+			"mame-script" => OutputFormat::SymbolsMameScript,
+			"nocash-sym" => OutputFormat::SymbolsNocashSym,
 
 			_ =>
 			{
@@ -905,21 +2156,29 @@ fn parse_define_arg(
 pub fn format_output(
 	report: &mut diagn::Report,
 	fileserver: &dyn util::FileServer,
+	opts: &asm::AssemblyOptions,
+	ast: &asm::AstTopLevel,
 	decls: &asm::ItemDecls,
 	defs: &asm::ItemDefs,
 	output: &util::BitVec,
 	format: &OutputFormat)
-	-> Vec<u8>
+	-> Result<Vec<u8>, ()>
 {
 	let text = {
 		match format
 		{
 			OutputFormat::Binary =>
-				return output.format_binary(report),
+				return Ok(output.format_binary(report)),
+
+			OutputFormat::Elf(opts) =>
+				return util::format_elf(report, decls, defs, output, opts),
 
 			OutputFormat::Annotated(opts) =>
 				output.format_annotated(fileserver, opts),
 
+			OutputFormat::Listing =>
+				util::format_listing(fileserver, decls, defs, output),
+
 			OutputFormat::TCGame { base, group } =>
 				output.format_tcgame(fileserver, *base, *group),
 
@@ -935,6 +2194,8 @@ pub fn format_output(
 			OutputFormat::Mif => output.format_mif(),
 			OutputFormat::IntelHex { address_unit } =>
 				output.format_intelhex(*address_unit),
+			OutputFormat::SRecord { addr_bytes } =>
+				output.format_srec(report, defs, *addr_bytes),
 
 			OutputFormat::List(opts) => output.format_list(opts),
 
@@ -952,12 +2213,24 @@ pub fn format_output(
 
 			OutputFormat::AddressSpan => output.format_addrspan(fileserver),
 
+			OutputFormat::Object =>
+				asm::format_object(report, fileserver, opts, ast, decls, defs)?,
+
+			OutputFormat::Map => util::format_map(decls, defs, output),
+
 			OutputFormat::Symbols => decls.symbols.format_default(decls, defs),
+			OutputFormat::SymbolsC => decls.symbols.format_c(report, decls, defs)?,
+			OutputFormat::SymbolsRust => decls.symbols.format_rust(report, decls, defs)?,
+			OutputFormat::SymbolsPython => decls.symbols.format_python(report, decls, defs)?,
 			OutputFormat::SymbolsMesenMlb => decls.symbols.format_mesen_mlb(decls, defs),
+			OutputFormat::SymbolsRgbdsSym => decls.symbols.format_rgbds_sym(decls, defs),
+			OutputFormat::SymbolsViceLabels => decls.symbols.format_vice_labels(decls, defs),
+			OutputFormat::SymbolsMameScript => decls.symbols.format_mame_script(decls, defs),
+			OutputFormat::SymbolsNocashSym => decls.symbols.format_nocash_sym(decls, defs),
 		}
 	};
 
-	text.bytes().collect()
+	Ok(text.bytes().collect())
 }
 
 
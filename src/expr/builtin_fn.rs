@@ -14,6 +14,11 @@ pub enum ExprBuiltinFn
     Utf32be,
     Utf32le,
     Strlen,
+    Format,
+    Upper,
+    Lower,
+    Str,
+    Substr,
 }
 
 
@@ -34,6 +39,11 @@ pub fn resolve_builtin_fn(
         "$utf32be" => Some(ExprBuiltinFn::Utf32be),
         "$utf32le" => Some(ExprBuiltinFn::Utf32le),
         "$strlen" => Some(ExprBuiltinFn::Strlen),
+        "$format" => Some(ExprBuiltinFn::Format),
+        "$upper" => Some(ExprBuiltinFn::Upper),
+        "$lower" => Some(ExprBuiltinFn::Lower),
+        "$str" => Some(ExprBuiltinFn::Str),
+        "$substr" => Some(ExprBuiltinFn::Substr),
         _ => {
             if opts.use_legacy_behavior
             {
@@ -76,6 +86,11 @@ pub fn get_builtin_fn_eval(
         ExprBuiltinFn::Utf32be => eval_builtin_utf32be,
         ExprBuiltinFn::Utf32le => eval_builtin_utf32le,
         ExprBuiltinFn::Strlen => eval_builtin_strlen,
+        ExprBuiltinFn::Format => eval_builtin_format,
+        ExprBuiltinFn::Upper => eval_builtin_upper,
+        ExprBuiltinFn::Lower => eval_builtin_lower,
+        ExprBuiltinFn::Str => eval_builtin_str,
+        ExprBuiltinFn::Substr => eval_builtin_substr,
     }
 }
 
@@ -159,7 +174,9 @@ pub fn eval_builtin_le(
 {
     query.ensure_arg_number(1)?;
 
-    let bigint = query.args[0].value.expect_sized_bigint(
+    let value = query.args[0].value.clone().coerce_to_integer();
+
+    let bigint = value.expect_sized_bigint(
         query.report,
         query.args[0].span)?;
     
@@ -259,4 +276,369 @@ pub fn eval_builtin_strlen(
     Ok(expr::Value::make_integer(s.len())
         .statically_known()
         .derived_from(&query.args[0].value))
-}
\ No newline at end of file
+}
+
+
+pub fn eval_builtin_upper(
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    query.ensure_arg_number(1)?;
+
+    let s = query.args[0].value.expect_string(
+        query.report,
+        query.args[0].span)?;
+
+    Ok(expr::Value::make_string(s.to_uppercase())
+        .statically_known()
+        .derived_from(&query.args[0].value))
+}
+
+
+pub fn eval_builtin_lower(
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    query.ensure_arg_number(1)?;
+
+    let s = query.args[0].value.expect_string(
+        query.report,
+        query.args[0].span)?;
+
+    Ok(expr::Value::make_string(s.to_lowercase())
+        .statically_known()
+        .derived_from(&query.args[0].value))
+}
+
+
+pub fn eval_builtin_str(
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    query.ensure_min_max_arg_number(1, 2)?;
+
+    let bigint = query.args[0].value.expect_bigint(
+        query.report,
+        query.args[0].span)?
+        .clone();
+
+    let mut value = expr::Value::make_void()
+        .statically_known()
+        .derived_from(&query.args[0].value);
+
+    let base = {
+        if query.args.len() == 2
+        {
+            value = value.derived_from(&query.args[1].value);
+
+            query.args[1].value.expect_usize(
+                query.report,
+                query.args[1].span)?
+        }
+        else
+            { 10 }
+    };
+
+    if base < 2 || base > 36
+    {
+        query.report.error_span(
+            format!("invalid base {} (must be between 2 and 36)", base),
+            query.args[1].span);
+
+        return Err(());
+    }
+
+    Ok(expr::Value::make_string(bigint.to_str_radix(base))
+        .with_metadata(*value.get_metadata()))
+}
+
+
+/// Returns the bytes from `start` up to, but not including,
+/// `end`, or the end of the string. Indices count UTF-8 bytes,
+/// like `$strlen`, and can't split a character.
+pub fn eval_builtin_substr(
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    query.ensure_min_max_arg_number(2, 3)?;
+
+    let s = query.args[0].value.expect_string(
+        query.report,
+        query.args[0].span)?;
+
+    let start = query.args[1].value.expect_usize(
+        query.report,
+        query.args[1].span)?;
+
+    let mut value = expr::Value::make_void()
+        .statically_known()
+        .derived_from(&query.args[0].value)
+        .derived_from(&query.args[1].value);
+
+    let end = {
+        if query.args.len() == 3
+        {
+            value = value.derived_from(&query.args[2].value);
+
+            query.args[2].value.expect_usize(
+                query.report,
+                query.args[2].span)?
+        }
+        else
+            { s.len() }
+    };
+
+    if start > end || end > s.len()
+    {
+        query.report.error_span(
+            format!(
+                "invalid substring range {}..{} (string has {} bytes)",
+                start,
+                end,
+                s.len()),
+            query.span);
+
+        return Err(());
+    }
+
+    let Some(substr) = s.get(start..end)
+        else {
+            query.report.error_span(
+                format!(
+                    "substring range {}..{} splits a multi-byte character",
+                    start,
+                    end),
+                query.span);
+
+            return Err(());
+        };
+
+    Ok(expr::Value::make_string(substr.to_string())
+        .with_metadata(*value.get_metadata()))
+}
+
+
+/// Formats the arguments into a string, replacing each `{}`
+/// in the first argument, in order. Placeholders can specify
+/// a minimum width and a radix, like `{:04x}` or `{:8b}`.
+/// Numbers are right-aligned, padded with zeroes if the width
+/// starts with `0`, and strings are left-aligned. Widths count
+/// UTF-8 bytes, like `$strlen`.
+pub fn eval_builtin_format(
+    query: &mut expr::EvalFunctionQuery)
+    -> Result<expr::Value, ()>
+{
+    if query.args.len() < 1
+    {
+        query.report.error_span(
+            "function expected at least 1 argument (but got 0)",
+            query.span);
+
+        return Err(());
+    }
+
+    let fmt = query.args[0].value.expect_string(
+        query.report,
+        query.args[0].span)?;
+
+    let mut value = expr::Value::make_void()
+        .statically_known();
+
+    let mut result = String::new();
+    let mut arg_index = 1;
+    let mut chars = fmt.chars();
+
+    while let Some(c) = chars.next()
+    {
+        if c == '}'
+        {
+            if chars.next() != Some('}')
+            {
+                query.report.error_span(
+                    "unmatched `}` in format string",
+                    query.args[0].span);
+
+                return Err(());
+            }
+
+            result.push('}');
+            continue;
+        }
+
+        if c != '{'
+        {
+            result.push(c);
+            continue;
+        }
+
+        if chars.clone().next() == Some('{')
+        {
+            chars.next();
+            result.push('{');
+            continue;
+        }
+
+        let mut placeholder = String::new();
+        let mut closed = false;
+
+        while let Some(c) = chars.next()
+        {
+            if c == '}'
+            {
+                closed = true;
+                break;
+            }
+
+            placeholder.push(c);
+        }
+
+        if !closed
+        {
+            query.report.error_span(
+                "unmatched `{` in format string",
+                query.args[0].span);
+
+            return Err(());
+        }
+
+        if arg_index >= query.args.len()
+        {
+            query.report.error_span(
+                format!(
+                    "format string has more placeholders than arguments (got {})",
+                    query.args.len() - 1),
+                query.span);
+
+            return Err(());
+        }
+
+        let arg = &query.args[arg_index];
+        arg_index += 1;
+
+        value = value.derived_from(&arg.value);
+
+        let formatted = format_placeholder(
+            query.report,
+            query.args[0].span,
+            &placeholder,
+            &arg.value,
+            arg.span)?;
+
+        result.push_str(&formatted);
+    }
+
+    if arg_index < query.args.len()
+    {
+        query.report.error_span(
+            "argument not used in format string",
+            query.args[arg_index].span);
+
+        return Err(());
+    }
+
+    Ok(expr::Value::make_string(result)
+        .with_metadata(*value.derived_from(&query.args[0].value).get_metadata()))
+}
+
+
+fn format_placeholder(
+    report: &mut diagn::Report,
+    fmt_span: diagn::Span,
+    placeholder: &str,
+    value: &expr::Value,
+    value_span: diagn::Span)
+    -> Result<String, ()>
+{
+    let spec = {
+        match placeholder.strip_prefix(':')
+        {
+            Some(spec) => spec,
+            None if placeholder.len() == 0 => "",
+            None =>
+            {
+                report.error_span(
+                    format!("invalid format placeholder `{{{}}}`", placeholder),
+                    fmt_span);
+
+                return Err(());
+            }
+        }
+    };
+
+    let zero_pad = spec.starts_with('0');
+    let width_len = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(spec.len());
+
+    let width = spec[..width_len].parse::<usize>().unwrap_or(0);
+
+    let radix = {
+        match &spec[width_len..]
+        {
+            "" | "d" => 10,
+            "x" | "X" => 16,
+            "b" => 2,
+            "o" => 8,
+            _ =>
+            {
+                report.error_span(
+                    format!("invalid format placeholder `{{{}}}`", placeholder),
+                    fmt_span);
+
+                return Err(());
+            }
+        }
+    };
+
+    let uppercase = spec.ends_with('X');
+
+    match value
+    {
+        expr::Value::String(_, s) =>
+            Ok(format!("{}{}", s, " ".repeat(width.saturating_sub(s.len())))),
+
+        expr::Value::Bool(_, b) =>
+            Ok(format!("{:<width$}", b, width = width)),
+
+        expr::Value::Integer(_, bigint) =>
+        {
+            let digits = bigint.to_str_radix(radix);
+
+            let (sign, digits) = {
+                match digits.strip_prefix('-')
+                {
+                    Some(digits) => ("-", digits),
+                    None => ("", digits.as_str()),
+                }
+            };
+
+            let digits = {
+                if uppercase
+                    { digits.to_uppercase() }
+                else
+                    { digits.to_string() }
+            };
+
+            let len = sign.len() + digits.len();
+            let padding = width.saturating_sub(len);
+
+            if zero_pad
+            {
+                Ok(format!("{}{}{}", sign, "0".repeat(padding), digits))
+            }
+            else
+            {
+                Ok(format!("{}{}{}", " ".repeat(padding), sign, digits))
+            }
+        }
+
+        _ =>
+        {
+            report.error_span(
+                format!("cannot format {}", value.type_name()),
+                value_span);
+
+            Err(())
+        }
+    }
+}
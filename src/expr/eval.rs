@@ -126,6 +126,7 @@ pub type EvalProvider<'provider> =
 pub enum EvalQuery<'a, 'opts>
 {
 	CtxLabel(&'a mut EvalCtxLabelQuery<'a>),
+	AnonymousLabel(&'a mut EvalAnonymousLabelQuery<'a>),
 	Variable(&'a mut EvalVariableQuery<'a>),
 	Member(&'a mut EvalMemberQuery<'a>),
 	Function(&'a mut EvalFunctionQuery<'a, 'opts>),
@@ -141,6 +142,14 @@ pub struct EvalCtxLabelQuery<'a>
 }
 
 
+pub struct EvalAnonymousLabelQuery<'a>
+{
+	pub report: &'a mut diagn::Report,
+	pub offset: isize,
+	pub span: diagn::Span,
+}
+
+
 pub struct EvalVariableQuery<'a>
 {
 	pub report: &'a mut diagn::Report,
@@ -252,6 +261,9 @@ pub fn dummy_eval_query(
 		expr::EvalQuery::CtxLabel(query_ctxlabel) =>
 			expr::dummy_eval_ctxlabel(query_ctxlabel),
 		
+		expr::EvalQuery::AnonymousLabel(query_anon) =>
+			expr::dummy_eval_anonymous_label(query_anon),
+		
 		expr::EvalQuery::Variable(query_var) =>
 			expr::dummy_eval_var(query_var),
 		
@@ -279,6 +291,18 @@ pub fn dummy_eval_ctxlabel(
 }
 
 
+pub fn dummy_eval_anonymous_label(
+	query: &mut EvalAnonymousLabelQuery)
+	-> Result<expr::Value, ()>
+{
+	query.report.error_span(
+		"cannot reference anonymous labels in this context",
+		query.span);
+		
+	Err(())
+}
+
+
 pub fn dummy_eval_var(
 	query: &mut EvalVariableQuery)
 	-> Result<expr::Value, ()>
@@ -499,6 +523,17 @@ impl expr::Expr
 				provider(EvalQuery::CtxLabel(&mut query))
 			}	
 
+			&expr::Expr::AnonymousLabel { span, offset } =>
+			{
+				let mut query = EvalAnonymousLabelQuery {
+					report,
+					span,
+					offset,
+				};
+
+				provider(EvalQuery::AnonymousLabel(&mut query))
+			}
+
 			&expr::Expr::MemberAccess { span, ref lhs, ref member_name } =>
 			{
 				let value = propagate!(lhs
@@ -517,7 +552,8 @@ impl expr::Expr
 			&expr::Expr::UnaryOp(span, _, op, ref inner_expr) =>
 			{
 				let inner = propagate!(inner_expr
-					.eval_with_ctx(report, ctx, provider)?);
+					.eval_with_ctx(report, ctx, provider)?)
+					.coerce_to_integer();
 
 				match inner
 				{
@@ -564,7 +600,8 @@ impl expr::Expr
 			&expr::Expr::Slice(span, _, ref left_expr, ref right_expr, ref inner_expr) =>
 			{
 				let inner = propagate!(
-					inner_expr.eval_with_ctx(report, ctx, provider)?);
+					inner_expr.eval_with_ctx(report, ctx, provider)?)
+					.coerce_to_integer();
 				
 				match inner
 				{
@@ -597,7 +634,8 @@ impl expr::Expr
 			&expr::Expr::SliceShort(span, size_span, ref size_expr, ref inner_expr) =>
 			{
 				let inner = propagate!(
-					inner_expr.eval_with_ctx(report, ctx, provider)?);
+					inner_expr.eval_with_ctx(report, ctx, provider)?)
+					.coerce_to_integer();
 				
 				match inner
 				{
@@ -767,6 +805,30 @@ fn eval_binary_op<'provider>(
 	let lhs = propagate!(lhs_expr.eval_with_ctx(report, ctx, provider)?);
 	let rhs = propagate!(rhs_expr.eval_with_ctx(report, ctx, provider)?);
 
+	if let (expr::Value::String(_, lhs_str), expr::Value::String(_, rhs_str)) = (&lhs, &rhs)
+	{
+		let result = match op
+		{
+			expr::BinaryOp::Concat => Some(expr::Value::make_string(format!("{}{}", lhs_str, rhs_str))),
+			expr::BinaryOp::Eq => Some(expr::Value::make_bool(lhs_str == rhs_str)),
+			expr::BinaryOp::Ne => Some(expr::Value::make_bool(lhs_str != rhs_str)),
+			expr::BinaryOp::Lt => Some(expr::Value::make_bool(lhs_str < rhs_str)),
+			expr::BinaryOp::Le => Some(expr::Value::make_bool(lhs_str <= rhs_str)),
+			expr::BinaryOp::Gt => Some(expr::Value::make_bool(lhs_str > rhs_str)),
+			expr::BinaryOp::Ge => Some(expr::Value::make_bool(lhs_str >= rhs_str)),
+			_ => None,
+		};
+
+		if let Some(result) = result
+		{
+			return Ok(result.statically_known().derived_from(&lhs).derived_from(&rhs));
+		}
+	}
+
+	// Other operators work on the strings' encodings
+	let lhs = lhs.coerce_to_integer();
+	let rhs = rhs.coerce_to_integer();
+
 	match (op, &lhs, &rhs)
 	{
 		(expr::BinaryOp::Eq, lhs, rhs)
@@ -1,7 +1,7 @@
 use crate::*;
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum Expr
 {
 	Literal(diagn::Span, Value),
@@ -14,6 +14,12 @@ pub enum Expr
 		span: diagn::Span,
 		nesting_level: usize,
 	},
+	/// A relative reference to an anonymous label, like `:-`
+	/// (offset -1) or `:++` (offset 2)
+	AnonymousLabel {
+		span: diagn::Span,
+		offset: isize,
+	},
 	MemberAccess {
 		span: diagn::Span,
 		lhs: Box<Expr>,
@@ -30,7 +36,7 @@ pub enum Expr
 }
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct ExprStructMemberInit
 {
 	pub span: diagn::Span,
@@ -47,6 +53,8 @@ pub enum Value
 	Void(ValueMetadata),
 	Integer(ValueMetadata, util::BigInt),
 	Bool(ValueMetadata, bool),
+	/// Used as its UTF-8 encoding wherever an integer is expected
+	String(ValueMetadata, String),
 	Struct(ValueMetadata, ValueStruct),
 	ExprBuiltinFn(ValueMetadata, expr::ExprBuiltinFn),
 	AsmBuiltinFn(ValueMetadata, asm::AsmBuiltinFn),
@@ -79,7 +87,7 @@ pub struct ValueStructMember
 }
 
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum UnaryOp
 {
 	Neg,
@@ -87,7 +95,7 @@ pub enum UnaryOp
 }
 
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum BinaryOp
 {
 	Assign,
@@ -116,6 +124,7 @@ impl Expr
 			&Expr::Variable     (span, ..) => span,
 			&Expr::StructInit   { span, .. } => span,
 			&Expr::NestingLevel { span, .. } => span,
+			&Expr::AnonymousLabel { span, .. } => span,
 			&Expr::MemberAccess { span, .. } => span,
 			&Expr::UnaryOp      (span, ..) => span,
 			&Expr::BinaryOp     (span, ..) => span,
@@ -209,6 +218,7 @@ impl Value
 			Value::Void(..) => "void",
 			Value::Integer(..) => "integer",
 			Value::Bool(..) => "bool",
+			Value::String(..) => "string",
 			Value::Struct(..) => "struct",
 			Value::ExprBuiltinFn(..) => "built-in function",
 			Value::AsmBuiltinFn(..) => "built-in function",
@@ -294,6 +304,14 @@ impl Value
 	}
 
 
+	pub fn make_string<S: Into<String>>(value: S) -> Value
+	{
+		Value::String(
+			ValueMetadata::new(),
+			value.into())
+	}
+
+
 	pub fn make_struct(value: ValueStruct) -> Value
 	{
 		Value::Struct(
@@ -393,6 +411,7 @@ impl Value
 			Value::Void(meta, ..) => meta,
 			Value::Integer(meta, ..) => meta,
 			Value::Bool(meta, ..) => meta,
+			Value::String(meta, ..) => meta,
 			Value::Struct(meta, ..) => meta,
 			Value::ExprBuiltinFn(meta, ..) => meta,
 			Value::AsmBuiltinFn(meta, ..) => meta,
@@ -411,6 +430,7 @@ impl Value
 			Value::Void(meta, ..) => meta,
 			Value::Integer(meta, ..) => meta,
 			Value::Bool(meta, ..) => meta,
+			Value::String(meta, ..) => meta,
 			Value::Struct(meta, ..) => meta,
 			Value::ExprBuiltinFn(meta, ..) => meta,
 			Value::AsmBuiltinFn(meta, ..) => meta,
@@ -425,11 +445,29 @@ impl Value
 		match &self
 		{
 			&Value::Integer(_, bigint) => Some(bigint.clone()),
+			&Value::String(_, s) => Some(util::BigInt::from_bytes_be(s.as_bytes())),
 			_ => None,
 		}
 	}
 
 
+	/// Converts strings into their UTF-8 encoding,
+	/// and leaves any other values untouched
+	#[must_use]
+	pub fn coerce_to_integer(self) -> Value
+	{
+		match self
+		{
+			Value::String(meta, ref s) =>
+				Value::Integer(
+					meta,
+					util::BigInt::from_bytes_be(s.as_bytes())),
+
+			_ => self,
+		}
+	}
+
+
 	pub fn unwrap_bigint(
 		&self)
 		-> &util::BigInt
@@ -570,21 +608,23 @@ impl Value
 		span: diagn::Span)
 		-> Result<expr::Value, ()>
 	{
-		match self
+		let value = self.coerce_to_integer();
+
+		match value
 		{
 			expr::Value::Unknown(_) |
 			expr::Value::FailedConstraint(_, _) =>
-				Ok(self),
+				Ok(value),
 
 			expr::Value::Integer(_, _) =>
-				Ok(self),
+				Ok(value),
 
 			_ =>
 			{
 				report.error_span(
 					format!(
 						"expected integer, got {}",
-						self.type_name()),
+						value.type_name()),
 					span);
 
 				Err(())
@@ -599,22 +639,24 @@ impl Value
 		span: diagn::Span)
 		-> Result<expr::Value, ()>
 	{
-		match self
+		let value = self.coerce_to_integer();
+
+		match value
 		{
 			expr::Value::Unknown(_) |
 			expr::Value::FailedConstraint(_, _) =>
-				Ok(self),
+				Ok(value),
 
 			expr::Value::Integer(_, ref bigint)
 				if bigint.size.is_some() =>
-				Ok(self),
+				Ok(value),
 
 			_ =>
 			{
 				report.error_span(
 					format!(
 						"expected integer with definite size, got {}",
-						self.type_name()),
+						value.type_name()),
 					span);
 
 				Err(())
@@ -803,6 +845,11 @@ impl Value
 		span: diagn::Span)
 		-> Result<String, ()>
 	{
+		if let Value::String(_, s) = self
+		{
+			return Ok(s.clone());
+		}
+
 		if let Value::Integer(_, bigint) = self
 		{
 			return Ok(bigint.as_string());
@@ -962,6 +1009,12 @@ impl std::cmp::PartialEq for Value
 				_ => false,
 			}
 
+			Value::String(_, a) => match other
+			{
+				Value::String(_, b) => a == b,
+				_ => false,
+			}
+
 			Value::Struct(_, a) => match other
 			{
 				Value::Struct(_, b) => a == b,
@@ -1026,6 +1079,10 @@ impl std::fmt::Display for expr::Value
 				write_meta(meta)?;
 				write!(f, "Bool({:?})", value)
 			}
+			Value::String(meta, value) => {
+				write_meta(meta)?;
+				write!(f, "String({:?})", value)
+			}
 			Value::Struct(meta, value) => {
 				write_meta(meta)?;
 				write!(f, "Struct({:?})", value)
@@ -60,11 +60,15 @@ impl expr::Expr
 
 			expr::Expr::StructInit { .. } => None,
 			expr::Expr::NestingLevel { .. } => None,
+			expr::Expr::AnonymousLabel { .. } => None,
 			expr::Expr::MemberAccess { .. } => None,
 			
 			expr::Expr::Literal(_, expr::Value::Integer(_, util::BigInt { size: Some(size), .. })) =>
 				Some(*size),
 
+			expr::Expr::Literal(_, expr::Value::String(_, s)) =>
+				Some(s.len() * 8),
+
 			expr::Expr::Literal(..) => None,
 
 			expr::Expr::UnaryOp(..) => None,
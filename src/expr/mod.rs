@@ -23,6 +23,7 @@ pub use self::eval::{
     EvalProvider,
     EvalQuery,
     EvalCtxLabelQuery,
+    EvalAnonymousLabelQuery,
     EvalVariableQuery,
     EvalMemberQuery,
     EvalFunctionQuery,
@@ -30,6 +31,7 @@ pub use self::eval::{
     ASM_SUBSTITUTION_VARIABLE,
     dummy_eval_query,
     dummy_eval_ctxlabel,
+    dummy_eval_anonymous_label,
     dummy_eval_var,
     dummy_eval_member,
     dummy_eval_fn,
@@ -445,11 +445,11 @@ impl<'a, 'src> ExpressionParser<'a, 'src>
 				let member_name = self.walker.get_span_excerpt(tk_member.span).to_string();
 				let span = lhs.span().join(tk_member.span);
 				
-				lhs = expr::Expr::MemberAccess {
+				lhs = self.parse_call_args(expr::Expr::MemberAccess {
 					span,
 					lhs: Box::new(lhs),
 					member_name,
-				};
+				})?;
 			}
 			else
 			{
@@ -464,7 +464,14 @@ impl<'a, 'src> ExpressionParser<'a, 'src>
 	fn parse_call(&mut self) -> Result<expr::Expr, ()>
 	{
 		let leaf = self.parse_leaf()?;
-		
+		self.parse_call_args(leaf)
+	}
+
+
+	/// Parses a parenthesized argument list after `leaf`,
+	/// if there is one
+	fn parse_call_args(&mut self, leaf: expr::Expr) -> Result<expr::Expr, ()>
+	{
 		if self.walker.next_linebreak().is_some()
 			{ return Ok(leaf); }
 			
@@ -503,7 +510,10 @@ impl<'a, 'src> ExpressionParser<'a, 'src>
 			
 		else if next_token.kind == syntax::TokenKind::Dot
 			{ self.parse_nested_label() }
-			
+
+		else if next_token.kind == syntax::TokenKind::Colon
+			{ self.parse_anonymous_label() }
+
 		else if next_token.kind == syntax::TokenKind::Number
 			{ self.parse_number() }
 			
@@ -614,8 +624,65 @@ impl<'a, 'src> ExpressionParser<'a, 'src>
 			member_name,
 		})
 	}
-	
-	
+
+
+	fn parse_anonymous_label(&mut self) -> Result<expr::Expr, ()>
+	{
+		let tk_colon = self.walker.expect(self.report, syntax::TokenKind::Colon)?;
+		let mut span = tk_colon.span;
+		let mut offset: isize = 0;
+		let mut is_ambiguous = false;
+
+		// Signs must follow the colon without whitespace
+		loop
+		{
+			let tk_sign = self.walker.next_token();
+			let step = match tk_sign.kind
+			{
+				syntax::TokenKind::Minus => -1,
+				syntax::TokenKind::Plus => 1,
+				_ => break,
+			};
+
+			if offset != 0 && offset.signum() != step
+			{
+				is_ambiguous = true;
+			}
+
+			offset += step;
+			span = span.join(tk_sign.span);
+			self.walker.advance_to_token_end(&tk_sign);
+		}
+
+		if is_ambiguous
+		{
+			self.report.push_parent(
+				"ambiguous anonymous label reference",
+				span);
+
+			self.report.note(
+				"put a space before any operator following the reference");
+
+			self.report.pop_parent();
+			return Err(());
+		}
+
+		if offset == 0
+		{
+			self.report.error_span(
+				"expected `-` or `+`",
+				self.walker.get_cursor_span());
+
+			return Err(());
+		}
+
+		Ok(expr::Expr::AnonymousLabel {
+			span,
+			offset,
+		})
+	}
+
+
 	fn parse_number(&mut self) -> Result<expr::Expr, ()>
 	{
 		let tk_number = self.walker.expect(self.report, syntax::TokenKind::Number)?;
@@ -673,9 +740,7 @@ impl<'a, 'src> ExpressionParser<'a, 'src>
 		
 		let expr = expr::Expr::Literal(
 			tk_str.span,
-			expr::Value::Integer(
-					expr::ValueMetadata::new(),
-					util::BigInt::from_bytes_be(string.as_bytes()))
+			expr::Value::make_string(string)
 				.statically_known());
 
 		Ok(expr)
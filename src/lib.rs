@@ -4,6 +4,9 @@ pub mod expr;
 pub mod syntax;
 pub mod util;
 
+#[cfg(not(target_arch="wasm32"))]
+pub mod lsp;
+
 #[cfg(test)]
 pub mod test;
 
@@ -0,0 +1,488 @@
+use crate::*;
+
+
+/// The result of assembling a document,
+/// kept around to answer editor queries.
+pub struct Analysis
+{
+    pub report: diagn::Report,
+    pub assembly: asm::AssemblyResult,
+}
+
+
+pub struct Diagnostic
+{
+    pub kind: diagn::MessageKind,
+    pub descr: String,
+    pub span: Option<diagn::Span>,
+    pub related: Vec<(String, diagn::Span)>,
+}
+
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CompletionKind
+{
+    Mnemonic,
+    Label,
+    Constant,
+    Function,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct Completion
+{
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String,
+}
+
+
+/// What an identifier in the source refers to.
+enum Target
+{
+    Symbol(util::ItemRef<asm::Symbol>),
+    Ruledef(util::ItemRef<asm::Ruledef>),
+    Instruction(util::ItemRef<asm::Instruction>),
+}
+
+
+pub fn analyze(
+    fileserver: &mut dyn util::FileServer,
+    root_filename: &str)
+    -> Analysis
+{
+    let mut report = diagn::Report::new();
+
+    let opts = asm::AssemblyOptions::new();
+
+    let assembly = asm::assemble(
+        &mut report,
+        &opts,
+        fileserver,
+        &[root_filename]);
+
+    Analysis {
+        report,
+        assembly,
+    }
+}
+
+
+impl Analysis
+{
+    pub fn empty() -> Analysis
+    {
+        Analysis {
+            report: diagn::Report::new(),
+            assembly: asm::AssemblyResult::new(),
+        }
+    }
+
+
+    /// Flattens the report into one diagnostic per top-level
+    /// message. Inner messages at the same location are folded
+    /// into the description, and the others become related info.
+    pub fn diagnostics(&self) -> Vec<Diagnostic>
+    {
+        let mut diagnostics = Vec::new();
+
+        for msg in self.report.messages()
+        {
+            let span = find_first_span(msg);
+
+            let mut diagnostic = Diagnostic {
+                kind: msg.kind,
+                descr: msg.descr.clone(),
+                span,
+                related: Vec::new(),
+            };
+
+            collect_inner(&mut diagnostic, &msg.inner);
+
+            diagnostics.push(diagnostic);
+        }
+
+        diagnostics
+    }
+
+
+    pub fn definition_at(
+        &self,
+        fileserver: &dyn util::FileServer,
+        file_handle: util::FileServerHandle,
+        offset: usize)
+        -> Option<diagn::Span>
+    {
+        let (target, _) = self.target_at(fileserver, file_handle, offset)?;
+
+        let decls = self.assembly.decls.as_ref()?;
+        let defs = self.assembly.defs.as_ref()?;
+
+        match target
+        {
+            Target::Symbol(symbol_ref) =>
+                Some(decls.symbols.get(symbol_ref).span),
+
+            Target::Ruledef(ruledef_ref) =>
+                Some(decls.ruledefs.get(ruledef_ref).span),
+
+            Target::Instruction(instr_ref) =>
+            {
+                let mtch = defs.instructions.maybe_get(instr_ref)?.get_chosen_match()?;
+
+                let rule = defs.ruledefs
+                    .get(mtch.ruledef_ref)
+                    .get_rule(mtch.rule_ref);
+
+                Some(rule.pattern_span)
+            }
+        }
+    }
+
+
+    /// Returns a Markdown description of the item under
+    /// the given offset, and the span it was found at.
+    pub fn hover_at(
+        &self,
+        fileserver: &dyn util::FileServer,
+        file_handle: util::FileServerHandle,
+        offset: usize)
+        -> Option<(String, diagn::Span)>
+    {
+        let (target, span) = self.target_at(fileserver, file_handle, offset)?;
+
+        let decls = self.assembly.decls.as_ref()?;
+        let defs = self.assembly.defs.as_ref()?;
+
+        let text = {
+            match target
+            {
+                Target::Symbol(symbol_ref) =>
+                {
+                    let decl = decls.symbols.get(symbol_ref);
+
+                    let kind = {
+                        match decl.kind
+                        {
+                            util::SymbolKind::Label => "label",
+                            util::SymbolKind::Constant => "constant",
+                            util::SymbolKind::Function => "function",
+                            util::SymbolKind::Namespace => "namespace",
+                            util::SymbolKind::Other => "symbol",
+                        }
+                    };
+
+                    let value = defs.symbols
+                        .maybe_get(symbol_ref)
+                        .map(|s| format_value(&s.value))
+                        .unwrap_or("?".to_string());
+
+                    format!(
+                        "```\n({}) {} = {}\n```",
+                        kind,
+                        decl.name,
+                        value)
+                }
+
+                Target::Ruledef(ruledef_ref) =>
+                {
+                    format!(
+                        "```\n(ruledef) {}\n```",
+                        decls.ruledefs.get(ruledef_ref).name)
+                }
+
+                Target::Instruction(instr_ref) =>
+                {
+                    let instr = defs.instructions.maybe_get(instr_ref)?;
+                    let mtch = instr.get_chosen_match()?;
+
+                    let rule = defs.ruledefs
+                        .get(mtch.ruledef_ref)
+                        .get_rule(mtch.rule_ref);
+
+                    let ruledef_name = &decls.ruledefs.get(mtch.ruledef_ref).name;
+
+                    let mut text = format!(
+                        "```\n({}) {}\n```",
+                        if ruledef_name.starts_with("#") { "instruction" } else { ruledef_name },
+                        fileserver.get_excerpt(rule.pattern_span));
+
+                    if let expr::Value::Integer(_, ref bigint) = instr.encoding
+                    {
+                        text.push_str(&format!(
+                            "\n\nencoding: `0x{:0>width$}`",
+                            bigint.to_str_radix(16),
+                            width = bigint.size.unwrap_or(0).div_ceil(4)));
+                    }
+
+                    text
+                }
+            }
+        };
+
+        Some((text, span))
+    }
+
+
+    pub fn completions(
+        &self,
+        fileserver: &dyn util::FileServer)
+        -> Vec<Completion>
+    {
+        let mut completions = Vec::<Completion>::new();
+
+        if let Some(defs) = &self.assembly.defs
+        {
+            for entry in defs.ruledef_map.iter_entries()
+            {
+                let rule = defs.ruledefs
+                    .get(entry.ruledef_ref)
+                    .get_rule(entry.rule_ref);
+
+                let mnemonic = rule.pattern
+                    .iter()
+                    .map_while(|part| match part {
+                        asm::RulePatternPart::Exact(c) => Some(*c),
+                        _ => None,
+                    })
+                    .collect::<String>();
+
+                if mnemonic.len() == 0 ||
+                    completions.iter().any(|c| c.label == mnemonic)
+                {
+                    continue;
+                }
+
+                completions.push(Completion {
+                    label: mnemonic,
+                    kind: CompletionKind::Mnemonic,
+                    detail: fileserver.get_excerpt(rule.pattern_span),
+                });
+            }
+        }
+
+        if let Some(decls) = &self.assembly.decls
+        {
+            for decl in decls.symbols.iter()
+            {
+                if decl.depth != 0 ||
+                    decl.span.location().is_none() ||
+                    decl.name.starts_with("__")
+                {
+                    continue;
+                }
+
+                let kind = {
+                    match decl.kind
+                    {
+                        util::SymbolKind::Label => CompletionKind::Label,
+                        util::SymbolKind::Function => CompletionKind::Function,
+                        _ => CompletionKind::Constant,
+                    }
+                };
+
+                completions.push(Completion {
+                    label: decl.name.clone(),
+                    kind,
+                    detail: "".to_string(),
+                });
+            }
+        }
+
+        completions.sort_by(|a, b| a.label.cmp(&b.label));
+        completions
+    }
+
+
+    fn target_at(
+        &self,
+        fileserver: &dyn util::FileServer,
+        file_handle: util::FileServerHandle,
+        offset: usize)
+        -> Option<(Target, diagn::Span)>
+    {
+        let decls = self.assembly.decls.as_ref()?;
+
+        let src = fileserver.get_bytes_unwrap(file_handle);
+        let (start, end) = find_word(&src, offset)?;
+        let word = String::from_utf8_lossy(&src[start..end]).to_string();
+        let span = diagn::Span::new(file_handle, start, end);
+
+        let hierarchy_level = word.chars().take_while(|c| *c == '.').count();
+        let hierarchy = word[hierarchy_level..]
+            .split('.')
+            .collect::<Vec<_>>();
+
+        if hierarchy.iter().all(|s| s.len() > 0)
+        {
+            let ctx = {
+                if hierarchy_level == 0
+                {
+                    util::SymbolContext::new_global()
+                }
+                else
+                {
+                    // Local symbols are relative to the last
+                    // symbol declared before them at the parent level
+                    decls.symbols
+                        .iter()
+                        .filter(|d| d.depth == hierarchy_level - 1)
+                        .filter(|d| d.span.file_handle == file_handle)
+                        .filter(|d| d.span.location().is_some_and(|l| l.0 <= start))
+                        .max_by_key(|d| d.span.location().unwrap().0)
+                        .map(|d| d.ctx.clone())
+                        .unwrap_or(util::SymbolContext::new_global())
+                }
+            };
+
+            let maybe_symbol = decls.symbols.try_get_by_name(
+                &ctx,
+                hierarchy_level,
+                &hierarchy);
+
+            if let Some(symbol_ref) = maybe_symbol
+            {
+                return Some((Target::Symbol(symbol_ref), span));
+            }
+
+            if hierarchy_level == 0 && hierarchy.len() == 1
+            {
+                let maybe_ruledef = decls.ruledefs.try_get_by_name(
+                    &util::SymbolContext::new_global(),
+                    0,
+                    &hierarchy);
+
+                if let Some(ruledef_ref) = maybe_ruledef
+                {
+                    return Some((Target::Ruledef(ruledef_ref), span));
+                }
+            }
+        }
+
+        let ast = self.assembly.ast.as_ref()?;
+
+        for node in &ast.nodes
+        {
+            let asm::AstAny::Instruction(ast_instr) = node
+                else { continue };
+
+            let Some((instr_start, instr_end)) = ast_instr.span.location()
+                else { continue };
+
+            if ast_instr.span.file_handle == file_handle &&
+                instr_start <= offset &&
+                offset <= instr_end
+            {
+                return Some((Target::Instruction(ast_instr.item_ref?), span));
+            }
+        }
+
+        None
+    }
+}
+
+
+fn find_first_span(msg: &diagn::Message) -> Option<diagn::Span>
+{
+    if msg.span.is_some()
+    {
+        return msg.span;
+    }
+
+    msg.inner
+        .iter()
+        .find_map(|inner| find_first_span(inner))
+}
+
+
+fn collect_inner(
+    diagnostic: &mut Diagnostic,
+    inner: &[diagn::Message])
+{
+    for msg in inner
+    {
+        match msg.span
+        {
+            Some(span) if Some(span) != diagnostic.span =>
+                diagnostic.related.push((msg.descr.clone(), span)),
+
+            _ =>
+            {
+                diagnostic.descr.push_str("\n");
+                diagnostic.descr.push_str(&msg.descr);
+            }
+        }
+
+        collect_inner(diagnostic, &msg.inner);
+    }
+}
+
+
+/// Finds the bounds of the identifier surrounding `offset`,
+/// including any `.` hierarchy separators.
+fn find_word(
+    src: &[u8],
+    offset: usize)
+    -> Option<(usize, usize)>
+{
+    let is_word_char = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'.';
+
+    let offset = offset.min(src.len());
+
+    let mut start = offset;
+    while start > 0 && is_word_char(src[start - 1])
+    {
+        start -= 1;
+    }
+
+    let mut end = offset;
+    while end < src.len() && is_word_char(src[end])
+    {
+        end += 1;
+    }
+
+    // Trailing dots belong to the surrounding syntax
+    while end > start && src[end - 1] == b'.'
+    {
+        end -= 1;
+    }
+
+    if start == end || src[start].is_ascii_digit()
+    {
+        return None;
+    }
+
+    Some((start, end))
+}
+
+
+fn format_value(value: &expr::Value) -> String
+{
+    match value
+    {
+        expr::Value::Integer(_, bigint) => format_bigint(bigint),
+        expr::Value::Bool(_, b) => format!("{}", b),
+        expr::Value::String(_, s) => format!("{:?}", s),
+        _ => "?".to_string(),
+    }
+}
+
+
+fn format_bigint(bigint: &util::BigInt) -> String
+{
+    if bigint.sign() < 0
+    {
+        format!(
+            "-0x{} ({})",
+            (-bigint).to_str_radix(16),
+            bigint.to_str_radix(10))
+    }
+    else
+    {
+        format!(
+            "0x{} ({})",
+            bigint.to_str_radix(16),
+            bigint.to_str_radix(10))
+    }
+}
@@ -0,0 +1,172 @@
+/// Converts a `file://` URI into a filesystem path.
+/// Other schemes are passed through unchanged.
+pub fn uri_to_path(uri: &str) -> String
+{
+    let Some(path) = uri.strip_prefix("file://")
+        else { return uri.to_string() };
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len()
+    {
+        let escaped = {
+            if bytes[i] == b'%' && i + 2 < bytes.len()
+            {
+                std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            }
+            else
+            {
+                None
+            }
+        };
+
+        match escaped
+        {
+            Some(byte) =>
+            {
+                decoded.push(byte);
+                i += 3;
+            }
+            None =>
+            {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    let path = String::from_utf8_lossy(&decoded).to_string();
+
+    // Windows drive letters come as `/C:/...`
+    let is_drive_path = path.len() >= 3 &&
+        path.as_bytes()[0] == b'/' &&
+        path.as_bytes()[1].is_ascii_alphabetic() &&
+        path.as_bytes()[2] == b':';
+
+    if is_drive_path
+    {
+        path[1..].to_string()
+    }
+    else
+    {
+        path
+    }
+}
+
+
+/// Converts a filename into a `file://` URI, making it
+/// absolute first, since URIs can't hold relative paths.
+pub fn path_to_uri(path: &str) -> String
+{
+    let path = make_absolute(path).replace("\\", "/");
+
+    let mut uri = "file://".to_string();
+
+    if !path.starts_with("/")
+    {
+        uri.push('/');
+    }
+
+    for byte in path.bytes()
+    {
+        if byte.is_ascii_alphanumeric() || b"/-_.~:".contains(&byte)
+        {
+            uri.push(byte as char);
+        }
+        else
+        {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    uri
+}
+
+
+/// Resolves a relative path against the current directory.
+/// Absolute paths are kept as they are, to match the URIs
+/// sent by the client, including drive paths like `C:/...`
+/// on systems where they aren't absolute.
+fn make_absolute(path: &str) -> String
+{
+    let is_drive_path = path.len() >= 2 &&
+        path.as_bytes()[0].is_ascii_alphabetic() &&
+        path.as_bytes()[1] == b':';
+
+    if is_drive_path || path.starts_with('/') || path.starts_with('\\')
+    {
+        return path.to_string();
+    }
+
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(path.to_string())
+}
+
+
+/// Converts a byte offset into an LSP position,
+/// made of a zero-based line and UTF-16 column.
+pub fn offset_to_position(
+    src: &str,
+    offset: usize)
+    -> (usize, usize)
+{
+    let mut line = 0;
+    let mut column = 0;
+
+    for (index, c) in src.char_indices()
+    {
+        if index >= offset
+        {
+            break;
+        }
+
+        if c == '\n'
+        {
+            line += 1;
+            column = 0;
+        }
+        else
+        {
+            column += c.len_utf16();
+        }
+    }
+
+    (line, column)
+}
+
+
+pub fn position_to_offset(
+    src: &str,
+    line: usize,
+    column: usize)
+    -> usize
+{
+    let mut cur_line = 0;
+    let mut cur_column = 0;
+
+    for (index, c) in src.char_indices()
+    {
+        if cur_line == line && (cur_column >= column || c == '\n')
+        {
+            return index;
+        }
+
+        if c == '\n'
+        {
+            cur_line += 1;
+            cur_column = 0;
+        }
+        else
+        {
+            cur_column += c.len_utf16();
+        }
+    }
+
+    src.len()
+}
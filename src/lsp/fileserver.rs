@@ -0,0 +1,196 @@
+use crate::*;
+
+
+/// Serves the contents of documents open in the editor,
+/// falling back to the standard library and the disk.
+pub struct LspFileServer
+{
+    handles: std::collections::HashMap<String, util::FileServerHandle>,
+    handles_to_filename: Vec<String>,
+    std_files: std::collections::HashMap<String, &'static str>,
+    open_docs: std::collections::HashMap<String, String>,
+}
+
+
+impl LspFileServer
+{
+    pub fn new() -> LspFileServer
+    {
+        LspFileServer {
+            handles: std::collections::HashMap::new(),
+            handles_to_filename: Vec::new(),
+            std_files: std::collections::HashMap::new(),
+            open_docs: std::collections::HashMap::new(),
+        }
+    }
+
+
+    pub fn add_std_files(
+        &mut self,
+        entries: &[(&str, &'static str)])
+    {
+        for (filename, contents) in entries
+        {
+            self.std_files.insert(
+                filename.to_string(),
+                *contents);
+        }
+    }
+
+
+    pub fn set_document(
+        &mut self,
+        filename: &str,
+        contents: String)
+    {
+        self.open_docs.insert(
+            filename.to_string(),
+            contents);
+    }
+
+
+    pub fn get_document(
+        &self,
+        filename: &str)
+        -> Option<&str>
+    {
+        self.open_docs
+            .get(filename)
+            .map(|s| s.as_str())
+    }
+
+
+    pub fn close_document(
+        &mut self,
+        filename: &str)
+    {
+        self.open_docs.remove(filename);
+    }
+
+
+    pub fn find_handle(
+        &self,
+        filename: &str)
+        -> Option<util::FileServerHandle>
+    {
+        self.handles.get(filename).copied()
+    }
+}
+
+
+impl util::FileServer for LspFileServer
+{
+    fn get_handle(
+        &mut self,
+        report: &mut diagn::Report,
+        span: Option<diagn::Span>,
+        filename: &str)
+        -> Result<util::FileServerHandle, ()>
+    {
+        if let Some(handle) = self.handles.get(filename)
+        {
+            return Ok(*handle);
+        }
+
+        let exists =
+            self.open_docs.contains_key(filename) ||
+            self.std_files.contains_key(filename) ||
+            std::path::Path::new(filename).exists();
+
+        if !exists
+        {
+            let msg = format!("file not found: `{}`", filename);
+
+            match span
+            {
+                Some(span) => report.error_span(msg, span),
+                None => report.error(msg),
+            }
+
+            return Err(());
+        }
+
+        let handle = self.handles_to_filename.len();
+
+        self.handles.insert(
+            filename.to_string(),
+            handle);
+
+        self.handles_to_filename.push(
+            filename.to_string());
+
+        Ok(handle)
+    }
+
+
+    fn get_filename(
+        &self,
+        file_handle: util::FileServerHandle)
+        -> &str
+    {
+        &self.handles_to_filename[file_handle]
+    }
+
+
+    fn get_bytes(
+        &self,
+        report: &mut diagn::Report,
+        span: Option<diagn::Span>,
+        file_handle: util::FileServerHandle)
+        -> Result<Vec<u8>, ()>
+    {
+        let filename = &self.handles_to_filename[file_handle];
+
+        if let Some(contents) = self.open_docs.get(filename)
+        {
+            return Ok(contents.as_bytes().to_vec());
+        }
+
+        if let Some(contents) = self.std_files.get(filename)
+        {
+            return Ok(contents.as_bytes().to_vec());
+        }
+
+        match std::fs::read(filename)
+        {
+            Ok(bytes) => Ok(bytes),
+            Err(err) =>
+            {
+                let msg = format!(
+                    "could not read file `{}`: {}",
+                    filename,
+                    err);
+
+                match span
+                {
+                    Some(span) => report.error_span(msg, span),
+                    None => report.error(msg),
+                }
+
+                Err(())
+            }
+        }
+    }
+
+
+    fn write_bytes(
+        &mut self,
+        report: &mut diagn::Report,
+        span: Option<diagn::Span>,
+        filename: &str,
+        _data: &Vec<u8>)
+        -> Result<(), ()>
+    {
+        let msg = format!(
+            "cannot write file `{}` from the language server",
+            filename);
+
+        match span
+        {
+            Some(span) => report.error_span(msg, span),
+            None => report.error(msg),
+        }
+
+        Err(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::*;
+
+
+mod analysis;
+pub use self::analysis::{
+    Analysis,
+    Completion,
+    CompletionKind,
+    Diagnostic,
+    analyze,
+};
+
+mod convert;
+pub use self::convert::{
+    offset_to_position,
+    path_to_uri,
+    position_to_offset,
+    uri_to_path,
+};
+
+mod fileserver;
+pub use self::fileserver::LspFileServer;
+
+mod server;
+pub use self::server::Server;
+
+mod transport;
+pub use self::transport::{
+    read_message,
+    write_message,
+};
+
+
+/// Runs a language server over stdin and stdout
+/// until the client asks it to exit.
+pub fn run_stdio(std_files: &[(&str, &'static str)]) -> Result<(), ()>
+{
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut writer = std::io::stdout();
+
+    let mut server = lsp::Server::new(std_files);
+
+    serve(&mut server, &mut reader, &mut writer)
+}
+
+
+/// Handles messages until the client asks to exit
+/// or the stream ends, skipping over malformed ones.
+pub fn serve(
+    server: &mut lsp::Server,
+    reader: &mut dyn std::io::BufRead,
+    writer: &mut dyn std::io::Write)
+    -> Result<(), ()>
+{
+    while let Some(message) = lsp::read_message(reader)
+    {
+        let message = match message
+        {
+            Ok(message) => message,
+            Err(err) =>
+            {
+                eprintln!("customasm-lsp: skipping malformed message: {}", err);
+                server.handle_malformed(writer, &err)?;
+                continue;
+            }
+        };
+
+        if !server.handle(writer, &message)?
+        {
+            break;
+        }
+    }
+
+    // Exiting without a prior shutdown request is an error
+    match server.is_shutdown_requested()
+    {
+        true => Ok(()),
+        false => Err(()),
+    }
+}
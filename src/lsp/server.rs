@@ -0,0 +1,480 @@
+use crate::*;
+
+
+const ERROR_PARSE: i64 = -32700;
+const ERROR_INVALID_REQUEST: i64 = -32600;
+const ERROR_METHOD_NOT_FOUND: i64 = -32601;
+
+
+pub struct Server
+{
+    fileserver: lsp::LspFileServer,
+
+    /// Keyed by the filename of each open document,
+    /// which is assembled as its own root file
+    analyses: std::collections::BTreeMap<String, lsp::Analysis>,
+    published_uris: Vec<String>,
+    shutdown_requested: bool,
+}
+
+
+impl Server
+{
+    pub fn new(std_files: &[(&str, &'static str)]) -> Server
+    {
+        let mut fileserver = lsp::LspFileServer::new();
+        fileserver.add_std_files(std_files);
+
+        Server {
+            fileserver,
+            analyses: std::collections::BTreeMap::new(),
+            published_uris: Vec::new(),
+            shutdown_requested: false,
+        }
+    }
+
+
+    pub fn is_shutdown_requested(&self) -> bool
+    {
+        self.shutdown_requested
+    }
+
+
+    /// Handles a single message from the client, writing
+    /// out any responses and notifications.
+    /// Returns `false` once the client asks to exit.
+    pub fn handle(
+        &mut self,
+        writer: &mut dyn std::io::Write,
+        message: &serde_json::Value)
+        -> Result<bool, ()>
+    {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+
+        let Some(id) = message.get("id")
+        else
+        {
+            return self.handle_notification(writer, method, params);
+        };
+
+        let result = {
+            if self.shutdown_requested
+            {
+                Err((ERROR_INVALID_REQUEST, "server is shutting down"))
+            }
+            else
+            {
+                match method
+                {
+                    "initialize" => Ok(self.initialize()),
+                    "shutdown" =>
+                    {
+                        self.shutdown_requested = true;
+                        Ok(serde_json::Value::Null)
+                    }
+                    "textDocument/definition" => Ok(self.definition(params)),
+                    "textDocument/hover" => Ok(self.hover(params)),
+                    "textDocument/completion" => Ok(self.completion(params)),
+                    _ => Err((ERROR_METHOD_NOT_FOUND, "method not found")),
+                }
+            }
+        };
+
+        let response = {
+            match result
+            {
+                Ok(result) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": result,
+                }),
+
+                Err((code, msg)) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": code,
+                        "message": msg,
+                    },
+                }),
+            }
+        };
+
+        lsp::write_message(writer, &response)?;
+        Ok(true)
+    }
+
+
+    /// Answers a message whose body couldn't be parsed,
+    /// which has no id to reply to.
+    pub fn handle_malformed(
+        &mut self,
+        writer: &mut dyn std::io::Write,
+        err: &str)
+        -> Result<(), ()>
+    {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": serde_json::Value::Null,
+            "error": {
+                "code": ERROR_PARSE,
+                "message": format!("parse error: {}", err),
+            },
+        });
+
+        lsp::write_message(writer, &response)
+    }
+
+
+    fn handle_notification(
+        &mut self,
+        writer: &mut dyn std::io::Write,
+        method: &str,
+        params: &serde_json::Value)
+        -> Result<bool, ()>
+    {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+        let filename = lsp::uri_to_path(uri);
+
+        match method
+        {
+            "exit" => return Ok(false),
+
+            "textDocument/didOpen" =>
+            {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.fileserver.set_document(&filename, text.to_string());
+                self.analyses.insert(filename, lsp::Analysis::empty());
+                self.reanalyze(writer)?;
+            }
+
+            "textDocument/didChange" =>
+            {
+                // Only full-document sync is advertised
+                let maybe_text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+
+                if let Some(text) = maybe_text
+                {
+                    self.fileserver.set_document(&filename, text.to_string());
+                    self.reanalyze(writer)?;
+                }
+            }
+
+            "textDocument/didSave" =>
+                self.reanalyze(writer)?,
+
+            "textDocument/didClose" =>
+            {
+                self.fileserver.close_document(&filename);
+                self.analyses.remove(&filename);
+                self.reanalyze(writer)?;
+            }
+
+            _ => {}
+        }
+
+        Ok(true)
+    }
+
+
+    fn initialize(&self) -> serde_json::Value
+    {
+        serde_json::json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": 1,
+                    "save": true,
+                },
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "completionProvider": {},
+            },
+            "serverInfo": {
+                "name": "customasm-lsp",
+                "version": env!("CUSTOMASM_VERSION"),
+            },
+        })
+    }
+
+
+    /// Reassembles every open document, since any of
+    /// them might include the one that changed.
+    fn reanalyze(
+        &mut self,
+        writer: &mut dyn std::io::Write)
+        -> Result<(), ()>
+    {
+        let filenames = self.analyses
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for filename in filenames
+        {
+            let analysis = lsp::analyze(
+                &mut self.fileserver,
+                &filename);
+
+            self.analyses.insert(filename, analysis);
+        }
+
+        self.publish_diagnostics(writer)
+    }
+
+
+    fn publish_diagnostics(
+        &mut self,
+        writer: &mut dyn std::io::Write)
+        -> Result<(), ()>
+    {
+        let mut diagnostics_by_uri =
+            std::collections::BTreeMap::<String, Vec<serde_json::Value>>::new();
+
+        for (root_filename, analysis) in &self.analyses
+        {
+            for diagnostic in analysis.diagnostics()
+            {
+                let (uri, range) = diagnostic.span
+                    .and_then(|span| self.span_to_location(span))
+                    .unwrap_or_else(|| (
+                        lsp::path_to_uri(root_filename),
+                        make_range((0, 0), (0, 0))));
+
+                let related = diagnostic.related
+                    .iter()
+                    .filter_map(|(descr, span)| {
+                        let (uri, range) = self.span_to_location(*span)?;
+
+                        Some(serde_json::json!({
+                            "location": {
+                                "uri": uri,
+                                "range": range,
+                            },
+                            "message": descr,
+                        }))
+                    })
+                    .collect::<Vec<_>>();
+
+                let severity = {
+                    match diagnostic.kind
+                    {
+                        diagn::MessageKind::Error => 1,
+                        diagn::MessageKind::Warning => 2,
+                        diagn::MessageKind::Note => 3,
+                    }
+                };
+
+                let json = serde_json::json!({
+                    "range": range,
+                    "severity": severity,
+                    "source": "customasm",
+                    "message": diagnostic.descr,
+                    "relatedInformation": related,
+                });
+
+                // The same file can be reached from many roots
+                let diagnostics = diagnostics_by_uri
+                    .entry(uri)
+                    .or_insert_with(|| Vec::new());
+
+                if !diagnostics.contains(&json)
+                {
+                    diagnostics.push(json);
+                }
+            }
+        }
+
+        // Clear out files that no longer have diagnostics
+        for uri in &self.published_uris
+        {
+            if !diagnostics_by_uri.contains_key(uri)
+            {
+                write_publish_diagnostics(writer, uri, Vec::new())?;
+            }
+        }
+
+        self.published_uris = diagnostics_by_uri
+            .keys()
+            .cloned()
+            .collect();
+
+        for (uri, diagnostics) in diagnostics_by_uri
+        {
+            write_publish_diagnostics(writer, &uri, diagnostics)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn definition(
+        &self,
+        params: &serde_json::Value)
+        -> serde_json::Value
+    {
+        let maybe_location = self.query_position(params)
+            .and_then(|(analysis, file_handle, offset)| {
+                analysis.definition_at(&self.fileserver, file_handle, offset)
+            })
+            .and_then(|span| self.span_to_location(span));
+
+        match maybe_location
+        {
+            Some((uri, range)) => serde_json::json!({
+                "uri": uri,
+                "range": range,
+            }),
+            None => serde_json::Value::Null,
+        }
+    }
+
+
+    fn hover(
+        &self,
+        params: &serde_json::Value)
+        -> serde_json::Value
+    {
+        let maybe_hover = self.query_position(params)
+            .and_then(|(analysis, file_handle, offset)| {
+                analysis.hover_at(&self.fileserver, file_handle, offset)
+            });
+
+        let Some((text, span)) = maybe_hover
+            else { return serde_json::Value::Null };
+
+        let mut hover = serde_json::json!({
+            "contents": {
+                "kind": "markdown",
+                "value": text,
+            },
+        });
+
+        if let Some((_, range)) = self.span_to_location(span)
+        {
+            hover["range"] = range;
+        }
+
+        hover
+    }
+
+
+    fn completion(
+        &self,
+        params: &serde_json::Value)
+        -> serde_json::Value
+    {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+        let filename = lsp::uri_to_path(uri);
+
+        let Some(analysis) = self.analyses.get(&filename)
+            else { return serde_json::json!([]) };
+
+        let items = analysis
+            .completions(&self.fileserver)
+            .into_iter()
+            .map(|completion| {
+                let kind = {
+                    match completion.kind
+                    {
+                        lsp::CompletionKind::Mnemonic => 14,
+                        lsp::CompletionKind::Label => 6,
+                        lsp::CompletionKind::Constant => 21,
+                        lsp::CompletionKind::Function => 3,
+                    }
+                };
+
+                serde_json::json!({
+                    "label": completion.label,
+                    "kind": kind,
+                    "detail": completion.detail,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::Value::Array(items)
+    }
+
+
+    /// Finds the analysis, file, and byte offset
+    /// targeted by a text document position request.
+    fn query_position(
+        &self,
+        params: &serde_json::Value)
+        -> Option<(&lsp::Analysis, util::FileServerHandle, usize)>
+    {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let filename = lsp::uri_to_path(uri);
+
+        let analysis = self.analyses.get(&filename)?;
+        let file_handle = self.fileserver.find_handle(&filename)?;
+        let src = self.fileserver.get_document(&filename)?;
+
+        let line = params["position"]["line"].as_u64()? as usize;
+        let column = params["position"]["character"].as_u64()? as usize;
+
+        let offset = lsp::position_to_offset(src, line, column);
+
+        Some((analysis, file_handle, offset))
+    }
+
+
+    fn span_to_location(
+        &self,
+        span: diagn::Span)
+        -> Option<(String, serde_json::Value)>
+    {
+        let fileserver: &dyn util::FileServer = &self.fileserver;
+
+        let (start, end) = span.location()?;
+
+        let src = fileserver.get_str(
+            &mut diagn::Report::new(),
+            None,
+            span.file_handle)
+            .ok()?;
+
+        let filename = fileserver.get_filename(span.file_handle);
+
+        Some((
+            lsp::path_to_uri(filename),
+            make_range(
+                lsp::offset_to_position(&src, start),
+                lsp::offset_to_position(&src, end))))
+    }
+}
+
+
+fn make_range(
+    start: (usize, usize),
+    end: (usize, usize))
+    -> serde_json::Value
+{
+    serde_json::json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+
+fn write_publish_diagnostics(
+    writer: &mut dyn std::io::Write,
+    uri: &str,
+    diagnostics: Vec<serde_json::Value>)
+    -> Result<(), ()>
+{
+    lsp::write_message(
+        writer,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {
+                "uri": uri,
+                "diagnostics": diagnostics,
+            },
+        }))
+}
@@ -0,0 +1,60 @@
+/// Reads a single JSON-RPC message framed by
+/// a `Content-Length` header. Returns `None` at
+/// the end of the stream or on malformed framing,
+/// after which no more messages can be read, and
+/// an error for a body that isn't valid JSON,
+/// which can be skipped over.
+pub fn read_message(
+    reader: &mut dyn std::io::BufRead)
+    -> Option<Result<serde_json::Value, String>>
+{
+    let mut content_length = None;
+
+    loop
+    {
+        let mut line = String::new();
+
+        if reader.read_line(&mut line).ok()? == 0
+        {
+            return None;
+        }
+
+        let line = line.trim_end();
+        if line.len() == 0
+        {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':')
+        {
+            if name.trim().eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length?];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(serde_json::from_slice(&body)
+        .map_err(|err| err.to_string()))
+}
+
+
+pub fn write_message(
+    writer: &mut dyn std::io::Write,
+    message: &serde_json::Value)
+    -> Result<(), ()>
+{
+    let body = message.to_string();
+
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        body.len(),
+        body)
+        .map_err(|_| ())?;
+
+    writer.flush().map_err(|_| ())
+}
@@ -0,0 +1,16 @@
+use customasm::*;
+
+
+// generated by build script
+include!(concat!(env!("OUT_DIR"), "/std_files.rs"));
+
+
+fn main()
+{
+	let maybe_result = lsp::run_stdio(STD_FILES);
+
+	if let Err(()) = maybe_result
+	{
+		std::process::exit(1);
+	}
+}
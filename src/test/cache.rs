@@ -0,0 +1,336 @@
+use crate::*;
+
+
+fn make_cache_dir(name: &str) -> std::path::PathBuf
+{
+    let dir = std::env::temp_dir().join(format!(
+        "customasm_test_cache_{}_{}",
+        name,
+        std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+
+fn make_opts(dir: &std::path::Path) -> asm::AssemblyOptions
+{
+    let mut opts = asm::AssemblyOptions::new();
+    opts.cache = Some(std::sync::Arc::new(std::sync::Mutex::new(
+        asm::AssemblyCache::new_with_dir(dir))));
+
+    opts
+}
+
+
+fn assemble_with_cache(
+    opts: &asm::AssemblyOptions,
+    src: &str)
+    -> Option<String>
+{
+    let mut report = diagn::Report::new();
+    let mut fileserver = util::FileServerMock::new();
+    fileserver.add("main.asm", src);
+
+    let assembly = asm::assemble(
+        &mut report,
+        opts,
+        &mut fileserver,
+        &["main.asm"]);
+
+    opts.cache
+        .as_ref()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .save(&mut report)
+        .unwrap();
+
+    assembly.output.map(|output| output.format_hexstr())
+}
+
+
+fn parse(
+    src: &str,
+    file_handle: util::FileServerHandle)
+    -> Option<asm::AstTopLevel>
+{
+    let mut report = diagn::Report::new();
+    let mut walker = syntax::Walker::new(src, file_handle, 0);
+
+    asm::parser::parse(
+        &mut report,
+        &asm::AssemblyOptions::new(),
+        &mut walker)
+        .ok()
+}
+
+
+fn collect_asm_files(
+    dir: &std::path::Path,
+    result: &mut Vec<std::path::PathBuf>)
+{
+    for entry in std::fs::read_dir(dir).unwrap()
+    {
+        let path = entry.unwrap().path();
+
+        if path.is_dir()
+        {
+            collect_asm_files(&path, result);
+        }
+        else if path.extension().map_or(false, |ext| ext == "asm")
+        {
+            result.push(path);
+        }
+    }
+}
+
+
+#[test]
+fn test_ast_roundtrip()
+{
+    let mut filenames = Vec::new();
+    collect_asm_files(std::path::Path::new("tests"), &mut filenames);
+    collect_asm_files(std::path::Path::new("std"), &mut filenames);
+    collect_asm_files(std::path::Path::new("examples"), &mut filenames);
+
+    let mut parsed_count = 0;
+
+    for filename in filenames
+    {
+        let Ok(src) = std::fs::read_to_string(&filename)
+            else { continue };
+
+        let Some(ast) = parse(&src, 7)
+            else { continue };
+
+        let json = asm::cache::encode_ast(&ast, 7)
+            .unwrap_or_else(|| panic!("could not encode `{:?}`", filename));
+
+        let decoded = asm::cache::decode_ast(&json, 7)
+            .unwrap_or_else(|| panic!("could not decode `{:?}`", filename));
+
+        assert_eq!(
+            format!("{:?}", decoded),
+            format!("{:?}", ast),
+            "`{:?}` changed after decoding",
+            filename);
+
+        parsed_count += 1;
+    }
+
+    assert!(parsed_count > 100);
+}
+
+
+#[test]
+fn test_ast_restores_file_handle()
+{
+    let ast = parse("x = 1\nld x", 7).unwrap();
+    let json = asm::cache::encode_ast(&ast, 7).unwrap();
+    let decoded = asm::cache::decode_ast(&json, 3).unwrap();
+
+    assert_eq!(decoded.nodes[1].span().file_handle, 3);
+
+    // Spans from other files can't be persisted
+    assert!(asm::cache::encode_ast(&ast, 3).is_none());
+}
+
+
+const RULEDEFS: &str = "
+    #ruledef
+    {
+        ld {x: u8} => 0x11 @ x
+        halt => 0xff
+    }
+";
+
+
+#[test]
+fn test_cache_persisted()
+{
+    let dir = make_cache_dir("persisted");
+    let src = format!("{}\nld 0x22\nhalt", RULEDEFS);
+
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    let filenames = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(filenames.iter().any(|f| f.starts_with("ast-")));
+    assert!(filenames.iter().any(|f| f.starts_with("matches-")));
+
+    // A new cache picks up the files written by the previous one
+    let opts = make_opts(&dir);
+    let mut cache = opts.cache.as_ref().unwrap().lock().unwrap();
+
+    let ast = cache.get_parsed_file(&opts, "other.asm", 5, &src).unwrap();
+    assert_eq!(
+        format!("{:?}", ast),
+        format!("{:?}", parse(&src, 5).unwrap()));
+
+    assert!(cache.get_parsed_file(&opts, "other.asm", 5, "halt").is_none());
+    drop(cache);
+
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn test_cache_invalidated_by_ruledef_edit()
+{
+    let dir = make_cache_dir("invalidated");
+
+    let opts = make_opts(&dir);
+    assert_eq!(
+        assemble_with_cache(&opts, &format!("{}\nld 0x22", RULEDEFS)).unwrap(),
+        "1122");
+
+    let src = "
+        #ruledef
+        {
+            ld {x: u16} => 0x11 @ x
+            halt => 0xff
+        }
+        ld 0x22
+    ";
+
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, src).unwrap(), "110022");
+
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, src).unwrap(), "110022");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn test_cache_stale_entry()
+{
+    let dir = make_cache_dir("stale");
+    let src = format!("{}\nld 0x22\nhalt", RULEDEFS);
+
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    // Point the cached `ld 0x22` entry at the `halt` rule, as if
+    // left over from before an edit that kept the same ruledefs hash
+    let matches_filename = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("matches-"))
+        .unwrap();
+
+    let mut json: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(&matches_filename).unwrap())
+        .unwrap();
+
+    let halt_entry = json["instructions"]["halt"].clone();
+    json["instructions"]["ld 0x22"][0][1] = halt_entry[0][1].clone();
+
+    std::fs::write(&matches_filename, json.to_string()).unwrap();
+
+    // Rather than finding no match, it's matched again from scratch
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+
+fn list_cache_files(dir: &std::path::Path) -> Vec<String>
+{
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect()
+}
+
+
+#[test]
+fn test_cache_drops_other_builds()
+{
+    let dir = make_cache_dir("other_builds");
+    let src = format!("{}\nld 0x22\nhalt", RULEDEFS);
+
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    // Same contents, as if written by another version
+    let ast_filename = list_cache_files(&dir)
+        .into_iter()
+        .find(|f| f.starts_with("ast-"))
+        .unwrap();
+
+    let other_filename = format!("ast-0000000000000000-{}", &ast_filename[21..]);
+
+    std::fs::rename(
+        dir.join(&ast_filename),
+        dir.join(&other_filename))
+        .unwrap();
+
+    let opts = make_opts(&dir);
+    let mut cache = opts.cache.as_ref().unwrap().lock().unwrap();
+    assert!(cache.get_parsed_file(&opts, "main.asm", 0, &src).is_none());
+    drop(cache);
+
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    let filenames = list_cache_files(&dir);
+    assert!(filenames.contains(&ast_filename));
+    assert!(!filenames.contains(&other_filename));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+
+#[test]
+fn test_cache_evicts_least_recently_used()
+{
+    let dir = make_cache_dir("evicted");
+    let src = format!("{}\nld 0x22\nhalt", RULEDEFS);
+
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    let ast_filename = list_cache_files(&dir)
+        .into_iter()
+        .find(|f| f.starts_with("ast-"))
+        .unwrap();
+
+    let old_time = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+
+    for i in 0..asm::cache::CACHE_FILES_MAX
+    {
+        let filename = dir.join(format!("{}{:016x}.json", &ast_filename[..21], i));
+        std::fs::copy(dir.join(&ast_filename), &filename).unwrap();
+
+        std::fs::File::options()
+            .append(true)
+            .open(&filename)
+            .unwrap()
+            .set_modified(old_time)
+            .unwrap();
+    }
+
+    // Loading the file again marks it as the most recently used
+    let opts = make_opts(&dir);
+    assert_eq!(assemble_with_cache(&opts, &src).unwrap(), "1122ff");
+
+    let filenames = list_cache_files(&dir);
+    assert_eq!(filenames.len(), asm::cache::CACHE_FILES_MAX);
+    assert!(filenames.contains(&ast_filename));
+    assert!(filenames.iter().any(|f| f.starts_with("matches-")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -389,6 +389,30 @@ fn test_ops_relational_bool()
 }
 
 
+#[test]
+fn test_ops_string()
+{
+	test("\"ab\"", Pass(expr::Value::make_string("ab")));
+	test("\"ab\" @ \"cd\"", Pass(expr::Value::make_string("abcd")));
+	test("\"ab\" @ 0x00", Pass(expr::Value::make_integer(util::BigInt::new(0x616200, Some(24)))));
+	test("\"ab\" + 1", Pass(expr::Value::make_integer(util::BigInt::new(0x6163, None))));
+
+	test("\"ab\" == \"ab\"", Pass(expr::Value::make_bool(true)));
+	test("\"ab\" != \"ab\"", Pass(expr::Value::make_bool(false)));
+	test("\"ab\" <  \"b\"",  Pass(expr::Value::make_bool(true)));
+	test("\"b\"  <= \"ab\"", Pass(expr::Value::make_bool(false)));
+	test("\"a\"  == 0x61",  Pass(expr::Value::make_bool(true)));
+
+	test("$upper(\"ab\")", Pass(expr::Value::make_string("AB")));
+	test("$lower(\"AB\")", Pass(expr::Value::make_string("ab")));
+	test("$str(255, 16)", Pass(expr::Value::make_string("ff")));
+	test("$substr(\"hello\", 1, 3)", Pass(expr::Value::make_string("el")));
+	test("$format(\"{:04x}\", 0xab)", Pass(expr::Value::make_string("00ab")));
+
+	test("\"ab\" && true", Fail(("test", 1, "invalid argument type")));
+}
+
+
 #[test]
 fn test_ops_lazy()
 {
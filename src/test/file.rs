@@ -171,7 +171,7 @@ fn populate_fileserver(
             let mut filename = cur_folder_name.to_string();
             filename.push_str(&file_stem);
 
-            let contents = std::fs::read_to_string(&path).unwrap();
+            let contents = std::fs::read(&path).unwrap();
             fileserver.add(&filename, contents);
         }
         else
@@ -0,0 +1,255 @@
+use crate::*;
+
+
+const SRC: &str = "\
+#ruledef cpu
+{
+    ld {x: u8} => 0x55 @ x
+    halt => 0xff
+}
+
+start:
+    ld value
+.loop:
+    ld .loop
+    halt
+value = 0x12
+";
+
+
+const URI: &str = "file:///project/main.asm";
+
+
+fn message(value: serde_json::Value) -> Vec<u8>
+{
+    let body = value.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+}
+
+
+/// Opens `src` in a fresh server, sends the given
+/// requests, and returns every message written back.
+fn run(src: &str, requests: &[serde_json::Value]) -> Vec<serde_json::Value>
+{
+    let mut input = Vec::new();
+
+    input.extend(message(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {},
+    })));
+
+    input.extend(message(serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": {
+                "uri": URI,
+                "languageId": "customasm",
+                "version": 1,
+                "text": src,
+            },
+        },
+    })));
+
+    for request in requests
+    {
+        input.extend(message(request.clone()));
+    }
+
+    let mut server = lsp::Server::new(&[]);
+    let mut reader = std::io::Cursor::new(input);
+    let mut output = Vec::new();
+
+    // Fails for the missing shutdown request
+    assert!(lsp::serve(&mut server, &mut reader, &mut output).is_err());
+
+    let mut reader = std::io::Cursor::new(output);
+    let mut responses = Vec::new();
+
+    while let Some(msg) = lsp::read_message(&mut reader)
+    {
+        responses.push(msg.unwrap());
+    }
+
+    responses
+}
+
+
+fn position_request(
+    id: usize,
+    method: &str,
+    line: usize,
+    character: usize)
+    -> serde_json::Value
+{
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": {
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        },
+    })
+}
+
+
+fn find_response(
+    responses: &[serde_json::Value],
+    id: usize)
+    -> &serde_json::Value
+{
+    &responses
+        .iter()
+        .find(|r| r["id"] == id)
+        .unwrap()["result"]
+}
+
+
+#[test]
+fn test_lsp_diagnostics()
+{
+    let src_with_error = format!("{}    ld unknown\n", SRC);
+
+    let responses = run(&src_with_error, &[
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": URI, "version": 2 },
+                "contentChanges": [{ "text": SRC }],
+            },
+        }),
+    ]);
+
+    let publishes = responses
+        .iter()
+        .filter(|r| r["method"] == "textDocument/publishDiagnostics")
+        .collect::<Vec<_>>();
+
+    assert_eq!(publishes.len(), 2);
+    assert_eq!(publishes[0]["params"]["uri"], URI);
+
+    let diagnostics = publishes[0]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 12);
+
+    // Fixing the error clears the diagnostics
+    assert_eq!(publishes[1]["params"]["diagnostics"], serde_json::json!([]));
+}
+
+
+#[test]
+fn test_lsp_definition()
+{
+    let responses = run(SRC, &[
+        position_request(1, "textDocument/definition", 7, 8),
+        position_request(2, "textDocument/definition", 9, 8),
+        position_request(3, "textDocument/definition", 10, 5),
+        position_request(4, "textDocument/definition", 10, 0),
+    ]);
+
+    let range = |line: usize, start: usize, end: usize| serde_json::json!({
+        "uri": URI,
+        "range": {
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        },
+    });
+
+    assert_eq!(*find_response(&responses, 1), range(11, 0, 5));
+    assert_eq!(*find_response(&responses, 2), range(8, 0, 6));
+    assert_eq!(*find_response(&responses, 3), range(3, 4, 8));
+    assert_eq!(*find_response(&responses, 4), serde_json::Value::Null);
+}
+
+
+#[test]
+fn test_lsp_hover()
+{
+    let responses = run(SRC, &[
+        position_request(1, "textDocument/hover", 7, 8),
+        position_request(2, "textDocument/hover", 7, 5),
+    ]);
+
+    assert_eq!(
+        find_response(&responses, 1)["contents"]["value"],
+        "```\n(constant) value = 0x12 (18)\n```");
+
+    assert_eq!(
+        find_response(&responses, 2)["contents"]["value"],
+        "```\n(cpu) ld {x: u8}\n```\n\nencoding: `0x5512`");
+}
+
+
+#[test]
+fn test_lsp_completion()
+{
+    let responses = run(SRC, &[
+        position_request(1, "textDocument/completion", 10, 0),
+    ]);
+
+    let labels = find_response(&responses, 1)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(labels, ["halt", "ld", "start", "value"]);
+}
+
+
+#[test]
+fn test_lsp_malformed_message()
+{
+    let mut input = Vec::new();
+
+    input.extend(b"Content-Length: 7\r\n\r\n{ bad }");
+
+    input.extend(message(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "shutdown",
+    })));
+
+    input.extend(message(serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "exit",
+    })));
+
+    let mut server = lsp::Server::new(&[]);
+    let mut reader = std::io::Cursor::new(input);
+    let mut output = Vec::new();
+
+    // Keeps serving after the malformed message
+    assert!(lsp::serve(&mut server, &mut reader, &mut output).is_ok());
+
+    let mut reader = std::io::Cursor::new(output);
+    let parse_error = lsp::read_message(&mut reader).unwrap().unwrap();
+    let shutdown = lsp::read_message(&mut reader).unwrap().unwrap();
+
+    assert_eq!(parse_error["id"], serde_json::Value::Null);
+    assert_eq!(parse_error["error"]["code"], -32700);
+    assert_eq!(shutdown["id"], 1);
+    assert_eq!(shutdown["result"], serde_json::Value::Null);
+}
+
+
+#[test]
+fn test_lsp_uri_conversion()
+{
+    assert_eq!(lsp::uri_to_path("file:///home/a%20b/main.asm"), "/home/a b/main.asm");
+    assert_eq!(lsp::uri_to_path("file:///C:/project/main.asm"), "C:/project/main.asm");
+    assert_eq!(lsp::path_to_uri("/home/a b/main.asm"), "file:///home/a%20b/main.asm");
+    assert_eq!(lsp::path_to_uri("C:\\project\\main.asm"), "file:///C:/project/main.asm");
+
+    let cwd = std::env::current_dir().unwrap().canonicalize().unwrap();
+    assert_eq!(
+        lsp::path_to_uri("Cargo.toml"),
+        lsp::path_to_uri(&cwd.join("Cargo.toml").to_string_lossy()));
+    assert!(lsp::path_to_uri("Cargo.toml").starts_with("file:///"));
+}
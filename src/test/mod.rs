@@ -1,12 +1,18 @@
 use crate::*;
 
 
+mod cache;
 mod examples;
 mod excerpt;
 mod expr;
 mod file;
 mod file_navigation;
 mod lib;
+mod lsp;
+#[cfg(feature = "parallel-matcher")]
+mod parallel_matcher;
+mod report_format;
+mod watch;
 
 
 // generated by build script
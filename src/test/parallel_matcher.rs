@@ -0,0 +1,133 @@
+use crate::*;
+
+
+fn generate_src(instr_count: usize, with_errors: bool) -> String
+{
+    let mut src = String::new();
+
+    src.push_str("#subruledef reg\n{\n");
+    for r in 0..8
+    {
+        src.push_str(&format!("    r{} => {}`3\n", r, r));
+    }
+    src.push_str("}\n\n");
+
+    src.push_str("#ruledef\n{\n");
+    src.push_str("    nop => 0x00\n");
+    src.push_str("    ld {r: reg}, {imm: i8} => 0x1 @ 0b0 @ r @ imm\n");
+    src.push_str("    ld {r: reg}, [{addr: u16}] => 0x2 @ 0b0 @ r @ addr\n");
+    src.push_str("    add {a: reg}, {b: reg} => 0x3 @ 0b00 @ a @ b @ 0b0000\n");
+    src.push_str("    jmp {addr: u24} => 0x4 @ 0x0 @ addr\n");
+    src.push_str("    jmp {r: reg} => 0x5 @ 0b0 @ r\n");
+    src.push_str("}\n\n");
+
+    for i in 0..instr_count
+    {
+        match i % 7
+        {
+            0 => src.push_str(&format!("l{}:\n    nop\n", i)),
+            1 => src.push_str(&format!("    ld r{}, {}\n", i % 8, i % 100)),
+            2 => src.push_str(&format!("    ld r{}, [0x{:x}]\n", i % 8, i % 0x10000)),
+            3 => src.push_str(&format!("    add r{}, r{}\n", i % 8, (i / 8) % 8)),
+            4 => src.push_str(&format!("    jmp l{}\n", i - 4)),
+            5 if with_errors && i % 700 == 5 => src.push_str("    bad r1\n"),
+            5 => src.push_str(&format!("    jmp r{}\n", i % 8)),
+            _ => src.push_str(&format!("    jmp {} + l{}\n", i % 16, i - 6)),
+        }
+    }
+
+    src
+}
+
+
+fn assemble_src(
+    src: &str,
+    optimize_instruction_matching: bool,
+    matcher_thread_count: usize)
+    -> (Option<Vec<u8>>, String, std::time::Duration)
+{
+    let mut report = diagn::Report::new();
+    let mut fileserver = util::FileServerMock::new();
+    fileserver.add("main.asm", src);
+
+    let opts = asm::AssemblyOptions {
+        optimize_instruction_matching,
+        matcher_thread_count,
+        ..asm::AssemblyOptions::new()
+    };
+
+    let start = std::time::Instant::now();
+
+    let assembly = asm::assemble(
+        &mut report,
+        &opts,
+        &mut fileserver,
+        &["main.asm"]);
+
+    let elapsed = start.elapsed();
+
+    let binary = assembly.output
+        .map(|output| output.format_binary(&mut report));
+
+    let mut msgs = Vec::<u8>::new();
+    report.print_all(&mut msgs, &fileserver, false);
+
+    (binary, String::from_utf8(msgs).unwrap(), elapsed)
+}
+
+
+#[test]
+fn test_parallel_matcher_same_output()
+{
+    let src = generate_src(5000, false);
+
+    let sequential = assemble_src(&src, true, 1);
+    let parallel = assemble_src(&src, true, 4);
+
+    assert!(sequential.0.is_some());
+    assert_eq!(sequential.0, parallel.0);
+    assert_eq!(sequential.1, parallel.1);
+}
+
+
+#[test]
+fn test_parallel_matcher_same_errors()
+{
+    let src = generate_src(5000, true);
+
+    let sequential = assemble_src(&src, true, 1);
+    let parallel = assemble_src(&src, true, 4);
+
+    assert!(sequential.0.is_none());
+    assert!(sequential.1.matches("no match found").count() > 1);
+    assert_eq!(sequential.1, parallel.1);
+}
+
+
+/// Run with `cargo test --release -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_parallel_matcher()
+{
+    let src = generate_src(100000, false);
+
+    for optimize_instruction_matching in [true, false]
+    {
+        let sequential = assemble_src(&src, optimize_instruction_matching, 1);
+        let parallel = assemble_src(&src, optimize_instruction_matching, 0);
+
+        assert_eq!(sequential.0, parallel.0);
+
+        println!(
+            "optimize_instruction_matching = {}: sequential {:?}, parallel {:?}, {:.2}x",
+            optimize_instruction_matching,
+            sequential.2,
+            parallel.2,
+            sequential.2.as_secs_f64() / parallel.2.as_secs_f64());
+
+        if std::thread::available_parallelism().map_or(1, |n| n.get()) > 1
+        {
+            assert!(parallel.2 < sequential.2);
+        }
+    }
+}
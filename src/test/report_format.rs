@@ -0,0 +1,137 @@
+use crate::*;
+
+
+fn assemble_report(src: &str) -> (diagn::Report, util::FileServerMock)
+{
+    let mut report = diagn::Report::new();
+    let mut fileserver = util::FileServerMock::new();
+    fileserver.add("main.asm", src);
+
+    asm::assemble(
+        &mut report,
+        &asm::AssemblyOptions::new(),
+        &mut fileserver,
+        &["main.asm"]);
+
+    (report, fileserver)
+}
+
+
+#[test]
+fn test_report_json()
+{
+    let (report, fileserver) = assemble_report("#d8 1\n#d8 unknown");
+
+    let mut output = Vec::new();
+    report.print_all_json(&mut output, &fileserver);
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json, serde_json::json!({
+        "messages": [{
+            "kind": "error",
+            "description": "failed to resolve data element",
+            "file": "main.asm",
+            "range": {
+                "start": { "line": 2, "column": 5 },
+                "end": { "line": 2, "column": 12 },
+            },
+            "inner": [{
+                "kind": "error",
+                "description": "unknown symbol `unknown`",
+                "file": "main.asm",
+                "range": {
+                    "start": { "line": 2, "column": 5 },
+                    "end": { "line": 2, "column": 12 },
+                },
+                "inner": [],
+            }],
+        }],
+    }));
+}
+
+
+#[test]
+fn test_report_json_no_span()
+{
+    let mut report = diagn::Report::new();
+    report.warning("something happened");
+
+    let mut output = Vec::new();
+    report.print_all_json(&mut output, &util::FileServerMock::new());
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json, serde_json::json!({
+        "messages": [{
+            "kind": "warning",
+            "description": "something happened",
+            "inner": [],
+        }],
+    }));
+}
+
+
+#[test]
+fn test_report_sarif()
+{
+    let (report, fileserver) = assemble_report("#d8 1\n#d8 unknown");
+
+    let mut output = Vec::new();
+    report.print_all_sarif(&mut output, &fileserver);
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["version"], "2.1.0");
+    assert_eq!(json["runs"][0]["tool"]["driver"]["name"], "customasm");
+
+    let results = json["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+
+    let region = serde_json::json!({
+        "startLine": 2,
+        "startColumn": 5,
+        "endLine": 2,
+        "endColumn": 12,
+    });
+
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(results[0]["message"]["text"], "failed to resolve data element");
+    assert_eq!(results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "main.asm");
+    assert_eq!(results[0]["locations"][0]["physicalLocation"]["region"], region);
+
+    assert_eq!(results[0]["relatedLocations"][0]["message"]["text"], "error: unknown symbol `unknown`");
+    assert_eq!(results[0]["relatedLocations"][0]["physicalLocation"]["region"], region);
+}
+
+
+#[test]
+fn test_report_sarif_macro_expansion()
+{
+    let (report, fileserver) = assemble_report(
+        "#ruledef {\n ld {x: u8} => 0x12 @ x\n}\n\
+        #macro foo(a) {\n ld a\n}\n\
+        foo 0x1234");
+
+    let mut output = Vec::new();
+    report.print_all_sarif(&mut output, &fileserver);
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let results = json["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(results[0]["message"]["text"], "failed to resolve instruction");
+
+    let related = results[0]["relatedLocations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|loc| loc["message"]["text"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        related.iter().filter(|text| text.contains("in expansion of macro")).count(),
+        1);
+}
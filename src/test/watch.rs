@@ -0,0 +1,117 @@
+use crate::*;
+
+
+fn assemble_report(src: &str) -> (diagn::Report, util::FileServerMock)
+{
+    let mut report = diagn::Report::new();
+    let mut fileserver = util::FileServerMock::new();
+    fileserver.add("main.asm", src);
+
+    asm::assemble(
+        &mut report,
+        &asm::AssemblyOptions::new(),
+        &mut fileserver,
+        &["main.asm"]);
+
+    (report, fileserver)
+}
+
+
+fn get_summary(old_src: &str, new_src: &str) -> Vec<String>
+{
+    let (old_report, _) = assemble_report(old_src);
+    let (new_report, fileserver) = assemble_report(new_src);
+
+    driver::get_watch_summary(
+        &old_report,
+        &new_report,
+        &fileserver)
+}
+
+
+#[test]
+fn test_watch_summary_unchanged()
+{
+    assert_eq!(
+        get_summary("#d8 x", "#d8 x"),
+        vec!["0 new, 0 fixed, 1 remaining"]);
+}
+
+
+#[test]
+fn test_watch_summary_moved()
+{
+    assert_eq!(
+        get_summary("#d8 x", "#d8 1\n\n#d8 x"),
+        vec!["0 new, 0 fixed, 1 remaining"]);
+}
+
+
+#[test]
+fn test_watch_summary_new_and_fixed()
+{
+    assert_eq!(
+        get_summary("#d8 x", "#d8 y"),
+        vec![
+            "  new: error: main.asm: failed to resolve data element: unknown symbol `y`",
+            "  fixed: error: main.asm: failed to resolve data element: unknown symbol `x`",
+            "1 new, 1 fixed, 0 remaining",
+        ]);
+}
+
+
+#[test]
+fn test_watch_summary_all_fixed()
+{
+    assert_eq!(
+        get_summary("#d8 x", "#d8 1"),
+        vec![
+            "  fixed: error: main.asm: failed to resolve data element: unknown symbol `x`",
+            "0 new, 1 fixed, 0 remaining",
+        ]);
+}
+
+
+#[test]
+fn test_watch_modified_times()
+{
+    let dir = std::env::temp_dir().join(format!(
+        "customasm_test_watch_{}",
+        std::process::id()));
+
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let filename = dir.join("main.asm");
+    let _ = std::fs::remove_file(&filename);
+    let filenames = [filename.to_str().unwrap().to_string()];
+
+    // Missing files are watched for creation
+    let missing = driver::get_modified_times(&filenames);
+    assert_eq!(missing, vec![None]);
+
+    std::fs::write(&filename, "#d8 1").unwrap();
+    let created = driver::get_modified_times(&filenames);
+    assert!(created[0].is_some());
+    assert_ne!(created, missing);
+
+    assert_eq!(driver::get_modified_times(&filenames), created);
+
+    let file = std::fs::File::options()
+        .write(true)
+        .open(&filename)
+        .unwrap();
+
+    file.set_modified(
+        created[0].unwrap() + std::time::Duration::from_secs(1))
+        .unwrap();
+
+    drop(file);
+
+    let modified = driver::get_modified_times(&filenames);
+    assert_ne!(modified, created);
+
+    std::fs::remove_file(&filename).unwrap();
+    assert_eq!(driver::get_modified_times(&filenames), missing);
+
+    let _ = std::fs::remove_dir(&dir);
+}
@@ -177,6 +177,26 @@ impl BigInt
     }
 
 
+    pub fn maybe_add(
+        &self,
+        rhs: &BigInt)
+        -> Option<BigInt>
+    {
+        let largest_bits = std::cmp::max(
+            self.bigint.bits(),
+            rhs.bigint.bits());
+            
+        if largest_bits >= BIGINT_MAX_BITS - 1
+        {
+            return None;
+        }
+
+        self.bigint
+            .checked_add(&rhs.bigint)
+            .map(|res| res.into())
+    }
+
+
     pub fn maybe_sub(
         &self,
         rhs: &BigInt)
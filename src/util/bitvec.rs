@@ -7,6 +7,11 @@ pub struct BitVec
     data: util::BigInt,
     len: usize,
     pub spans: Vec<BitVecSpan>,
+    /// Space left by `#res`, which is taken up
+    /// in its bank without any data being written
+    pub reserved_spans: Vec<BitVecSpan>,
+    /// Final cursor position of each bank, in bits from its start
+    pub bank_ends: Vec<usize>,
 }
 
 
@@ -17,6 +22,8 @@ pub struct BitVecSpan
     pub offset: Option<usize>,
     pub size: usize,
     pub span: diagn::Span,
+    pub bank_ref: util::ItemRef<asm::Bankdef>,
+    pub instr_ref: Option<util::ItemRef<asm::Instruction>>,
 }
 
 
@@ -28,6 +35,15 @@ pub struct BitVecBlock
 }
 
 
+#[derive(Clone, Debug)]
+pub struct BitVecAddressedBlock
+{
+    pub offset: usize,
+    pub size: usize,
+    pub addr: u64,
+}
+
+
 impl BitVec
 {
 	pub fn new() -> BitVec
@@ -36,6 +52,8 @@ impl BitVec
             data: util::BigInt::from(0),
             len: 0,
             spans: Vec::new(),
+            reserved_spans: Vec::new(),
+            bank_ends: Vec::new(),
 		}
 	}
 
@@ -98,6 +116,8 @@ impl BitVec
         span: diagn::Span,
         offset: usize,
         addr: util::BigInt,
+        bank_ref: util::ItemRef<asm::Bankdef>,
+        instr_ref: Option<util::ItemRef<asm::Instruction>>,
         bigint: &util::BigInt)
 	{
         self.write_bigint(
@@ -108,7 +128,9 @@ impl BitVec
             Some(offset),
             bigint.size.unwrap(),
             addr,
-            span);
+            span,
+            bank_ref,
+            instr_ref);
     }
 	
 	
@@ -117,13 +139,17 @@ impl BitVec
         offset: Option<usize>,
         size: usize,
         addr: util::BigInt,
-        span: diagn::Span)
+        span: diagn::Span,
+        bank_ref: util::ItemRef<asm::Bankdef>,
+        instr_ref: Option<util::ItemRef<asm::Instruction>>)
 	{
         self.spans.push(BitVecSpan {
             offset,
             size,
             addr,
             span,
+            bank_ref,
+            instr_ref,
         });
     }
     
@@ -197,6 +223,89 @@ impl BitVec
         
         result
     }
+
+
+    /// Like `get_blocks`, but also splits blocks wherever
+    /// the addresses stop being contiguous (e.g. across banks),
+    /// and gives the address of the first byte of each block.
+    pub fn get_addressed_blocks(
+        &self,
+        defs: &asm::ItemDefs)
+        -> Vec<BitVecAddressedBlock>
+    {
+        let mut result = Vec::new();
+
+        let mut sorted_spans = self.spans
+            .iter()
+            .filter(|s| s.offset.is_some() && s.size > 0)
+            .collect::<Vec<_>>();
+
+        sorted_spans.sort_by(|a, b| a.offset.cmp(&b.offset));
+
+        for block in self.get_blocks()
+        {
+            let block_end = block.offset + block.size;
+
+            let mut current = BitVecAddressedBlock {
+                offset: block.offset,
+                size: 0,
+                addr: 0,
+            };
+
+            let mut is_first = true;
+
+            for span in &sorted_spans
+            {
+                let span_offset = span.offset.unwrap();
+
+                if span_offset < block.offset || span_offset >= block_end
+                {
+                    continue;
+                }
+
+                // Compare addresses in bits, since
+                // banks can have any address unit
+                let addr_unit = defs.bankdefs.get(span.bank_ref).addr_unit as u64;
+
+                let Some(span_addr) = span.addr
+                    .maybe_into::<u64>()
+                    .and_then(|addr| addr.checked_mul(addr_unit))
+                    else { continue };
+
+                if is_first
+                {
+                    current.addr = span_addr;
+                    is_first = false;
+                    continue;
+                }
+
+                let expected_addr =
+                    current.addr + (span_offset - current.offset) as u64;
+
+                if span_addr != expected_addr
+                {
+                    current.size = span_offset - current.offset;
+                    result.push(current);
+
+                    current = BitVecAddressedBlock {
+                        offset: span_offset,
+                        size: 0,
+                        addr: span_addr,
+                    };
+                }
+            }
+
+            current.size = block_end - current.offset;
+            result.push(current);
+        }
+
+        for block in &mut result
+        {
+            block.addr /= 8;
+        }
+
+        result
+    }
 }
 
 
@@ -339,6 +339,111 @@ impl util::BitVec
 	}
 
 
+	pub fn format_srec(
+		&self,
+		report: &mut diagn::Report,
+		defs: &asm::ItemDefs,
+		addr_bytes: usize)
+		-> String
+	{
+		let mut result = String::new();
+		let mut record_count = 0;
+		let mut addr_overflow = false;
+
+		let addr_limit = 1_u64 << (addr_bytes * 8);
+
+		let write_record = |
+			result: &mut String,
+			kind: usize,
+			addr_bytes: usize,
+			addr: u64,
+			data: &[u8]|
+		{
+			let length = (addr_bytes + data.len() + 1) as u8;
+
+			result.push_str(&format!("S{}{:02X}", kind, length));
+
+			let mut checksum = length;
+
+			for i in (0..addr_bytes).rev()
+			{
+				let byte = (addr >> (i * 8)) as u8;
+				result.push_str(&format!("{:02X}", byte));
+				checksum = checksum.wrapping_add(byte);
+			}
+
+			for byte in data.iter().copied()
+			{
+				result.push_str(&format!("{:02X}", byte));
+				checksum = checksum.wrapping_add(byte);
+			}
+
+			result.push_str(&format!("{:02X}", !checksum));
+			result.push('\n');
+		};
+
+		write_record(&mut result, 0, 2, 0, &[]);
+
+		let data_kind = addr_bytes - 1;
+
+		for block in self.get_addressed_blocks(defs)
+		{
+			let offset = block.offset;
+			let size = block.size;
+
+			let mut read_index = offset;
+			let mut addr = block.addr;
+			let mut accum_bytes = Vec::<u8>::new();
+
+			while read_index < offset + size
+			{
+				let mut byte: u8 = 0;
+				for _ in 0..8
+				{
+					byte <<= 1;
+					byte |= if self.read_bit(read_index) { 1 } else { 0 };
+					read_index += 1;
+				}
+
+				accum_bytes.push(byte);
+
+				if accum_bytes.len() >= 32 ||
+					read_index >= offset + size
+				{
+					addr_overflow |= addr + accum_bytes.len() as u64 > addr_limit;
+
+					write_record(&mut result, data_kind, addr_bytes, addr, &accum_bytes);
+					record_count += 1;
+
+					addr += accum_bytes.len() as u64;
+					accum_bytes.clear();
+				}
+			}
+		}
+
+		if record_count <= 0xffff
+		{
+			write_record(&mut result, 5, 2, record_count, &[]);
+		}
+		else
+		{
+			write_record(&mut result, 6, 3, record_count, &[]);
+		}
+
+		write_record(&mut result, 11 - addr_bytes, addr_bytes, 0, &[]);
+
+		if addr_overflow
+		{
+			report.warning(
+				format!(
+					"addresses do not fit in {} bytes; will be truncated",
+					addr_bytes));
+		}
+
+		result
+	}
+
+
 	pub fn format_list(&self, opts: &FormatListOptions) -> String
 	{
 		let mut result = String::new();
@@ -0,0 +1,636 @@
+use crate::*;
+
+
+pub struct FormatElfOptions
+{
+    pub machine: u16,
+    pub is_64bit: bool,
+    pub big_endian: bool,
+    pub entry: String,
+}
+
+
+const ET_EXEC: u16 = 2;
+const EV_CURRENT: u8 = 1;
+
+const PT_LOAD: u32 = 1;
+const PF_RWX: u32 = 0x7;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_NOBITS: u32 = 8;
+const SHF_WRITE_ALLOC: u64 = 0x3;
+const SHF_WRITE_ALLOC_EXEC: u64 = 0x7;
+
+const SHN_ABS: u16 = 0xfff1;
+const STB_GLOBAL_STT_NOTYPE: u8 = 0x10;
+
+
+struct ElfSection
+{
+    bankdef_index: usize,
+    name: String,
+    addr: u64,
+    size: u64,
+    /// Empty for banks without `#outp`, which
+    /// occupy memory but no space in the file
+    data: Vec<u8>,
+    is_nobits: bool,
+}
+
+
+struct ElfSymbol
+{
+    name: String,
+    value: u64,
+    section_index: u16,
+}
+
+
+/// Builds an ELF executable with one loadable segment
+/// and section per bank, plus a symbol table. Banks
+/// without `#outp` become `SHT_NOBITS` sections.
+pub fn format_elf(
+    report: &mut diagn::Report,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &util::BitVec,
+    opts: &FormatElfOptions)
+    -> Result<Vec<u8>, ()>
+{
+    let sections = collect_sections(
+        report,
+        decls,
+        defs,
+        output)?;
+
+    let symbols = collect_symbols(
+        decls,
+        defs,
+        &sections);
+
+    let entry = {
+        if opts.entry.len() == 0
+        {
+            0
+        }
+        else
+        {
+            match symbols.iter().find(|s| s.name == opts.entry)
+            {
+                Some(symbol) => symbol.value,
+                None =>
+                {
+                    report.error(
+                        format!(
+                            "unknown entry symbol `{}`",
+                            opts.entry));
+
+                    return Err(());
+                }
+            }
+        }
+    };
+
+    if !opts.is_64bit
+    {
+        let max_addr = sections
+            .iter()
+            .map(|s| s.addr + s.size)
+            .chain(symbols.iter().map(|s| s.value))
+            .max()
+            .unwrap_or(0);
+
+        if max_addr > u32::MAX as u64
+        {
+            report.error(
+                "addresses do not fit in a 32-bit ELF file; use `class:64`");
+
+            return Err(());
+        }
+    }
+
+    Ok(write_elf(opts, entry, &sections, &symbols))
+}
+
+
+fn collect_sections(
+    report: &mut diagn::Report,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &util::BitVec)
+    -> Result<Vec<ElfSection>, ()>
+{
+    let mut sections = Vec::new();
+
+    // The initial bank is only in use when
+    // no other banks have been declared
+    let first_index = if defs.bankdefs.len() > 1 { 1 } else { 0 };
+
+    for i in first_index..defs.bankdefs.len()
+    {
+        let bankdef = defs.bankdefs.get(util::ItemRef::new(i));
+        let bankdef_decl = decls.bankdefs.get(bankdef.item_ref);
+
+        let name = {
+            if i == 0
+                { ".text".to_string() }
+            else
+                { bankdef_decl.name.clone() }
+        };
+
+        let addr = bankdef.addr_start
+            .maybe_into::<u64>()
+            .and_then(|a| a.checked_mul(bankdef.addr_unit as u64))
+            .map(|a| a / 8);
+
+        let Some(addr) = addr
+        else
+        {
+            report.error_span(
+                format!(
+                    "address of bank `{}` is not supported in ELF output",
+                    name),
+                bankdef_decl.span);
+
+            return Err(());
+        };
+
+        let Some(output_offset) = bankdef.output_offset
+        else
+        {
+            let size_in_bits = bankdef.size_in_bits
+                .or(output.bank_ends.get(i).copied())
+                .unwrap_or(0);
+
+            if size_in_bits == 0
+            {
+                continue;
+            }
+
+            sections.push(ElfSection {
+                bankdef_index: i,
+                name,
+                addr,
+                size: size_in_bits.div_ceil(8) as u64,
+                data: Vec::new(),
+                is_nobits: true,
+            });
+
+            continue;
+        };
+
+        let output_end = {
+            match bankdef.size_in_bits
+            {
+                Some(size) => output_offset + size,
+                None => (0..defs.bankdefs.len())
+                    .filter_map(|j| defs.bankdefs.get(util::ItemRef::new(j)).output_offset)
+                    .filter(|&o| o > output_offset)
+                    .min()
+                    .unwrap_or(output.len()),
+            }
+        };
+
+        let output_end = output_end.min(output.len());
+
+        if output_end <= output_offset
+        {
+            continue;
+        }
+
+        if output_offset % 8 != 0 ||
+            (output_end - output_offset) % 8 != 0
+        {
+            report.warning(
+                format!(
+                    "bank `{}` is not aligned to an 8-bit boundary; will be padded with zeroes",
+                    name));
+        }
+
+        let mut data = Vec::new();
+
+        let mut index = output_offset;
+        while index < output_end
+        {
+            let mut byte: u8 = 0;
+            for _ in 0..8
+            {
+                byte <<= 1;
+                byte |= if index < output_end && output.read_bit(index) { 1 } else { 0 };
+                index += 1;
+            }
+
+            data.push(byte);
+        }
+
+        sections.push(ElfSection {
+            bankdef_index: i,
+            name,
+            addr,
+            size: data.len() as u64,
+            data,
+            is_nobits: false,
+        });
+    }
+
+    Ok(sections)
+}
+
+
+fn collect_symbols(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    sections: &[ElfSection])
+    -> Vec<ElfSymbol>
+{
+    let mut symbols = Vec::new();
+
+    decls.symbols.format(
+        decls,
+        defs,
+        &mut |_, symbol_decl, name, bigint|
+        {
+            let Some(value) = bigint.maybe_into::<u64>()
+                else { return };
+
+            let symbol = defs.symbols.get(symbol_decl.item_ref);
+
+            let bankdef_ref = {
+                match (symbol_decl.kind, symbol.bankdef_ref)
+                {
+                    (util::SymbolKind::Label, Some(bankdef_ref)) => Some(bankdef_ref),
+                    _ => None,
+                }
+            };
+
+            let Some(bankdef_ref) = bankdef_ref
+            else
+            {
+                symbols.push(ElfSymbol {
+                    name: name.to_string(),
+                    value,
+                    section_index: SHN_ABS,
+                });
+
+                return;
+            };
+
+            let bankdef = defs.bankdefs.get(bankdef_ref);
+
+            let Some(value) = value.checked_mul(bankdef.addr_unit as u64)
+                else { return };
+
+            let value = value / 8;
+
+            let section_index = sections
+                .iter()
+                .position(|s| s.bankdef_index == bankdef_ref.0)
+                .map(|i| i as u16 + 1)
+                .unwrap_or(SHN_ABS);
+
+            symbols.push(ElfSymbol {
+                name: name.to_string(),
+                value,
+                section_index,
+            });
+        });
+
+    symbols
+}
+
+
+struct ElfWriter
+{
+    bytes: Vec<u8>,
+    is_64bit: bool,
+    big_endian: bool,
+}
+
+
+impl ElfWriter
+{
+    fn write_u8(&mut self, value: u8)
+    {
+        self.bytes.push(value);
+    }
+
+
+    fn write_u16(&mut self, value: u16)
+    {
+        if self.big_endian
+            { self.bytes.extend_from_slice(&value.to_be_bytes()); }
+        else
+            { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    }
+
+
+    fn write_u32(&mut self, value: u32)
+    {
+        if self.big_endian
+            { self.bytes.extend_from_slice(&value.to_be_bytes()); }
+        else
+            { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    }
+
+
+    fn write_u64(&mut self, value: u64)
+    {
+        if self.big_endian
+            { self.bytes.extend_from_slice(&value.to_be_bytes()); }
+        else
+            { self.bytes.extend_from_slice(&value.to_le_bytes()); }
+    }
+
+
+    /// Writes an address, offset, or other
+    /// field whose size depends on the class.
+    fn write_word(&mut self, value: u64)
+    {
+        if self.is_64bit
+            { self.write_u64(value); }
+        else
+            { self.write_u32(value as u32); }
+    }
+
+
+    fn align(&mut self, alignment: usize)
+    {
+        while self.bytes.len() % alignment != 0
+        {
+            self.bytes.push(0);
+        }
+    }
+}
+
+
+struct StringTable
+{
+    bytes: Vec<u8>,
+}
+
+
+impl StringTable
+{
+    fn new() -> StringTable
+    {
+        StringTable {
+            bytes: vec![0],
+        }
+    }
+
+
+    fn add(&mut self, string: &str) -> u32
+    {
+        let index = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(string.as_bytes());
+        self.bytes.push(0);
+        index
+    }
+}
+
+
+fn write_elf(
+    opts: &FormatElfOptions,
+    entry: u64,
+    sections: &[ElfSection],
+    symbols: &[ElfSymbol])
+    -> Vec<u8>
+{
+    let (ehsize, phentsize, shentsize, symentsize, word_size) = {
+        if opts.is_64bit
+            { (64, 56, 64, 24, 8) }
+        else
+            { (52, 32, 40, 16, 4) }
+    };
+
+    let mut w = ElfWriter {
+        bytes: Vec::new(),
+        is_64bit: opts.is_64bit,
+        big_endian: opts.big_endian,
+    };
+
+
+    // Section indices: null, banks..., .symtab, .strtab, .shstrtab
+    let strtab_index = sections.len() + 2;
+    let shstrtab_index = sections.len() + 3;
+    let section_count = sections.len() + 4;
+
+    let mut shstrtab = StringTable::new();
+    let section_names = sections
+        .iter()
+        .map(|s| shstrtab.add(&s.name))
+        .collect::<Vec<_>>();
+    let symtab_name = shstrtab.add(".symtab");
+    let strtab_name = shstrtab.add(".strtab");
+    let shstrtab_name = shstrtab.add(".shstrtab");
+
+    let mut strtab = StringTable::new();
+    let symbol_names = symbols
+        .iter()
+        .map(|s| strtab.add(&s.name))
+        .collect::<Vec<_>>();
+
+
+    // Compute the file layout
+    let phoff = ehsize;
+    let mut offset = phoff + phentsize * sections.len();
+
+    let mut section_offsets = Vec::new();
+    for section in sections
+    {
+        section_offsets.push(offset);
+        offset += section.data.len();
+    }
+
+    offset = offset.next_multiple_of(word_size);
+    let symtab_offset = offset;
+    let symtab_size = symentsize * (symbols.len() + 1);
+    offset += symtab_size;
+
+    let strtab_offset = offset;
+    offset += strtab.bytes.len();
+
+    let shstrtab_offset = offset;
+    offset += shstrtab.bytes.len();
+
+    let shoff = offset.next_multiple_of(word_size);
+
+
+    // ELF header
+    w.bytes.extend_from_slice(&[0x7f, b'E', b'L', b'F']);
+    w.write_u8(if opts.is_64bit { 2 } else { 1 });
+    w.write_u8(if opts.big_endian { 2 } else { 1 });
+    w.write_u8(EV_CURRENT);
+    w.align(16);
+    w.write_u16(ET_EXEC);
+    w.write_u16(opts.machine);
+    w.write_u32(EV_CURRENT as u32);
+    w.write_word(entry);
+    w.write_word(phoff as u64);
+    w.write_word(shoff as u64);
+    w.write_u32(0);
+    w.write_u16(ehsize as u16);
+    w.write_u16(phentsize as u16);
+    w.write_u16(sections.len() as u16);
+    w.write_u16(shentsize as u16);
+    w.write_u16(section_count as u16);
+    w.write_u16(shstrtab_index as u16);
+
+
+    // Program headers
+    for (section, &section_offset) in sections.iter().zip(&section_offsets)
+    {
+        w.write_u32(PT_LOAD);
+
+        if opts.is_64bit
+        {
+            w.write_u32(PF_RWX);
+        }
+
+        w.write_word(section_offset as u64);
+        w.write_word(section.addr);
+        w.write_word(section.addr);
+        w.write_word(section.data.len() as u64);
+        w.write_word(section.size);
+
+        if !opts.is_64bit
+        {
+            w.write_u32(PF_RWX);
+        }
+
+        w.write_word(1);
+    }
+
+
+    // Section contents
+    for section in sections
+    {
+        w.bytes.extend_from_slice(&section.data);
+    }
+
+    w.align(word_size);
+
+    for _ in 0..symentsize
+    {
+        w.write_u8(0);
+    }
+
+    for (symbol, &name) in symbols.iter().zip(&symbol_names)
+    {
+        w.write_u32(name);
+
+        if opts.is_64bit
+        {
+            w.write_u8(STB_GLOBAL_STT_NOTYPE);
+            w.write_u8(0);
+            w.write_u16(symbol.section_index);
+            w.write_u64(symbol.value);
+            w.write_u64(0);
+        }
+        else
+        {
+            w.write_u32(symbol.value as u32);
+            w.write_u32(0);
+            w.write_u8(STB_GLOBAL_STT_NOTYPE);
+            w.write_u8(0);
+            w.write_u16(symbol.section_index);
+        }
+    }
+
+    w.bytes.extend_from_slice(&strtab.bytes);
+    w.bytes.extend_from_slice(&shstrtab.bytes);
+
+    w.align(word_size);
+
+
+    // Section headers
+    let write_section_header = |
+        w: &mut ElfWriter,
+        name: u32,
+        kind: u32,
+        flags: u64,
+        addr: u64,
+        offset: usize,
+        size: usize,
+        link: usize,
+        info: usize,
+        entsize: usize|
+    {
+        w.write_u32(name);
+        w.write_u32(kind);
+        w.write_word(flags);
+        w.write_word(addr);
+        w.write_word(offset as u64);
+        w.write_word(size as u64);
+        w.write_u32(link as u32);
+        w.write_u32(info as u32);
+        w.write_word(1);
+        w.write_word(entsize as u64);
+    };
+
+    write_section_header(&mut w, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+
+    for i in 0..sections.len()
+    {
+        let (kind, flags) = {
+            if sections[i].is_nobits
+                { (SHT_NOBITS, SHF_WRITE_ALLOC) }
+            else
+                { (SHT_PROGBITS, SHF_WRITE_ALLOC_EXEC) }
+        };
+
+        write_section_header(
+            &mut w,
+            section_names[i],
+            kind,
+            flags,
+            sections[i].addr,
+            section_offsets[i],
+            sections[i].size as usize,
+            0,
+            0,
+            0);
+    }
+
+    // All symbols are global, so the first
+    // non-local one comes right after the null entry
+    write_section_header(
+        &mut w,
+        symtab_name,
+        SHT_SYMTAB,
+        0,
+        0,
+        symtab_offset,
+        symtab_size,
+        strtab_index,
+        1,
+        symentsize);
+
+    write_section_header(
+        &mut w,
+        strtab_name,
+        SHT_STRTAB,
+        0,
+        0,
+        strtab_offset,
+        strtab.bytes.len(),
+        0,
+        0,
+        0);
+
+    write_section_header(
+        &mut w,
+        shstrtab_name,
+        SHT_STRTAB,
+        0,
+        0,
+        shstrtab_offset,
+        shstrtab.bytes.len(),
+        0,
+        0,
+        0);
+
+    w.bytes
+}
@@ -498,6 +498,100 @@ impl FileServer for FileServerReal
 }
 
 
+/// Wraps another `FileServer`, recording the name of
+/// every file that was successfully opened through it.
+pub struct FileServerRecorder<'a>
+{
+	inner: &'a mut dyn FileServer,
+	opened_filenames: Vec<String>,
+}
+
+
+impl<'a> FileServerRecorder<'a>
+{
+	pub fn new(inner: &'a mut dyn FileServer) -> FileServerRecorder<'a>
+	{
+		FileServerRecorder {
+			inner,
+			opened_filenames: Vec::new(),
+		}
+	}
+
+
+	/// Returns the opened filenames, in the order
+	/// they were first opened.
+	pub fn get_opened_filenames(&self) -> &[String]
+	{
+		&self.opened_filenames
+	}
+}
+
+
+impl<'a> FileServer for FileServerRecorder<'a>
+{
+	fn get_handle(
+		&mut self,
+		report: &mut diagn::Report,
+		span: Option<diagn::Span>,
+		filename: &str)
+		-> Result<FileServerHandle, ()>
+	{
+		let handle = self.inner.get_handle(
+			report,
+			span,
+			filename)?;
+
+		let opened_filename = self.inner.get_filename(handle);
+
+		if !self.opened_filenames.iter().any(|f| f == opened_filename)
+		{
+			self.opened_filenames.push(opened_filename.to_string());
+		}
+
+		Ok(handle)
+	}
+
+
+	fn get_filename(
+		&self,
+		file_handle: FileServerHandle)
+		-> &str
+	{
+		self.inner.get_filename(file_handle)
+	}
+
+
+	fn get_bytes(
+		&self,
+		report: &mut diagn::Report,
+		span: Option<diagn::Span>,
+		file_handle: FileServerHandle)
+		-> Result<Vec<u8>, ()>
+	{
+		self.inner.get_bytes(
+			report,
+			span,
+			file_handle)
+	}
+
+
+	fn write_bytes(
+		&mut self,
+		report: &mut diagn::Report,
+		span: Option<diagn::Span>,
+		filename: &str,
+		data: &Vec<u8>)
+		-> Result<(), ()>
+	{
+		self.inner.write_bytes(
+			report,
+			span,
+			filename,
+			data)
+	}
+}
+
+
 fn report_error<S>(
 	report: &mut diagn::Report,
 	span: Option<diagn::Span>,
@@ -0,0 +1,214 @@
+use crate::*;
+
+
+/// Maps byte addresses to their contents.
+pub type HexImportData = std::collections::BTreeMap<u64, u8>;
+
+
+pub fn parse_intelhex(
+    report: &mut diagn::Report,
+    span: diagn::Span,
+    contents: &str)
+    -> Result<HexImportData, ()>
+{
+    let mut data = HexImportData::new();
+    let mut addr_base: u64 = 0;
+
+    for (line_index, line) in contents.lines().enumerate()
+    {
+        let line = line.trim();
+        if line.len() == 0
+        {
+            continue;
+        }
+
+        let Some(record) = line.strip_prefix(':')
+            .and_then(|r| parse_hex_bytes(r))
+            .filter(|r| r.len() >= 5 && r.len() == r[0] as usize + 5)
+        else
+        {
+            report.error_span(
+                format!(
+                    "invalid Intel HEX record at line {}",
+                    line_index + 1),
+                span);
+
+            return Err(());
+        };
+
+        let checksum = record
+            .iter()
+            .fold(0_u8, |sum, b| sum.wrapping_add(*b));
+
+        if checksum != 0
+        {
+            report.error_span(
+                format!(
+                    "checksum mismatch in Intel HEX record at line {}",
+                    line_index + 1),
+                span);
+
+            return Err(());
+        }
+
+        let addr = ((record[1] as u64) << 8) | record[2] as u64;
+        let payload = &record[4..record.len() - 1];
+
+        match record[3]
+        {
+            // Data
+            0x00 =>
+            {
+                for (i, byte) in payload.iter().enumerate()
+                {
+                    data.insert(addr_base + addr + i as u64, *byte);
+                }
+            }
+
+            // End of file
+            0x01 => break,
+
+            // Extended segment address
+            0x02 if payload.len() == 2 =>
+            {
+                addr_base = (((payload[0] as u64) << 8) | payload[1] as u64) << 4;
+            }
+
+            // Extended linear address
+            0x04 if payload.len() == 2 =>
+            {
+                addr_base = (((payload[0] as u64) << 8) | payload[1] as u64) << 16;
+            }
+
+            // Start segment address and start linear address
+            0x03 | 0x05 => {}
+
+            _ =>
+            {
+                report.error_span(
+                    format!(
+                        "invalid Intel HEX record at line {}",
+                        line_index + 1),
+                    span);
+
+                return Err(());
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+
+pub fn parse_srec(
+    report: &mut diagn::Report,
+    span: diagn::Span,
+    contents: &str)
+    -> Result<HexImportData, ()>
+{
+    let mut data = HexImportData::new();
+
+    for (line_index, line) in contents.lines().enumerate()
+    {
+        let line = line.trim();
+        if line.len() == 0
+        {
+            continue;
+        }
+
+        let kind = line
+            .strip_prefix('S')
+            .and_then(|r| r.chars().next())
+            .and_then(|c| c.to_digit(10));
+
+        let record = line
+            .get(2..)
+            .and_then(|r| parse_hex_bytes(r))
+            .filter(|r| r.len() >= 2 && r.len() == r[0] as usize + 1);
+
+        let addr_bytes = {
+            match kind
+            {
+                Some(0) | Some(1) | Some(5) | Some(9) => Some(2),
+                Some(2) | Some(6) | Some(8) => Some(3),
+                Some(3) | Some(7) => Some(4),
+                _ => None,
+            }
+        };
+
+        let (Some(kind), Some(record), Some(addr_bytes)) = (kind, record, addr_bytes)
+        else
+        {
+            report_invalid_srec(report, span, line_index);
+            return Err(());
+        };
+
+        if record.len() < addr_bytes + 2
+        {
+            report_invalid_srec(report, span, line_index);
+            return Err(());
+        }
+
+        let checksum = record
+            .iter()
+            .fold(0_u8, |sum, b| sum.wrapping_add(*b));
+
+        if checksum != 0xff
+        {
+            report.error_span(
+                format!(
+                    "checksum mismatch in S-record at line {}",
+                    line_index + 1),
+                span);
+
+            return Err(());
+        }
+
+        // Only data records carry contents
+        if kind < 1 || kind > 3
+        {
+            continue;
+        }
+
+        let addr = record[1..addr_bytes + 1]
+            .iter()
+            .fold(0_u64, |addr, b| (addr << 8) | *b as u64);
+
+        let payload = &record[addr_bytes + 1..record.len() - 1];
+
+        for (i, byte) in payload.iter().enumerate()
+        {
+            data.insert(addr + i as u64, *byte);
+        }
+    }
+
+    Ok(data)
+}
+
+
+fn report_invalid_srec(
+    report: &mut diagn::Report,
+    span: diagn::Span,
+    line_index: usize)
+{
+    report.error_span(
+        format!(
+            "invalid S-record at line {}",
+            line_index + 1),
+        span);
+}
+
+
+fn parse_hex_bytes(digits: &str) -> Option<Vec<u8>>
+{
+    if digits.len() % 2 != 0 ||
+        !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
@@ -1,6 +1,8 @@
+/// An index into a list of `T` items.
+/// It doesn't own a `T`, so it's `Send` and `Sync` regardless.
 pub struct ItemRef<T>(
     pub usize,
-    std::marker::PhantomData<*const T>);
+    std::marker::PhantomData<fn() -> T>);
 
 
 impl<T> ItemRef<T>
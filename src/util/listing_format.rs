@@ -0,0 +1,486 @@
+use crate::*;
+
+
+const BYTES_PER_ROW: usize = 8;
+
+
+struct ListingRow
+{
+    line: String,
+    bank: String,
+    addr: String,
+    data: String,
+    source: String,
+}
+
+
+/// The text of a source file, along with
+/// the byte index where each line starts.
+struct ListingFile
+{
+    src: String,
+    line_starts: Vec<usize>,
+}
+
+
+impl ListingFile
+{
+    fn new(src: String) -> ListingFile
+    {
+        let mut line_starts = vec![0];
+
+        for (index, c) in src.char_indices()
+        {
+            if c == '\n'
+            {
+                line_starts.push(index + 1);
+            }
+        }
+
+        ListingFile {
+            src,
+            line_starts,
+        }
+    }
+
+
+    fn get_line_index(&self, byte_index: usize) -> usize
+    {
+        match self.line_starts.binary_search(&byte_index)
+        {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        }
+    }
+
+
+    fn get_line(&self, line: usize) -> &str
+    {
+        let start = self.line_starts[line];
+        let end = self.line_starts
+            .get(line + 1)
+            .copied()
+            .unwrap_or(self.src.len());
+
+        self.src[start..end].trim()
+    }
+}
+
+
+/// Builds a classic assembler listing, following the order
+/// of the source. Each line that produced output shows its bank,
+/// address, and data. Macro invocations and `asm` blocks
+/// are expanded beneath their originating lines.
+pub fn format_listing(
+    fileserver: &dyn util::FileServer,
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &util::BitVec)
+    -> String
+{
+    let mut files = std::collections::HashMap::<util::FileServerHandle, ListingFile>::new();
+    let mut rows = Vec::new();
+    let mut cur_expansions: &[asm::AstMacroExpansion] = &[];
+
+    let mut index = 0;
+    while index < output.spans.len()
+    {
+        // Group consecutive spans starting on the same line
+        let first = &output.spans[index];
+        let line = get_file(&mut files, fileserver, first.span.file_handle)
+            .get_line_index(first.span.location().unwrap().0);
+
+        let mut group_end = index + 1;
+        while group_end < output.spans.len()
+        {
+            let span = &output.spans[group_end];
+
+            if span.span.file_handle != first.span.file_handle ||
+                span.bank_ref != first.bank_ref
+            {
+                break;
+            }
+
+            let span_line = get_file(&mut files, fileserver, span.span.file_handle)
+                .get_line_index(span.span.location().unwrap().0);
+
+            if span_line != line
+            {
+                break;
+            }
+
+            group_end += 1;
+        }
+
+        let group = &output.spans[index..group_end];
+        index = group_end;
+
+        let maybe_instr = group
+            .iter()
+            .find_map(|s| s.instr_ref)
+            .map(|instr_ref| defs.instructions.get(instr_ref));
+
+        let expansions = maybe_instr
+            .map_or(&[][..], |instr| &instr.macro_expansions[..]);
+
+        // Print the invocation lines of newly-entered macros
+        let common_depth = cur_expansions
+            .iter()
+            .zip(expansions)
+            .take_while(|(a, b)| a.span == b.span)
+            .count();
+
+        for (depth, expansion) in expansions.iter().enumerate().skip(common_depth)
+        {
+            let file = get_file(&mut files, fileserver, expansion.span.file_handle);
+            let invocation_line = file.get_line_index(expansion.span.location().unwrap().0);
+
+            rows.push(ListingRow {
+                line: format!("{}", invocation_line + 1),
+                bank: get_bank_name(decls, first.bank_ref),
+                addr: first.addr.to_str_radix(16),
+                data: "".to_string(),
+                source: format!(
+                    "{}{}",
+                    indent(depth),
+                    file.get_line(invocation_line)),
+            });
+        }
+
+        cur_expansions = expansions;
+
+        let depth = expansions.len();
+        let file = get_file(&mut files, fileserver, first.span.file_handle);
+
+        let mut source = format!(
+            "{}{}",
+            indent(depth),
+            file.get_line(line));
+
+        if let Some(instr) = maybe_instr
+        {
+            if let Some(comment) = get_rule_comment(&mut files, fileserver, defs, instr)
+            {
+                if !has_comment(&source)
+                {
+                    source.push_str(&format!(" ; {}", comment));
+                }
+            }
+        }
+
+        let mut data_rows = format_data(output, group);
+        let addr_unit = defs.bankdefs.get(first.bank_ref).addr_unit;
+
+        // Labels and other lines without data still get a row
+        if data_rows.len() == 0
+        {
+            data_rows.push("".to_string());
+        }
+
+        for (row_index, data) in data_rows.into_iter().enumerate()
+        {
+            if row_index == 0
+            {
+                rows.push(ListingRow {
+                    line: format!("{}", line + 1),
+                    bank: get_bank_name(decls, first.bank_ref),
+                    addr: first.addr.to_str_radix(16),
+                    data,
+                    source: std::mem::take(&mut source),
+                });
+            }
+            else
+            {
+                let bits_before = row_index * BYTES_PER_ROW * 8;
+
+                let maybe_addr = {
+                    if bits_before % addr_unit == 0
+                    {
+                        first.addr
+                            .checked_add(
+                                &mut diagn::Report::new(),
+                                first.span,
+                                &util::BigInt::from(bits_before / addr_unit))
+                            .ok()
+                    }
+                    else
+                    {
+                        None
+                    }
+                };
+
+                rows.push(ListingRow {
+                    line: "".to_string(),
+                    bank: "".to_string(),
+                    addr: maybe_addr.map_or("".to_string(), |a| a.to_str_radix(16)),
+                    data,
+                    source: "".to_string(),
+                });
+            }
+        }
+
+        if let Some(instr) = maybe_instr
+        {
+            for expanded in get_asm_block_lines(defs, instr)
+            {
+                rows.push(ListingRow {
+                    line: "".to_string(),
+                    bank: "".to_string(),
+                    addr: "".to_string(),
+                    data: "".to_string(),
+                    source: format!("{}{}", indent(depth + 1), expanded),
+                });
+            }
+        }
+    }
+
+    format_rows(&rows)
+}
+
+
+fn get_file<'a>(
+    files: &'a mut std::collections::HashMap<util::FileServerHandle, ListingFile>,
+    fileserver: &dyn util::FileServer,
+    file_handle: util::FileServerHandle)
+    -> &'a ListingFile
+{
+    files
+        .entry(file_handle)
+        .or_insert_with(|| ListingFile::new(fileserver.get_str_unwrap(file_handle)))
+}
+
+
+fn get_bank_name(
+    decls: &asm::ItemDecls,
+    bank_ref: util::ItemRef<asm::Bankdef>)
+    -> String
+{
+    // The initial bank has no user-facing name
+    if bank_ref.0 == 0
+    {
+        return "-".to_string();
+    }
+
+    decls.bankdefs.get(bank_ref).name.clone()
+}
+
+
+fn indent(depth: usize) -> String
+{
+    "    ".repeat(depth)
+}
+
+
+/// Formats the data of a group of spans as hex bytes,
+/// split into rows. A trailing chunk of less than
+/// 8 bits is printed in hex if possible, or else in binary.
+fn format_data(
+    output: &util::BitVec,
+    group: &[util::BitVecSpan])
+    -> Vec<String>
+{
+    let mut chunks = Vec::new();
+
+    for span in group
+    {
+        let Some(offset) = span.offset
+            else { continue };
+
+        let mut bit_index = 0;
+        while bit_index < span.size
+        {
+            let chunk_size = (span.size - bit_index).min(8);
+            let bits_per_digit = if chunk_size % 4 == 0 { 4 } else { 1 };
+
+            let mut chunk = String::new();
+
+            for digit_index in 0..(chunk_size / bits_per_digit)
+            {
+                let mut digit = 0;
+
+                for i in 0..bits_per_digit
+                {
+                    let bit = output.read_bit(
+                        offset + bit_index + digit_index * bits_per_digit + i);
+
+                    digit <<= 1;
+                    digit |= if bit { 1 } else { 0 };
+                }
+
+                chunk.push(std::char::from_digit(digit, 16).unwrap());
+            }
+
+            chunks.push(chunk);
+            bit_index += chunk_size;
+        }
+    }
+
+    chunks
+        .chunks(BYTES_PER_ROW)
+        .map(|row| row.join(" "))
+        .collect()
+}
+
+
+/// Finds the comment at the end of the line that declared
+/// the rule used by an instruction, as in `nop => 0x00 ; 2 cycles`.
+fn get_rule_comment(
+    files: &mut std::collections::HashMap<util::FileServerHandle, ListingFile>,
+    fileserver: &dyn util::FileServer,
+    defs: &asm::ItemDefs,
+    instr: &asm::Instruction)
+    -> Option<String>
+{
+    let mtch = instr.get_chosen_match()?;
+    let ruledef = defs.ruledefs.get(mtch.ruledef_ref);
+    let rule = ruledef.get_rule(mtch.rule_ref);
+
+    let span = rule.pattern_span;
+    let file = get_file(files, fileserver, span.file_handle);
+
+    let start = span.location()?.1;
+    let line = file.get_line_index(start);
+    let end = file.line_starts
+        .get(line + 1)
+        .copied()
+        .unwrap_or(file.src.len());
+
+    let comment = find_comment(&file.src[start..end])?;
+
+    let comment = comment
+        .trim_start_matches(';')
+        .trim();
+
+    if comment.len() == 0
+    {
+        return None;
+    }
+
+    Some(comment.to_string())
+}
+
+
+fn find_comment(src: &str) -> Option<&str>
+{
+    let mut index = 0;
+
+    while index < src.len()
+    {
+        let (kind, length) = syntax::decide_next_token(&src[index..]);
+
+        if kind == syntax::TokenKind::Comment
+        {
+            return Some(src[index..index + length].trim_end());
+        }
+
+        index += length;
+    }
+
+    None
+}
+
+
+fn has_comment(src: &str) -> bool
+{
+    find_comment(src).is_some()
+}
+
+
+/// Collects the source of every line in the `asm`
+/// blocks of the rule used by an instruction.
+fn get_asm_block_lines(
+    defs: &asm::ItemDefs,
+    instr: &asm::Instruction)
+    -> Vec<String>
+{
+    let Some(mtch) = instr.get_chosen_match()
+        else { return Vec::new() };
+
+    let ruledef = defs.ruledefs.get(mtch.ruledef_ref);
+    let rule = ruledef.get_rule(mtch.rule_ref);
+
+    let mut lines = Vec::new();
+    collect_asm_block_lines(&rule.expr, &mut lines);
+    lines
+}
+
+
+fn collect_asm_block_lines(
+    expr: &expr::Expr,
+    lines: &mut Vec<String>)
+{
+    match expr
+    {
+        expr::Expr::Asm(_, ast) =>
+        {
+            for node in &ast.nodes
+            {
+                match node
+                {
+                    asm::AstAny::Instruction(ast_instr) =>
+                        lines.push(ast_instr.src.trim().to_string()),
+
+                    asm::AstAny::Symbol(ast_symbol) =>
+                        lines.push(format!("{}:", ast_symbol.name)),
+
+                    _ => {}
+                }
+            }
+        }
+
+        expr::Expr::Block(_, exprs) =>
+        {
+            for expr in exprs
+            {
+                collect_asm_block_lines(expr, lines);
+            }
+        }
+
+        expr::Expr::BinaryOp(_, _, _, lhs, rhs) =>
+        {
+            collect_asm_block_lines(lhs, lines);
+            collect_asm_block_lines(rhs, lines);
+        }
+
+        expr::Expr::TernaryOp(_, _, true_branch, false_branch) =>
+        {
+            collect_asm_block_lines(true_branch, lines);
+            collect_asm_block_lines(false_branch, lines);
+        }
+
+        _ => {}
+    }
+}
+
+
+fn format_rows(rows: &[ListingRow]) -> String
+{
+    let line_width = rows.iter().map(|r| r.line.len()).max().unwrap_or(0).max("line".len());
+    let bank_width = rows.iter().map(|r| r.bank.len()).max().unwrap_or(0).max("bank".len());
+    let addr_width = rows.iter().map(|r| r.addr.len()).max().unwrap_or(0).max("addr".len());
+    let data_width = rows.iter().map(|r| r.data.len()).max().unwrap_or(0).max("data".len());
+
+    let mut result = String::new();
+
+    let mut push_row = |line: &str, bank: &str, addr: &str, data: &str, source: &str| {
+        let row = format!(
+            " {:>line_width$} | {:<bank_width$} | {:>addr_width$} | {:<data_width$} | {}",
+            line,
+            bank,
+            addr,
+            data,
+            source);
+
+        result.push_str(row.trim_end());
+        result.push_str("\n");
+    };
+
+    push_row("line", "bank", "addr", "data", "source");
+
+    for row in rows
+    {
+        push_row(&row.line, &row.bank, &row.addr, &row.data, &row.source);
+    }
+
+    result
+}
@@ -0,0 +1,323 @@
+use crate::*;
+
+
+const MAX_GAPS_PER_BANK: usize = 3;
+
+
+struct MapGap
+{
+    addr: util::BigInt,
+    size_in_units: usize,
+}
+
+
+/// Formats a memory map, listing how much of each bank
+/// is used, by data or by `#res`, along with its largest
+/// free gaps, followed by the size of each label, measured
+/// up to the next label in the same bank, or up to the
+/// end of the bank's contents.
+pub fn format_map(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &util::BitVec)
+    -> String
+{
+    let mut result = String::new();
+
+    result.push_str(&format_banks(decls, defs, output));
+    result.push_str("\n");
+    result.push_str(&format_labels(decls, defs, output));
+
+    result
+}
+
+
+fn format_banks(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &util::BitVec)
+    -> String
+{
+    let mut rows = vec![
+        vec![
+            "bank".to_string(),
+            "start".to_string(),
+            "end".to_string(),
+            "size".to_string(),
+            "used bits".to_string(),
+            "free bits".to_string(),
+            "used".to_string(),
+            "fill".to_string(),
+        ]
+    ];
+
+    let mut gaps_by_row = vec![Vec::new()];
+
+    // The initial bank is only in use when
+    // no other banks have been declared
+    let first_index = if defs.bankdefs.len() > 1 { 1 } else { 0 };
+
+    for i in first_index..defs.bankdefs.len()
+    {
+        let bankdef = defs.bankdefs.get(util::ItemRef::new(i));
+        let bankdef_decl = decls.bankdefs.get(bankdef.item_ref);
+
+        let end = bankdef.size_in_units
+            .filter(|size| *size > 0)
+            .and_then(|size| bankdef.addr_start.maybe_add(&util::BigInt::from(size - 1)))
+            .map_or("-".to_string(), |end| format!("0x{:x}", end));
+
+        let size = bankdef.size_in_units
+            .map_or("-".to_string(), |size| format!("0x{:x}", size));
+
+        let used_ranges = get_used_ranges(
+            bankdef,
+            output);
+
+        let used_bits = used_ranges
+            .iter()
+            .map(|r| r.1 - r.0)
+            .sum::<usize>();
+
+        let (free_bits, used_percent) = {
+            match bankdef.size_in_bits
+            {
+                Some(size) => (
+                    format!("{}", size.saturating_sub(used_bits)),
+                    format!(
+                        "{}%",
+                        if size == 0 { 0 } else { used_bits * 100 / size })),
+
+                None => ("-".to_string(), "-".to_string()),
+            }
+        };
+
+        rows.push(vec![
+            bankdef_decl.name.clone(),
+            format!("0x{:x}", bankdef.addr_start),
+            end,
+            size,
+            format!("{}", used_bits),
+            free_bits,
+            used_percent,
+            if bankdef.fill { "yes" } else { "no" }.to_string(),
+        ]);
+
+        gaps_by_row.push(get_largest_gaps(bankdef, &used_ranges));
+    }
+
+    let mut result = String::new();
+
+    for (row_index, line) in format_table(&rows).lines().enumerate()
+    {
+        result.push_str(line.trim_end());
+        result.push_str("\n");
+
+        for gap in &gaps_by_row[row_index]
+        {
+            result.push_str(&format!(
+                "  gap at 0x{:x}, size 0x{:x}\n",
+                gap.addr,
+                gap.size_in_units));
+        }
+    }
+
+    result
+}
+
+
+/// Gets the ranges of a bank taken up by data or by `#res`,
+/// in bits relative to the start of the bank, sorted and
+/// with overlapping ranges merged.
+fn get_used_ranges(
+    bankdef: &asm::Bankdef,
+    output: &util::BitVec)
+    -> Vec<(usize, usize)>
+{
+    let mut ranges = output.spans
+        .iter()
+        .chain(output.reserved_spans.iter())
+        .filter(|s| s.bank_ref == bankdef.item_ref && s.size > 0)
+        .filter_map(|s| {
+            let start = s.addr
+                .maybe_sub(&bankdef.addr_start)?
+                .maybe_into::<usize>()?
+                * bankdef.addr_unit;
+
+            let end = bankdef.size_in_bits
+                .map_or(start + s.size, |size| (start + s.size).min(size));
+
+            Some((start, end))
+        })
+        .filter(|r| r.0 < r.1)
+        .collect::<Vec<_>>();
+
+    ranges.sort();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+
+    for range in ranges
+    {
+        match merged.last_mut()
+        {
+            Some(last) if range.0 <= last.1 =>
+                last.1 = last.1.max(range.1),
+
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
+
+
+fn get_largest_gaps(
+    bankdef: &asm::Bankdef,
+    used_ranges: &[(usize, usize)])
+    -> Vec<MapGap>
+{
+    let Some(size) = bankdef.size_in_bits
+        else { return Vec::new() };
+
+    let mut gaps = Vec::new();
+    let mut position: usize = 0;
+
+    for range in used_ranges.iter().chain([(size, size)].iter())
+    {
+        let start = position.div_ceil(bankdef.addr_unit);
+        let end = range.0 / bankdef.addr_unit;
+
+        if end > start
+        {
+            if let Some(addr) = bankdef.addr_start.maybe_add(&util::BigInt::from(start))
+            {
+                gaps.push(MapGap {
+                    addr,
+                    size_in_units: end - start,
+                });
+            }
+        }
+
+        position = position.max(range.1);
+    }
+
+    gaps.sort_by(|a, b| b.size_in_units.cmp(&a.size_in_units));
+    gaps.truncate(MAX_GAPS_PER_BANK);
+    gaps
+}
+
+
+fn format_labels(
+    decls: &asm::ItemDecls,
+    defs: &asm::ItemDefs,
+    output: &util::BitVec)
+    -> String
+{
+    let mut labels = Vec::new();
+
+    decls.symbols.format(
+        decls,
+        defs,
+        &mut |_, symbol_decl, name, bigint|
+        {
+            if let util::SymbolKind::Constant = symbol_decl.kind
+            {
+                return;
+            }
+
+            let symbol = defs.symbols.get(symbol_decl.item_ref);
+
+            if let Some(bankdef_ref) = symbol.bankdef_ref
+            {
+                labels.push((bankdef_ref, bigint.clone(), name.to_string()));
+            }
+        });
+
+    labels.sort_by(|a, b| a.0.0.cmp(&b.0.0).then(a.1.cmp(&b.1)));
+
+    let mut rows = vec![
+        vec![
+            "label".to_string(),
+            "bank".to_string(),
+            "addr".to_string(),
+            "size".to_string(),
+        ]
+    ];
+
+    for (index, (bankdef_ref, addr, name)) in labels.iter().enumerate()
+    {
+        let end = {
+            match labels.get(index + 1)
+            {
+                Some(next) if next.0 == *bankdef_ref => Some(next.1.clone()),
+                _ => get_bank_end(defs, *bankdef_ref, output),
+            }
+        };
+
+        let size = end
+            .filter(|end| end >= addr)
+            .and_then(|end| end.maybe_sub(addr))
+            .map_or("-".to_string(), |size| format!("0x{:x}", size));
+
+        rows.push(vec![
+            name.clone(),
+            decls.bankdefs.get(*bankdef_ref).name.clone(),
+            format!("0x{:x}", addr),
+            size,
+        ]);
+    }
+
+    format_table(&rows)
+        .lines()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect()
+}
+
+
+/// Gets the address just past the bank's contents,
+/// including any space left by `#res`.
+fn get_bank_end(
+    defs: &asm::ItemDefs,
+    bankdef_ref: util::ItemRef<asm::Bankdef>,
+    output: &util::BitVec)
+    -> Option<util::BigInt>
+{
+    let bankdef = defs.bankdefs.get(bankdef_ref);
+    let bank_end = output.bank_ends.get(bankdef_ref.0)?;
+
+    bankdef.addr_start.maybe_add(
+        &util::BigInt::from(bank_end.div_ceil(bankdef.addr_unit)))
+}
+
+
+fn format_table(rows: &[Vec<String>]) -> String
+{
+    let column_count = rows
+        .iter()
+        .map(|row| row.len())
+        .max()
+        .unwrap_or(0);
+
+    let widths = (0..column_count)
+        .map(|c| rows
+            .iter()
+            .filter_map(|row| row.get(c))
+            .map(|cell| cell.chars().count())
+            .max()
+            .unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    let mut result = String::new();
+
+    for row in rows
+    {
+        for (c, cell) in row.iter().enumerate()
+        {
+            result.push_str(&format!("{:1$}  ", cell, widths[c]));
+        }
+
+        result.push_str("\n");
+    }
+
+    result
+}
@@ -8,6 +8,7 @@ mod bitvec;
 pub use self::bitvec::{
     BitVec,
     BitVecSpan,
+    BitVecBlock,
 };
 
 mod bitvec_format;
@@ -17,6 +18,25 @@ pub use self::bitvec_format::{
     FormatReadMemOptions,
 };
 
+mod elf_format;
+pub use self::elf_format::{
+    FormatElfOptions,
+    format_elf,
+};
+
+mod listing_format;
+pub use self::listing_format::format_listing;
+
+mod map_format;
+pub use self::map_format::format_map;
+
+mod hex_import;
+pub use self::hex_import::{
+    HexImportData,
+    parse_intelhex,
+    parse_srec,
+};
+
 mod overlap_checker;
 pub use self::overlap_checker::OverlapChecker;
 
@@ -26,6 +46,7 @@ pub use self::fileserver::{
     FileServerHandle,
     FileServerMock,
     FileServerReal,
+    FileServerRecorder,
     FILESERVER_MOCK_WRITE_FILENAME_SUFFIX,
 };
 
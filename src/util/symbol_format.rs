@@ -73,6 +73,295 @@ impl util::SymbolManager<asm::Symbol>
     }
 
 
+    pub fn format_rgbds_sym(
+        &self,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> String
+	{
+        let mut result = String::new();
+        result.push_str("; File generated by customasm\n");
+
+        result.push_str(&self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Some((bank, addr)) = get_banked_label(
+                    defs,
+                    symbol_decl,
+                    bigint,
+                    Some(GAMEBOY_ROM_BANK_SIZE),
+                    Some(ADDR_16BIT_MAX))
+                    else { return };
+
+                result.push_str(&format!(
+                    "{:02x}:{:04x} {}\n",
+                    bank,
+                    addr,
+                    name));
+            }));
+
+        result
+    }
+
+
+    pub fn format_vice_labels(
+        &self,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> String
+	{
+        self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Some((_, addr)) = get_banked_label(
+                    defs,
+                    symbol_decl,
+                    bigint,
+                    None,
+                    Some(ADDR_16BIT_MAX))
+                    else { return };
+
+                result.push_str(&format!(
+                    "al C:{:04x} .{}\n",
+                    addr,
+                    name.replace(".", "_")));
+            })
+    }
+
+
+    pub fn format_mame_script(
+        &self,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> String
+	{
+        self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Some((_, addr)) = get_banked_label(
+                    defs,
+                    symbol_decl,
+                    bigint,
+                    None,
+                    None)
+                    else { return };
+
+                result.push_str(&format!(
+                    "comadd {:x},{}\n",
+                    addr,
+                    name));
+            })
+    }
+
+
+    pub fn format_nocash_sym(
+        &self,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> String
+	{
+        let mut result = String::new();
+        result.push_str(";no$gmb symbolic information\n");
+
+        result.push_str(&self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Some((bank, addr)) = get_banked_label(
+                    defs,
+                    symbol_decl,
+                    bigint,
+                    Some(GAMEBOY_ROM_BANK_SIZE),
+                    Some(ADDR_16BIT_MAX))
+                    else { return };
+
+                result.push_str(&format!(
+                    "{:02x}:{:04x} {}\n",
+                    bank,
+                    addr,
+                    name));
+            }));
+
+        result
+    }
+
+
+    pub fn format_c(
+        &self,
+        report: &mut diagn::Report,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> Result<String, ()>
+	{
+        let mut identifiers = std::collections::HashMap::new();
+        let mut had_error = false;
+
+        let mut result = String::new();
+        result.push_str("#pragma once\n");
+        result.push_str("#include <stdint.h>\n\n");
+
+        result.push_str(&self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Ok(name) = make_identifier(
+                    report,
+                    &mut identifiers,
+                    symbol_decl,
+                    name,
+                    C_KEYWORDS)
+                    else { had_error = true; return };
+
+                let Some((bits, signed)) = get_int_width(bigint, 64)
+                else
+                {
+                    result.push_str(&format!(
+                        "/* `{}` does not fit in 64 bits */\n",
+                        name));
+                    return;
+                };
+
+                let value = bigint.maybe_into::<i128>().unwrap();
+
+                let literal = {
+                    if value == i64::MIN as i128
+                        { "(-0x7fffffffffffffff - 1)".to_string() }
+                    else
+                        { format_hex_literal(value) }
+                };
+
+                result.push_str(&format!(
+                    "static const {}int{}_t {} = {};\n",
+                    if signed { "" } else { "u" },
+                    bits,
+                    name,
+                    literal));
+            }));
+
+        match had_error
+        {
+            false => Ok(result),
+            true => Err(()),
+        }
+    }
+
+
+    pub fn format_rust(
+        &self,
+        report: &mut diagn::Report,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> Result<String, ()>
+	{
+        let mut identifiers = std::collections::HashMap::new();
+        let mut had_error = false;
+
+        let mut result = String::new();
+        result.push_str("#![allow(non_upper_case_globals)]\n\n");
+
+        result.push_str(&self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Ok(name) = make_identifier(
+                    report,
+                    &mut identifiers,
+                    symbol_decl,
+                    name,
+                    RUST_KEYWORDS)
+                    else { had_error = true; return };
+
+                let Some((bits, signed)) = get_int_width(bigint, 128)
+                else
+                {
+                    result.push_str(&format!(
+                        "// `{}` does not fit in 128 bits\n",
+                        name));
+                    return;
+                };
+
+                let literal = {
+                    if signed
+                        { format_hex_literal(bigint.maybe_into::<i128>().unwrap()) }
+                    else
+                        { format!("0x{:x}", bigint.maybe_into::<u128>().unwrap()) }
+                };
+
+                result.push_str(&format!(
+                    "pub const {}: {}{} = {};\n",
+                    name,
+                    if signed { "i" } else { "u" },
+                    bits,
+                    literal));
+            }));
+
+        match had_error
+        {
+            false => Ok(result),
+            true => Err(()),
+        }
+    }
+
+
+    pub fn format_python(
+        &self,
+        report: &mut diagn::Report,
+        decls: &asm::ItemDecls,
+        defs: &asm::ItemDefs)
+        -> Result<String, ()>
+	{
+        let mut identifiers = std::collections::HashMap::new();
+        let mut had_error = false;
+
+        let mut result = String::new();
+        result.push_str("from typing import Final\n\n");
+
+        result.push_str(&self.format(
+            decls,
+            defs,
+            &mut |result, symbol_decl, name, bigint|
+            {
+                let Ok(name) = make_identifier(
+                    report,
+                    &mut identifiers,
+                    symbol_decl,
+                    name,
+                    PYTHON_KEYWORDS)
+                    else { had_error = true; return };
+
+                let digits = bigint.to_str_radix(16);
+
+                let literal = {
+                    match digits.strip_prefix("-")
+                    {
+                        Some(digits) => format!("-0x{}", digits),
+                        None => format!("0x{}", digits),
+                    }
+                };
+
+                result.push_str(&format!(
+                    "{}: Final[int] = {}\n",
+                    name,
+                    literal));
+            }));
+
+        match had_error
+        {
+            false => Ok(result),
+            true => Err(()),
+        }
+    }
+
+
     pub fn format<FnFormat>(
         &self,
         decls: &asm::ItemDecls,
@@ -131,9 +420,9 @@ impl util::SymbolManager<asm::Symbol>
 
             if !symbol.no_emit
             {
-                match symbol.value
+                match symbol.value.get_bigint()
                 {
-                    expr::Value::Integer(_, ref bigint) =>
+                    Some(bigint) =>
                     {
                         let mut name = String::new();
 
@@ -149,7 +438,7 @@ impl util::SymbolManager<asm::Symbol>
 
                         formatter(result, symbol_decl, &name, &bigint);
                     }
-                    _ => {}
+                    None => {}
                 }
             }
 
@@ -165,4 +454,190 @@ impl util::SymbolManager<asm::Symbol>
             hierarchy.pop();
         }
     }
-}
\ No newline at end of file
+}
+
+const GAMEBOY_ROM_BANK_SIZE: usize = 0x4000;
+const ADDR_16BIT_MAX: usize = 0xffff;
+
+
+/// Gets the bank number and address of a label, skipping
+/// constants and labels with addresses over `addr_max`.
+///
+/// The bank number is the `data` field of the label's bank,
+/// as given by `$bankof(label).data`, if it's an integer.
+/// Otherwise, it's derived from the label's output position
+/// divided by `bank_size` in bytes, or zero if not given.
+/// Labels declared before any `#bankdef` are in the default bank.
+fn get_banked_label(
+    defs: &asm::ItemDefs,
+    symbol_decl: &util::SymbolDecl<asm::Symbol>,
+    bigint: &util::BigInt,
+    bank_size: Option<usize>,
+    addr_max: Option<usize>)
+    -> Option<(usize, usize)>
+{
+    if let util::SymbolKind::Constant = symbol_decl.kind
+    {
+        return None;
+    }
+
+    let addr = bigint.maybe_into::<usize>()?;
+
+    if addr_max.is_some_and(|addr_max| addr > addr_max)
+    {
+        return None;
+    }
+
+    let bank_ref = symbol_decl.bank_ref
+        .unwrap_or(util::ItemRef::new(0));
+
+    let bankdef = defs.bankdefs.get(bank_ref);
+
+    if let expr::Value::Integer(_, ref bank) = bankdef.userdata
+    {
+        return Some((bank.maybe_into::<usize>()?, addr));
+    }
+
+    let (Some(bank_size), Some(output_offset)) =
+        (bank_size, bankdef.output_offset)
+        else { return Some((0, addr)) };
+
+    let addr_start = bankdef.addr_start.maybe_into::<usize>()?;
+
+    let output_byte =
+        output_offset / 8 +
+        addr.checked_sub(addr_start)? * bankdef.addr_unit / 8;
+
+    Some((output_byte / bank_size, addr))
+}
+
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue",
+    "default", "do", "double", "else", "enum", "extern", "false",
+    "float", "for", "goto", "if", "inline", "int", "long",
+    "register", "restrict", "return", "short", "signed", "sizeof",
+    "static", "struct", "switch", "true", "typedef", "union",
+    "unsigned", "void", "volatile", "while",
+];
+
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate",
+    "dyn", "else", "enum", "extern", "false", "fn", "for", "gen",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "Self", "static",
+    "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final",
+    "macro", "override", "priv", "try", "typeof", "unsized",
+    "virtual", "yield",
+];
+
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async",
+    "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "finally", "for", "from", "global", "if",
+    "import", "in", "is", "lambda", "nonlocal", "not", "or",
+    "pass", "raise", "return", "try", "while", "with", "yield",
+    "Final",
+];
+
+
+/// Mangles a symbol name into an identifier, and reports
+/// an error if another symbol has already been given the
+/// same identifier, like `parent.child` and `parent_child`
+/// both becoming `parent_child`.
+fn make_identifier(
+    report: &mut diagn::Report,
+    identifiers: &mut std::collections::HashMap<String, (String, diagn::Span)>,
+    symbol_decl: &util::SymbolDecl<asm::Symbol>,
+    name: &str,
+    keywords: &[&str])
+    -> Result<String, ()>
+{
+    let identifier = mangle_identifier(name, keywords);
+
+    if let Some((other_name, other_span)) = identifiers.get(&identifier)
+    {
+        report.push_parent(
+            format!(
+                "symbol `{}` clashes with `{}` as identifier `{}`",
+                name,
+                other_name,
+                identifier),
+            symbol_decl.span);
+
+        report.note_span(
+            format!("`{}` declared here", other_name),
+            *other_span);
+
+        report.pop_parent();
+
+        return Err(());
+    }
+
+    identifiers.insert(
+        identifier.clone(),
+        (name.to_string(), symbol_decl.span));
+
+    Ok(identifier)
+}
+
+
+/// Turns a hierarchical symbol name like `parent.child`
+/// into a valid identifier like `parent_child`, adding
+/// an underscore to names that would clash with keywords.
+fn mangle_identifier(name: &str, keywords: &[&str]) -> String
+{
+    let mut result = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    if result.starts_with(|c: char| c.is_ascii_digit())
+    {
+        result.insert(0, '_');
+    }
+
+    if keywords.contains(&result.as_str())
+    {
+        result.push('_');
+    }
+
+    result
+}
+
+
+/// Gets the smallest of the 8, 16, 32, 64... bit integer types
+/// that can hold the given value, and whether it must be signed.
+fn get_int_width(
+    bigint: &util::BigInt,
+    max_bits: usize)
+    -> Option<(usize, bool)>
+{
+    let signed = bigint.sign() < 0;
+    let min_size = bigint.min_size();
+
+    let mut bits = 8;
+    while bits < min_size
+    {
+        bits *= 2;
+    }
+
+    if bits > max_bits
+    {
+        return None;
+    }
+
+    Some((bits, signed))
+}
+
+
+fn format_hex_literal(value: i128) -> String
+{
+    if value < 0
+        { format!("-0x{:x}", value.unsigned_abs()) }
+    else
+        { format!("0x{:x}", value) }
+}
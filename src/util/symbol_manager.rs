@@ -31,6 +31,7 @@ pub enum SymbolKind
     Constant,
     Label,
     Function,
+    Namespace,
     Other,
 }
 
@@ -39,6 +40,9 @@ pub enum SymbolKind
 pub struct SymbolContext
 {
     hierarchy: Vec<String>,
+    /// How many of the first names in `hierarchy` are
+    /// enclosing `#namespace`s rather than labels
+    namespace_depth: usize,
 }
 
 
@@ -48,6 +52,15 @@ impl SymbolContext
     {
         &self.hierarchy
     }
+
+
+    /// Converts a hierarchy level as written in the source,
+    /// relative to the innermost namespace, into an index
+    /// into `hierarchy`.
+    fn get_absolute_level(&self, hierarchy_level: usize) -> usize
+    {
+        self.namespace_depth + hierarchy_level
+    }
 }
 
 
@@ -156,6 +169,14 @@ impl<T> SymbolManager<T>
     }
 
 
+    /// Iterates over all declarations, in the order
+    /// they were declared.
+    pub fn iter(&self) -> impl Iterator<Item = &util::SymbolDecl<T>>
+    {
+        self.decls.iter()
+    }
+
+
     pub fn get_mut(
         &mut self,
         item_ref: util::ItemRef<T>)
@@ -189,15 +210,32 @@ impl<T> SymbolManager<T>
         -> Option<util::ItemRef<T>>
         where S: std::borrow::Borrow<str> + std::fmt::Debug
     {
-        if hierarchy_level > ctx.hierarchy.len()
+        let level = ctx.get_absolute_level(hierarchy_level);
+
+        if level > ctx.hierarchy.len()
         {
             None
         }
+        else if hierarchy_level == 0
+        {
+            // Search from the innermost namespace outwards
+            (0..=level)
+                .rev()
+                .find_map(|depth| {
+                    let parent = self.get_parent(
+                        None,
+                        &ctx.hierarchy[0..depth]);
+
+                    self.traverse(
+                        parent,
+                        hierarchy)
+                })
+        }
         else
         {
             let parent = self.get_parent(
                 None,
-                &ctx.hierarchy[0..hierarchy_level]);
+                &ctx.hierarchy[0..level]);
             
             self.traverse(
                 parent,
@@ -251,7 +289,9 @@ impl<T> SymbolManager<T>
         hierarchy_level: usize)
         -> Result<util::ItemRef<T>, ()>
     {
-        if hierarchy_level > ctx.hierarchy.len()
+        let level = ctx.get_absolute_level(hierarchy_level);
+
+        if level > ctx.hierarchy.len()
         {
             report.error_span(
                 "invalid label hierarchy",
@@ -262,7 +302,7 @@ impl<T> SymbolManager<T>
         
         let maybe_label = self.get_parent(
             None,
-            &ctx.hierarchy[0..hierarchy_level]);
+            &ctx.hierarchy[0..level]);
 
         match maybe_label
         {
@@ -313,8 +353,10 @@ impl<T> SymbolManager<T>
         kind: SymbolKind)
         -> Result<util::ItemRef<T>, ()>
     {
+        let level = ctx.get_absolute_level(hierarchy_level);
+
         // Check skips in nesting level
-        if hierarchy_level > ctx.hierarchy.len()
+        if level > ctx.hierarchy.len()
         {
             report.error_span(
                 "symbol declaration skips a nesting level",
@@ -332,7 +374,7 @@ impl<T> SymbolManager<T>
         // Check for duplicates at the same nesting level
         let parent_ref = self.get_parent(
             None,
-            &ctx.hierarchy[0..hierarchy_level]);
+            &ctx.hierarchy[0..level]);
 
         let children = self.get_children(parent_ref);
 
@@ -360,7 +402,7 @@ impl<T> SymbolManager<T>
         // Insert ItemRef into the parent's children-list
         let parent_ref = self.get_parent(
             None,
-            &ctx.hierarchy[0..hierarchy_level]);
+            &ctx.hierarchy[0..level]);
         
         let children = self.get_children_mut(parent_ref);
 
@@ -371,15 +413,25 @@ impl<T> SymbolManager<T>
 
         // Generate new SymbolContext
         let new_ctx = {
-            let mut new_hierarchy = ctx.hierarchy[0..hierarchy_level]
+            let mut new_hierarchy = ctx.hierarchy[0..level]
                 .iter()
                 .cloned()
                 .collect::<Vec<_>>();
             
             new_hierarchy.push(name.clone());
 
+            // Symbols declared inside a namespace
+            // are relative to it
+            let namespace_depth = {
+                if let SymbolKind::Namespace = kind
+                    { level + 1 }
+                else
+                    { ctx.namespace_depth }
+            };
+
             SymbolContext {
                 hierarchy: new_hierarchy,
+                namespace_depth,
             }
         };
 
@@ -433,6 +485,7 @@ impl SymbolContext
     {
         SymbolContext {
             hierarchy: Vec::new(),
+            namespace_depth: 0,
         }
     }
 }
\ No newline at end of file
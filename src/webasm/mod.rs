@@ -52,11 +52,27 @@ pub unsafe extern "C" fn wasm_assemble(
 	let formatted = driver::format_output(
 		&mut report,
 		&fileserver,
+		&opts,
+		&assembly.ast.as_ref().unwrap(),
 		&assembly.decls.as_ref().unwrap(),
 		&assembly.defs.as_ref().unwrap(),
 		&output,
 		&format);
 
+	let formatted = {
+		match formatted
+		{
+			Ok(f) => f,
+			Err(()) =>
+			{
+				let mut err = Vec::<u8>::new();
+				report.print_all(&mut err, &fileserver, true);
+				return unsafe { wasm_string_new_with(
+					String::from_utf8(err).unwrap()) };
+			}
+		}
+	};
+
 	unsafe { wasm_string_new_with(String::from_utf8_lossy(&formatted)) }
 }
 
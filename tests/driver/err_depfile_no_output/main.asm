@@ -0,0 +1,4 @@
+#d 0x12
+
+; command: main.asm -p --depfile=out.d
+; error: no output files to write dependencies for
@@ -0,0 +1,4 @@
+#d8 0x55
+
+; command: main.asm -o out.bin --diagnostics-format=xml
+; error: invalid argument for `--diagnostics-format`
@@ -0,0 +1,7 @@
+#ruledef
+{
+    halt => 0x55
+}
+
+; command: main.asm --disassemble=unk.bin -o out.txt
+; error: file not found
@@ -0,0 +1,7 @@
+#ruledef
+{
+    jmp {addr} => 0x55 @ (addr + 1)`8
+}
+
+; command: main.asm --disassemble=main.asm -o out.txt
+; error: no instructions available for disassembly
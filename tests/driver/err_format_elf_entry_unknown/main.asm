@@ -0,0 +1,10 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+start:
+halt
+
+; command: main.asm -f elf,entry:main -o out.elf
+; error: unknown entry symbol `main`
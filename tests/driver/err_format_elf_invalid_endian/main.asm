@@ -0,0 +1,9 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+halt
+
+; command: main.asm -f elf,endian:middle -o out.elf
+; error: invalid format argument `elf,endian
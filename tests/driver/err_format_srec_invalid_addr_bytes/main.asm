@@ -0,0 +1,9 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+halt
+
+; command: main.asm -f srec,addr_bytes:5 -o out.txt
+; error: invalid format argument `srec,addr_bytes
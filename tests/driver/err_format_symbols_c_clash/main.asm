@@ -0,0 +1,14 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+loop:
+halt
+.inner:
+halt
+loop_inner:
+halt
+
+; command: main.asm -f symbols-c -o out.txt
+; error: main.asm:10: symbol `loop_inner` clashes with `loop.inner` as identifier `loop_inner` / note:_:8: `loop.inner` declared here
@@ -0,0 +1,10 @@
+#bankdef rom { #addr 0x100, #size 0x10, #outp 0 }
+
+#layout
+{
+    text => rom
+    rodata => rom, keep = false
+}
+
+; command: link main.asm code.o util.o -o out.bin
+; error: main.asm:6: layout option `keep` is not supported when linking objects
@@ -0,0 +1,5 @@
+#bankdef code { #addr 0x8000, #size 0x10, #outp 0 }
+far = 0x1234
+
+; command: link main.asm far.o -o out.bin
+; error: far.o:14: relocated value does not fit the space left for it / note: object has 16 bits, got 24 bits
@@ -0,0 +1,4 @@
+#bankdef code { #addr 0x8000, #size 0x10, #outp 0 }
+
+; command: link main.asm code.o -o out.bin
+; error: code.o:12: unknown symbol `missing`
@@ -0,0 +1,4 @@
+#d 0x12
+
+; command: main.asm -c -f binary -o out.bin
+; error: `--object` can only be used with the `object` format
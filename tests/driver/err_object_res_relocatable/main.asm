@@ -0,0 +1,4 @@
+#res helper
+
+; command: main.asm -c -o out.o
+; error: main.asm:1: value must be known before linking
@@ -0,0 +1,3 @@
+#d8 0
+; command: -p
+; error: customasm.toml:2: invalid project file
@@ -0,0 +1,3 @@
+#d8 0
+; command: -p
+; error: unknown key `iterations` in `customasm.toml`
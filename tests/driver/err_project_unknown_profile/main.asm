@@ -0,0 +1,4 @@
+DEBUG = false
+#d8 0
+; command: --profile=release
+; error: unknown profile `release` in `customasm.toml`
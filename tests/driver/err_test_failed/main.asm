@@ -0,0 +1,8 @@
+; command: test cpu.inc
+; error: cpu.inc:11: test `wrong encoding` failed / note:cpu.inc:11: expected encoding 0x5513, got 0x5512
+; error: cpu.inc:16: test `unexpected error` failed / error:cpu.inc:18: no match found
+; error: cpu.inc:21: test `missing error` failed / note:cpu.inc:23: expected error `out of range`
+; error: cpu.inc:26: test `extra error` failed / error:cpu.inc:28: no match found
+; error: cpu.inc:26: test `extra error` failed / error:cpu.inc:29: no match found
+; error: cpu.inc:32: test `no output` failed / note:cpu.inc:32: expected encoding 0x5512, got no output
+; error: cpu.inc:32: test `no output` failed / error:cpu.inc:35: no match found
@@ -0,0 +1,7 @@
+#include "sub/rules.asm"
+
+halt
+#d $incbin("data.bin")
+
+; command: main.asm -o out.bin --depfile=out.d
+; output: out.d
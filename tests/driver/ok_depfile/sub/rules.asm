@@ -0,0 +1,4 @@
+#ruledef
+{
+    halt => 0x55
+}
@@ -0,0 +1,7 @@
+#include "<std>/cpu/6502.asm"
+
+nop
+
+; command: main.asm -MD -- -f hexstr -o out.txt
+; output: main.bin.d
+; output: out.txt.d
@@ -0,0 +1,16 @@
+#subruledef reg
+{
+    a => 0x0
+    b => 0x1
+}
+
+#ruledef
+{
+    nop => 0x00
+    ld {r: reg}, {x: u8} => 0x1 @ r`4 @ x
+    jmp {addr: u16} => 0x20 @ le(addr)
+    br {offset: s8} => 0x30 @ offset
+}
+
+; command: main.asm --disassemble=prog.bin -o out.txt
+; output: out.txt
@@ -0,0 +1,16 @@
+#ruledef
+{
+    halt => 0x0000
+    mov {x} => 0x1 @ x[11:0]
+    add {x: u4}, {y: u4} => 0x20 @ x @ y
+}
+
+#bankdef rom
+{
+    bits = 16
+    addr = 0x8000
+    outp = 0
+}
+
+; command: main.asm --disassemble=prog.bin -o out.txt
+; output: out.txt
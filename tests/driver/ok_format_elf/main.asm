@@ -0,0 +1,22 @@
+#ruledef test
+{
+    halt => 0x55
+    jmp {addr: u16} => 0xc3 @ addr
+}
+
+#bankdef rom { #addr 0x8000, #size 0x10, #outp 0 }
+#bankdef ram { #addr 0x2000, #size 0x100 }
+
+#bank rom
+start:
+halt
+.loop:
+jmp .loop
+
+#bank ram
+var: #res 2
+
+value = 0x2a
+
+; command: main.asm -f elf -o out.elf
+; output: out.elf
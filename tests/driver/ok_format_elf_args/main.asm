@@ -0,0 +1,15 @@
+#ruledef test
+{
+    halt => 0x55
+    jmp {addr: u16} => 0xc3 @ addr
+}
+
+#bankdef code { #bits 16, #addr 0x100, #outp 0 }
+
+start:
+#d16 0x1234
+.loop:
+jmp .loop
+
+; command: main.asm -f elf,machine:62,class:64,endian:big,entry:start.loop -o out.elf
+; output: out.elf
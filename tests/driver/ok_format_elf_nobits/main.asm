@@ -0,0 +1,12 @@
+#bankdef rom { #addr 0x100, #size 0x4, #outp 0 }
+#bankdef ram { #addr 0x8000 }
+
+#bank rom
+#d8 0x11, 0x22
+
+#bank ram
+buffer: #res 6
+count: #res 2
+
+; command: main.asm -f elf -o out.elf
+; output: out.elf
@@ -0,0 +1,17 @@
+#ruledef test
+{
+    nop => 0x00 ; 2 cycles
+    ld {x} => 0x10 @ x`8 ; 3 cycles
+    jmp {x: u16} => 0x20 @ x
+}
+
+start: nop
+    ld 0x12 ; load it
+loop:
+    jmp loop
+    #d8 1, 2, 3
+    #d 0x0102030405060708090a
+    #d4 0xf
+
+; command: main.asm -f listing -o out.txt
+; output: out.txt
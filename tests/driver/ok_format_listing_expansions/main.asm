@@ -0,0 +1,26 @@
+#bankdef code { #addr 0x8000, #size 0x100, #outp 0 }
+
+#ruledef
+{
+    nop => 0xea
+    ld {x} => 0xa9 @ x`8 ; 2 cycles
+    clr => asm
+    {
+        ld 0
+        nop
+    }
+}
+
+#macro twice(value)
+{
+    ld value
+    ld value
+}
+
+start:
+    clr
+    twice 0x55
+    nop
+
+; command: main.asm -f listing -o out.txt
+; output: out.txt
@@ -0,0 +1,17 @@
+CONSTANT = 0x1234
+
+start:
+    #d8 0
+.local:
+    #d8 1
+
+#addr 0x4000
+bank1:
+    #d8 2
+
+#addr 0x10000
+far:
+    #d8 3
+
+; command: main.asm -f mame-script -o out.txt
+; output: out.txt
@@ -0,0 +1,32 @@
+#ruledef
+{
+    nop => 0x00
+    jmp {addr: u16} => 0x20 @ addr
+}
+
+#bankdef code { #addr 0x8000, #size 0x10, #outp 0, #fill true }
+#bankdef vectors { #addr 0xfffc, #size 0x4, #outp 8 * 0x10 }
+#bankdef ram { #addr 0x0000, #size 0x100 }
+
+#bank code
+start:
+    nop
+    jmp loop
+loop:
+    jmp start
+
+#addr 0x800c
+tail:
+    nop
+
+#bank vectors
+    #d16 start
+
+#bank ram
+counter:
+    #res 2
+buffer:
+    #res 0x10
+
+; command: main.asm -f map -o out.txt
+; output: out.txt
@@ -0,0 +1,27 @@
+#bankdef rom0 { addr = 0x0000, size = 0x4000, outp = 0 }
+#bankdef rom1 { addr = 0x4000, size = 0x4000, outp = 8 * 0x4000 }
+#bankdef rom5 { addr = 0x4000, size = 0x4000, outp = 8 * 0x8000, data = 5 }
+#bankdef wram { addr = 0xc000, size = 0x2000 }
+
+CONSTANT = 0x1234
+
+#bank rom0
+start:
+    #d8 0
+.local:
+    #d8 1
+
+#bank rom1
+bank1:
+    #d8 2
+
+#bank rom5
+bank5:
+    #d8 3
+
+#bank wram
+var:
+    #res 1
+
+; command: main.asm -f nocash-sym -o out.txt
+; output: out.txt
@@ -0,0 +1,11 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+#addr 0x12_3456
+halt
+halt
+
+; command: main.asm -f srec,addr_bytes:3 -o out.txt
+; output: out.txt
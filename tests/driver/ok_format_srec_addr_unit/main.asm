@@ -0,0 +1,12 @@
+#bankdef words { #bits 16, #addr 0x100, #size 0x4, #outp 0 }
+#bankdef bytes { #addr 0x400, #size 0x2, #outp 16 * 4 }
+
+#bank words
+#d16 0x1122, 0x3344
+#d16 0x5566
+
+#bank bytes
+#d8 0x77
+
+; command: main.asm -f srec -o out.txt
+; output: out.txt
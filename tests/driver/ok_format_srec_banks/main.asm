@@ -0,0 +1,18 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+#bankdef a { #addr 0x8000, #size 0x4, #outp 0 }
+#bankdef b { #addr 0xc000, #size 0x8, #outp 8 * 4 }
+
+#bank a
+halt
+halt
+halt
+halt
+#bank b
+#d "hello"
+
+; command: main.asm -f srec,addr_bytes:2 -o out.txt
+; output: out.txt
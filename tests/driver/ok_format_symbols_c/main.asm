@@ -0,0 +1,21 @@
+#ruledef test
+{
+    halt => 0x55
+}
+
+small = 0x12
+negative = -5
+wide = 0x1_0000_0000
+huge = 1 << 80
+
+#bankdef rom { #addr 0x8000, #size 0x10, #outp 0 }
+
+start:
+halt
+loop:
+halt
+.inner:
+halt
+
+; command: main.asm -f symbols-c -o out.txt
+; output: out.txt
@@ -0,0 +1,6 @@
+#bankdef code { #addr 0x8000, #size 0x20, #outp 0 }
+#bankdef data { #addr 0x9000, #size 0x4, #outp 8 * 0x20 }
+#bank code
+
+; command: link main.asm code.o util.o -o out.bin
+; output: out.bin
@@ -0,0 +1,4 @@
+#bankdef code { #addr 0x8000, #size 0x20, #outp 0 }
+
+; command: link main.asm a.o b.o -o out.bin
+; output: out.bin
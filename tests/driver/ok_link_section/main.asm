@@ -0,0 +1,10 @@
+#bankdef rom { #addr 0x100, #size 0x10, #outp 0 }
+
+#layout
+{
+    text => rom
+    rodata => rom, align = 8
+}
+
+; command: link main.asm code.o util.o -o out.bin
+; output: out.bin
@@ -0,0 +1,21 @@
+#ruledef
+{
+    nop => 0x00
+    ld {x: u8} => 0x10 @ x
+    jmp {addr: u16} => 0x20 @ addr
+}
+
+count = 3
+
+start:
+    nop
+    ld count
+    jmp helper
+    #d8 0xff, count * 2
+    #d16 table
+#bank data
+table:
+    #d8 1, 2
+
+; command: main.asm -c -o out.o
+; output: out.o
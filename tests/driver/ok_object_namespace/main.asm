@@ -0,0 +1,18 @@
+#ruledef
+{
+    ld {x: u8} => 0x10 @ x
+}
+
+#namespace lib
+{
+    #export init
+    count = 3
+    init:
+        ld count
+}
+
+main:
+    ld init
+
+; command: main.asm -c -o out.o
+; output: out.o
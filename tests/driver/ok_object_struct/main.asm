@@ -0,0 +1,14 @@
+#ruledef
+{
+    ld {x: u8} => 0x10 @ x
+}
+
+#enum Color { Red, Green = 5, Blue }
+#struct Point { x: 16, y: EXT }
+
+main:
+    ld Color.Blue
+    ld Point.size
+
+; command: main.asm -c -o out.o
+; output: out.o
@@ -0,0 +1,9 @@
+DEBUG = false
+VERSION = 1
+
+#d8 DEBUG ? 0xdd : 0xee
+#d8 VERSION
+
+; command: --profile=debug -dVERSION=3
+; output: out.bin
+; output: out.txt
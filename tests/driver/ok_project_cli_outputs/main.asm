@@ -0,0 +1,5 @@
+VALUE = 0
+#d8 VALUE
+
+; command: -f hexstr -o out.txt
+; output: out.txt
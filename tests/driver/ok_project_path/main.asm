@@ -0,0 +1,2 @@
+; command: --project=proj/customasm.toml
+; output: proj/out.bin
@@ -0,0 +1 @@
+; command: test cpu.inc
@@ -0,0 +1 @@
+#enum Color { Red, Red } ; error: duplicate symbol `Red` / note:_:1: first declared here
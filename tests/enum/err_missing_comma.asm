@@ -0,0 +1 @@
+#enum Color { Red Green } ; error: expected `}`
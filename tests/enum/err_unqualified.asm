@@ -0,0 +1,9 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#enum Color { Red, Green }
+
+ld Green ; error: failed / note:_:3: within / error: unknown symbol `Green`
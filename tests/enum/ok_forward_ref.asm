@@ -0,0 +1,11 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+ld Op.Last ; = 0x5503
+
+#enum Op { First = BASE, Second, Last }
+
+BASE = 1
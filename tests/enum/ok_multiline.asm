@@ -0,0 +1,17 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#enum Reg
+{
+    A = 0x10
+    B
+    C, D
+}
+
+ld Reg.A ; = 0x5510
+ld Reg.B ; = 0x5511
+ld Reg.C ; = 0x5512
+ld Reg.D ; = 0x5513
@@ -0,0 +1,13 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace cpu
+{
+    #enum Mode { Idle, Run }
+    ld Mode.Run ; = 0x5501
+}
+
+ld cpu.Mode.Run ; = 0x5501
@@ -0,0 +1,18 @@
+#enum Cond { Always, Zero, Carry }
+
+#subruledef cond
+{
+    z => Cond.Zero`2
+    c => Cond.Carry`2
+}
+
+#ruledef test
+{
+    jmp {x: u8} => 0b00 @ Cond.Always`2 @ 0x0 @ x
+    jmp {c: cond}, {x: u8} => 0b00 @ c @ 0x0 @ x
+}
+
+
+jmp 0x12 ; = 0x0012
+jmp z, 0x34 ; = 0x1034
+jmp c, 0x56 ; = 0x2056
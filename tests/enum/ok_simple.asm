@@ -0,0 +1,11 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#enum Color { Red, Green = 5, Blue }
+
+ld Color.Red ; = 0x5500
+ld Color.Green ; = 0x5505
+ld Color.Blue ; = 0x5506
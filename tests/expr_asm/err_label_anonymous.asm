@@ -0,0 +1,10 @@
+#ruledef
+{
+    jmp {addr: u8} => 0xee @ addr
+    loop => asm {
+        :
+        jmp :-
+    }
+}
+
+loop ; error: failed / note:_:4: within / error:_:5: anonymous labels are not permitted
@@ -0,0 +1 @@
+#d "abc" && true ; error: failed / error: invalid argument type
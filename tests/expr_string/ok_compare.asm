@@ -0,0 +1,7 @@
+#d8 "abc" == "abc" ? 1 : 0 ; = 0x01
+#d8 "abc" != "abd" ? 1 : 0 ; = 0x01
+#d8 "ab" < "b" ? 1 : 0 ; = 0x01
+#d8 "b" <= "ab" ? 1 : 0 ; = 0x00
+#d8 "abc" > "abb" ? 1 : 0 ; = 0x01
+#d8 "a" >= "a" ? 1 : 0 ; = 0x01
+#d8 "a" == 0x61 ? 1 : 0 ; = 0x01
@@ -0,0 +1,6 @@
+greeting = "hello"
+name = "world"
+message = greeting @ ", " @ name
+
+#d message ; = 0x68656c6c6f2c20776f726c64
+#d8 $strlen(message) ; = 0x0c
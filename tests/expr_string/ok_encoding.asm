@@ -0,0 +1,6 @@
+#d16 "ab" + 1 ; = 0x6163
+#d "ab"[7:0] ; = 0x62
+#d "a"`16 ; = 0x0061
+#d "ab" @ 0x00 ; = 0x616200
+#d $sizeof("abc")`8 ; = 0x18
+#d "abc".size`8 ; = 0x18
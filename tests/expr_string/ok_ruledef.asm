@@ -0,0 +1,13 @@
+#ruledef test
+{
+    ld {x: u8} => 0x55 @ x
+    str {s} => {
+        $assert(s == "on" || s == "off")
+        0xaa @ $upper(s)`24
+    }
+}
+
+
+ld "a" ; = 0x5561
+str "on" ; = 0xaa004f4e
+str "off" ; = 0xaa4f4646
@@ -0,0 +1,4 @@
+#fn label(index) => $format("item{:02}", index)
+
+#d label(1) ; = 0x6974656d3031
+#d label(12) @ 0x00 ; = 0x6974656d313200
@@ -0,0 +1 @@
+#d $format("{:q}", 1) ; error: failed / error: invalid format placeholder
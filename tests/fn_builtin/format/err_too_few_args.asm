@@ -0,0 +1 @@
+#d $format("{} {}", 1) ; error: failed / error: more placeholders than arguments
@@ -0,0 +1 @@
+#d $format("{}", 1, 2) ; error: failed / error: argument not used in format string
@@ -0,0 +1,12 @@
+#d $format("x") ; = 0x78
+#d $format("{}", 12) ; = 0x3132
+#d $format("{:04x}", 0xab) ; = 0x30306162
+#d $format("{:X}", 0xab) ; = 0x4142
+#d $format("{:08b}", 5) ; = 0x3030303030313031
+#d $format("{:o}", 8) ; = 0x3130
+#d $format("{:3}", 7) ; = 0x202037
+#d $format("{:03}", -7) ; = 0x2d3037
+#d $format("{}={}", "a", true) ; = 0x613d74727565
+#d $format("{:3}|", "a") ; = 0x6120207c
+#d $format("{{}}") ; = 0x7b7d
+#d $format("{:4}|", "é") ; = 0xc3a920207c
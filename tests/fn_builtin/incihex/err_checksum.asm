@@ -0,0 +1 @@
+#d $incihex("data3.hex") ; error: failed / error: checksum mismatch in Intel HEX record at line 1
@@ -0,0 +1 @@
+#d $incihex("unk") ; error: failed / error: not found
@@ -0,0 +1 @@
+#d $incihex("data4.hex") ; error: failed / error: invalid Intel HEX record at line 1
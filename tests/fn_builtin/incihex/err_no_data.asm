@@ -0,0 +1 @@
+#d $incihex("data1.hex", 0x200, 2) ; error: failed / error: range contains no data
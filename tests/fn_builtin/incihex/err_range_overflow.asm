@@ -0,0 +1 @@
+#d $incihex("data1.hex", 0xffffffffffffffff, 2) ; error: failed / error: range end is outside the supported range
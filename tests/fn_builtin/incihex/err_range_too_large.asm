@@ -0,0 +1 @@
+#d $incihex("data5.hex") ; error: failed / error: range is too large / note: give a start address and size
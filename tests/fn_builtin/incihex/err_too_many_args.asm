@@ -0,0 +1 @@
+#d $incihex("data1.hex",0,1,2) ; error: failed / error: expected 1 to 3 arguments
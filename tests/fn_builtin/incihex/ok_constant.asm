@@ -0,0 +1,3 @@
+x = $incihex("data1.hex", 0x100, 2)
+#d x ; = 0x1234
+#d8 $sizeof(x) ; = 0x10
@@ -0,0 +1,2 @@
+#d $incihex("data2.hex", 0x1_0000, 2) ; = 0xdead
+#d $incihex("data2.hex", 0x1_0002, 2) ; = 0xbeef
@@ -0,0 +1 @@
+#d $incihex("data1.hex") ; = 0x123456780000abcd
@@ -0,0 +1,2 @@
+#d $incihex("data5.hex", 0x0, 1) ; = 0x11
+#d $incihex("data5.hex", 0xffff_0000, 1) ; = 0x22
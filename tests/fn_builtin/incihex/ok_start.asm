@@ -0,0 +1 @@
+#d $incihex("data1.hex", 0x106) ; = 0xabcd
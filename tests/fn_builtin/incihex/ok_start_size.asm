@@ -0,0 +1,3 @@
+#d $incihex("data1.hex", 0x102, 2) ; = 0x5678
+#d $incihex("data1.hex", 0x103, 4) ; = 0x780000ab
+#d $incihex("data1.hex", 0x100, 0) ; = 0x
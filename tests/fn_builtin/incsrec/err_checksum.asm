@@ -0,0 +1 @@
+#d $incsrec("data3.s19") ; error: failed / error: checksum mismatch in S-record at line 1
@@ -0,0 +1 @@
+#d $incsrec("unk") ; error: failed / error: not found
@@ -0,0 +1 @@
+#d $incsrec("data4.s19") ; error: failed / error: invalid S-record at line 1
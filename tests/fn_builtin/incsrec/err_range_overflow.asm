@@ -0,0 +1 @@
+#d $incsrec("data1.s19", 0xffffffffffffffff, 2) ; error: failed / error: range end is outside the supported range
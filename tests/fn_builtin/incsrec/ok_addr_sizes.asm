@@ -0,0 +1,2 @@
+#d $incsrec("data2.srec", 0x1000_0000, 2) ; = 0xcafe
+#d $incsrec("data2.srec", 0x12_3456, 1) ; = 0x01
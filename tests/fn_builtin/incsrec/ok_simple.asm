@@ -0,0 +1 @@
+#d $incsrec("data1.s19") ; = 0x123456780000abcd
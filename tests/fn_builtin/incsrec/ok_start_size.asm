@@ -0,0 +1,2 @@
+#d $incsrec("data1.s19", 0x8001, 2) ; = 0x3456
+#d $incsrec("data1.s19", 0x8004, 3) ; = 0x0000ab
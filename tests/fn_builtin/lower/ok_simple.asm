@@ -0,0 +1,2 @@
+#d $lower("ABC") ; = 0x616263
+#d $lower("A1-z") ; = 0x61312d7a
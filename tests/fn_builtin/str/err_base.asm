@@ -0,0 +1 @@
+#d $str(5, 1) ; error: failed / error: invalid base 1
@@ -0,0 +1,6 @@
+#d $str(0) ; = 0x30
+#d $str(123) ; = 0x313233
+#d $str(-5) ; = 0x2d35
+#d $str(0xff, 16) ; = 0x6666
+#d $str(5, 2) ; = 0x313031
+#d $str(35, 36) ; = 0x7a
@@ -0,0 +1 @@
+#d $substr("hello", 3, 6) ; error: failed / error: invalid substring range
@@ -0,0 +1 @@
+#d $substr("héllo", 0, 2) ; error: failed / error: substring range 0..2 splits a multi-byte character
@@ -0,0 +1,7 @@
+#d $substr("hello", 1, 3) ; = 0x656c
+#d $substr("hello", 3) ; = 0x6c6f
+#d $substr("hello", 0, 5) ; = 0x68656c6c6f
+#d $substr("hello", 2, 2) @ 0x00 ; = 0x00
+#d $substr("héllo", 0, $strlen("héllo")) ; = 0x68c3a96c6c6f
+#d $substr("héllo", 1, 3) ; = 0xc3a9
+#d $substr("héllo", 3) ; = 0x6c6c6f
@@ -0,0 +1,2 @@
+#d $upper("abc") ; = 0x414243
+#d $upper("a1-Z") ; = 0x41312d5a
@@ -0,0 +1 @@
+#include "second.inc" ; error: file not found
@@ -0,0 +1,2 @@
+#include "missing.inc" ; error: file not found / note: searched in
+; command: err_not_found.asm -I lib -I lib2 -p
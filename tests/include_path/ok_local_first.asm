@@ -0,0 +1,3 @@
+#include "local.inc"
+; command: ok_local_first.asm -I lib -p
+; = 0x44
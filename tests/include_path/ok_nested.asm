@@ -0,0 +1,3 @@
+#include "lib/nested.inc"
+; command: ok_nested.asm --include-path=lib2 -p
+; = 0x33
@@ -0,0 +1,4 @@
+#include "first.inc"
+#include "second.inc"
+; command: ok_simple.asm -I lib -I lib2 -p
+; = 0x1133
@@ -0,0 +1,7 @@
+#macro twice(x)
+{
+    #d8 x
+    #d8 x
+}
+
+twice 1, 2 ; error: macro expected 1 argument (but got 2) / note:_:1: macro declared here
@@ -0,0 +1,7 @@
+#macro put(a)
+{
+    #d8 0x00
+    #d8 a
+}
+
+put 0x1234 ; error: out of range for directive / note: data directive has size 8 / note: in expansion of macro `put`
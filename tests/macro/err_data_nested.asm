@@ -0,0 +1,12 @@
+#macro inner(x)
+{
+    #d8 x
+}
+
+#macro outer(y)
+{
+    #d8 0x00
+    inner y
+}
+
+outer 0x1234 ; error: out of range for directive / note: data directive has size 8 / note:_:9: in expansion of macro `inner` / note: in expansion of macro `outer`
@@ -0,0 +1,11 @@
+#macro m()
+{
+    #d8 0
+}
+
+#macro m() ; error: duplicate macro `m` / note:_:1: first declared here
+{
+    #d8 1
+}
+
+m
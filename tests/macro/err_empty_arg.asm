@@ -0,0 +1,6 @@
+#macro m(x, y)
+{
+    #d8 x, y
+}
+
+m 1, ; error: expected macro argument
@@ -0,0 +1,11 @@
+#macro inner(x)
+{
+    unknown x
+}
+
+#macro outer(y)
+{
+    inner y
+}
+
+outer 0x12 ; note: in expansion of macro `outer` / note:_:8: in expansion of macro `inner` / error:_:3: no match found
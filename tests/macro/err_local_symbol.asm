@@ -0,0 +1,7 @@
+#macro m()
+{
+    top:
+    .inner: ; error: only top-level symbols
+}
+
+m
@@ -0,0 +1,7 @@
+#if true
+{
+    #macro m() ; error: macros can only be declared at the top level
+    {
+        #d8 0
+    }
+}
@@ -0,0 +1,11 @@
+#ruledef
+{
+    ld {x: u8} => 0xaa @ x
+}
+
+#macro m(x)
+{
+    st x
+}
+
+m 0x12 ; note: in expansion of macro `m` / error:_:8: no match found
@@ -0,0 +1,6 @@
+#macro put(x)
+{
+    #d8 x
+}
+
+put 1 + ; note: in expansion of macro `put` / error:_:3: macro argument `1 +` is not a valid expression
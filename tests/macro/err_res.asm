@@ -0,0 +1,6 @@
+#macro skip(n)
+{
+    #res n
+}
+
+skip -1 ; error: outside supported range / note: in expansion of macro `skip`
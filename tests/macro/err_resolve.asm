@@ -0,0 +1,11 @@
+#ruledef
+{
+    ld {x: u8} => 0x12 @ x
+}
+
+#macro m(a)
+{
+    ld a
+}
+
+m 0x1234 ; error:_:8: failed to resolve / note:_:3: within / error:_:8: out of range / note: in expansion of macro `m`
@@ -0,0 +1,14 @@
+#ruledef
+{
+    nop => 0xaa
+    ld {x: u8} => 0x12 @ x
+}
+
+#macro ld(x) ; warning: macro `ld` shadows instructions / note:_:4: rule never matched
+{
+    #d8 x
+}
+
+ld 0x55
+nop
+; = 0x55aa
@@ -0,0 +1,12 @@
+#bankdef a { #addr 0x00, #size 0x02, #outp 8 * 0x00 }
+#bankdef b { #addr 0x10, #size 0x02, #outp 8 * 0x02 }
+
+#macro put(bank_name, value)
+{
+    #bank bank_name
+    #d8 value
+}
+
+put b, 0x22
+put a, 0x11
+#d8 0x12 ; = 0x1112_22
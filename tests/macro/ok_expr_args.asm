@@ -0,0 +1,8 @@
+#macro put(x, y)
+{
+    #d8 x
+    #d8 y * 2
+}
+
+value = 3
+put value + 1, (1 + 2) ; = 0x0406
@@ -0,0 +1,14 @@
+#macro pick(cond)
+{
+    #if cond
+    {
+        #d8 0x11
+    }
+    #else
+    {
+        #d8 0x22
+    }
+}
+
+pick 1 == 1 ; = 0x11
+pick 1 == 2 ; = 0x22
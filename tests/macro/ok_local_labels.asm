@@ -0,0 +1,13 @@
+#ruledef
+{
+    jmp {addr: u8} => 0xee @ addr
+}
+
+#macro spin()
+{
+    top:
+    jmp top
+}
+
+spin ; = 0xee00
+spin ; = 0xee02
@@ -0,0 +1,16 @@
+#ruledef
+{
+    jmp {addr: u8} => 0xee @ addr
+}
+
+#macro spin()
+{
+    top:
+    jmp top
+}
+
+start:
+    spin ; = 0xee00
+.inner:
+    jmp .inner ; = 0xee02
+    jmp start.inner ; = 0xee02
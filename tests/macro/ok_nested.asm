@@ -0,0 +1,12 @@
+#macro inner(x)
+{
+    #d8 x
+}
+
+#macro outer(y)
+{
+    inner y + 1
+    inner y * 2
+}
+
+outer 3 ; = 0x0406
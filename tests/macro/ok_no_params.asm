@@ -0,0 +1,13 @@
+#ruledef
+{
+    nop => 0x00
+    halt => 0xff
+}
+
+#macro stop()
+{
+    nop
+    halt
+}
+
+stop ; = 0x00ff
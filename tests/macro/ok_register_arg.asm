@@ -0,0 +1,18 @@
+#subruledef reg
+{
+    a => 0x0
+    b => 0x1
+}
+
+#ruledef
+{
+    mov {r: reg}, {x: u8} => 0x1 @ r`4 @ x
+}
+
+#macro load(r, value)
+{
+    mov r, value
+}
+
+load a, 0x12 ; = 0x1012
+load b, 0x34 ; = 0x1134
@@ -0,0 +1,13 @@
+#ruledef
+{
+    ld {x: u8} => 0xaa @ x
+}
+
+#macro twice(x)
+{
+    ld x
+    ld x
+}
+
+twice 0x12 ; = 0xaa12aa12
+twice 0x34 ; = 0xaa34aa34
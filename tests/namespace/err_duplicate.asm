@@ -0,0 +1,11 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace lib
+{
+    loop:
+    loop: ; error: duplicate symbol `loop` / note:_:9: first declared here
+}
@@ -0,0 +1 @@
+#export x ; error: must be directly inside
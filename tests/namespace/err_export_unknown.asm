@@ -0,0 +1,9 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+#namespace lib
+{
+    #export missing ; error: unknown symbol `missing`
+}
@@ -0,0 +1,12 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace lib
+{
+    loop:
+}
+
+ld loop ; error: failed / note:_:3: within / error: unknown symbol `loop`
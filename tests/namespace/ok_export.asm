@@ -0,0 +1,18 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace lib
+{
+    #export init, SIZE
+    SIZE = 0x10
+    init:
+        ld SIZE ; = 0x5510
+    other:
+}
+
+ld init ; = 0x5500
+ld SIZE ; = 0x5510
+ld lib.other ; = 0x5502
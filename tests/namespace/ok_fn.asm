@@ -0,0 +1,13 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace math
+{
+    #fn double(x) => x * 2
+    ld double(3) ; = 0x5506
+}
+
+ld math.double(4) ; = 0x5508
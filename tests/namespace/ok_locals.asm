@@ -0,0 +1,17 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+start:
+#namespace lib
+{
+    init:
+    .inner:
+        ld .inner ; = 0x5500
+        ld start ; = 0x5500
+}
+.local:
+ld .local ; = 0x5504
+ld lib.init.inner ; = 0x5500
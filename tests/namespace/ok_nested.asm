@@ -0,0 +1,19 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace outer
+{
+    #namespace inner
+    {
+        value = 5
+        #export value
+    }
+    ld inner.value ; = 0x5505
+    ld value ; = 0x5505
+}
+
+ld outer.inner.value ; = 0x5505
+ld outer.value ; = 0x5505
@@ -0,0 +1,20 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#namespace a
+{
+    loop:
+        ld loop ; = 0x5500
+}
+
+#namespace b
+{
+    loop:
+        ld loop ; = 0x5502
+}
+
+ld a.loop ; = 0x5500
+ld b.loop ; = 0x5502
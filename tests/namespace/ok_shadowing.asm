@@ -0,0 +1,13 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+loop:
+#namespace lib
+{
+    ld loop ; = 0x5502
+    loop:
+}
+ld loop ; = 0x5500
@@ -0,0 +1,4 @@
+#repeat 10000000 ; error: exceeded repeat limit (10000000 iterations, maximum is 65536)
+{
+    #d8 0xaa
+}
@@ -0,0 +1,4 @@
+#for i in 0..0x1_0000_0000 ; error: exceeded repeat limit (4294967296 iterations, maximum is 65536)
+{
+    #d8 i
+}
@@ -0,0 +1,6 @@
+#repeat end ; error: exceeded repeat limit (1048576 iterations, maximum is 65536)
+{
+    #d8 0xaa
+}
+#addr 0x10_0000
+end:
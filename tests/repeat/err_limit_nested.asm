@@ -0,0 +1,7 @@
+#repeat 1000
+{
+    #repeat 60000 ; error: exceeded repeat limit (program would have over 1048576 nodes)
+    {
+        #d8 0xaa
+    }
+}
@@ -0,0 +1,5 @@
+#repeat 2
+{
+    top:
+    .inner: ; error: only top-level symbols
+}
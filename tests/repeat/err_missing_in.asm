@@ -0,0 +1,4 @@
+#for i of 0..2 ; error: expected `in`
+{
+    #d8 i
+}
@@ -0,0 +1,4 @@
+#for i in 2 ; error: expected `..`
+{
+    #d8 i
+}
@@ -0,0 +1,4 @@
+#repeat -1 ; error: value is outside the supported range
+{
+    #d8 0xaa
+}
@@ -0,0 +1,5 @@
+#repeat end + 1 ; error: repeat count did not converge
+{
+    #d8 0xaa
+}
+end:
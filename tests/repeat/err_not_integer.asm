@@ -0,0 +1,4 @@
+#repeat true ; error: expected non-negative integer
+{
+    #d8 0xaa
+}
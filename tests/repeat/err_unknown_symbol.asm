@@ -0,0 +1,4 @@
+#repeat x ; error: unresolved repeat count / error: unknown symbol `x`
+{
+    #d8 0xaa
+}
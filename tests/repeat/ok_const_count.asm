@@ -0,0 +1,5 @@
+count = 2
+#repeat count * 2
+{
+    #d8 0xaa
+} ; = 0xaaaaaaaa
@@ -0,0 +1,6 @@
+#d8 0x11
+#for i in 5..2
+{
+    #d8 i
+}
+#d8 0x22 ; = 0x1122
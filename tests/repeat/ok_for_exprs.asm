@@ -0,0 +1,5 @@
+start = 2
+#for i in start + 1..start * 3
+{
+    #d8 i
+} ; = 0x030405
@@ -0,0 +1,4 @@
+#for i in 0..4
+{
+    #d8 i * 2
+} ; = 0x00020406
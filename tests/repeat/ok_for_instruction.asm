@@ -0,0 +1,9 @@
+#ruledef
+{
+    ld r{n: u4} => 0x1 @ n
+}
+
+#for i in 0..3
+{
+    ld r{i}
+} ; = 0x101112
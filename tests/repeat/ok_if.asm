@@ -0,0 +1,7 @@
+#for i in 0..4
+{
+    #if i % 2 == 0
+    {
+        #d8 i
+    }
+} ; = 0x0002
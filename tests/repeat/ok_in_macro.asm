@@ -0,0 +1,10 @@
+#macro fill(n, value)
+{
+    #repeat n
+    {
+        #d8 value
+    }
+}
+
+fill 2, 0xaa
+fill 1, 0xbb ; = 0xaaaabb
@@ -0,0 +1,15 @@
+#ruledef
+{
+    nop => 0x00
+}
+
+#repeat fill_end - fill_start
+{
+    nop
+}
+
+fill_start:
+#d8 0xff
+#d8 0xff
+#d8 0xff
+fill_end: ; = 0x000000ffffff
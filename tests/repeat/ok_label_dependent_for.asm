@@ -0,0 +1,8 @@
+start:
+#d8 0x11
+#d8 0x22
+end:
+#for i in start..end
+{
+    #d8 i
+} ; = 0x11220001
@@ -0,0 +1,13 @@
+#ruledef
+{
+    jmp {addr: u8} => 0xee @ addr
+}
+
+outer:
+#repeat 2
+{
+    top:
+    #d8 0x00
+}
+.inner:
+jmp outer.inner ; = 0x0000ee02
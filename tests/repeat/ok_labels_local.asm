@@ -0,0 +1,10 @@
+#ruledef
+{
+    jmp {addr: u8} => 0xee @ addr
+}
+
+#repeat 2
+{
+    top:
+    jmp top
+} ; = 0xee00ee02
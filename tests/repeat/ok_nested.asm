@@ -0,0 +1,8 @@
+#for i in 0..2
+{
+    #for j in 0..3
+    {
+        #d4 i
+        #d4 j
+    }
+} ; = 0x000102101112
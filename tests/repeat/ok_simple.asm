@@ -0,0 +1,4 @@
+#repeat 3
+{
+    #d8 0xaa
+} ; = 0xaaaaaa
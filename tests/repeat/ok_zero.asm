@@ -0,0 +1,6 @@
+#d8 0x11
+#repeat 0
+{
+    #d8 0xaa
+}
+#d8 0x22 ; = 0x1122
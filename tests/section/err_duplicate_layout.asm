@@ -0,0 +1,10 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a
+    code => a ; error: duplicate layout for section `code`
+}
+
+#section code
+#d8 1
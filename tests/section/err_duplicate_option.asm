@@ -0,0 +1,9 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a, align = 2, align = 4 ; error: duplicate layout option `align`
+}
+
+#section code
+#d8 1
@@ -0,0 +1,9 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a, keep = true, discard = true ; error: section cannot be both kept and discarded
+}
+
+#section code
+#d8 0x11
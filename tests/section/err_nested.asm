@@ -0,0 +1,12 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a
+    data => code ; error: cannot place a section inside another section
+}
+
+#section code
+#d8 1
+#section data
+#d8 2
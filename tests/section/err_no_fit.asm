@@ -0,0 +1,10 @@
+#bankdef a { #addr 0xaa00, #size 0x2, #outp 8 * 0x0 }
+#bankdef b { #addr 0xbb00, #size 0x2, #outp 8 * 0x2 }
+
+#layout
+{
+    code => a, b ; error: section `code` does not fit in any of its banks
+}
+
+#section code
+#d8 1, 2, 3
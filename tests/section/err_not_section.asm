@@ -0,0 +1,6 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    a => a ; error: `a` is not a section
+}
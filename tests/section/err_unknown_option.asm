@@ -0,0 +1,9 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a, fill = true ; error: unknown layout option `fill`
+}
+
+#section code
+#d8 1
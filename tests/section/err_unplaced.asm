@@ -0,0 +1,4 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#section code ; error: section `code` is not placed by any `#layout`
+#d8 1
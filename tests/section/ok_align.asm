@@ -0,0 +1,19 @@
+#ruledef test
+{
+    ld {x: u16} => 0x55 @ x
+}
+
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    vectors => a, align = 8
+}
+
+#bank a
+ld 0x1234
+
+#section vectors
+x:
+ld x
+; = 0x551234_0000000000_55aa08
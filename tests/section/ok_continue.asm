@@ -0,0 +1,21 @@
+#ruledef test
+{
+    ld {x: u16} => 0x55 @ x
+}
+
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a
+}
+
+#section code
+x:
+ld x
+#bank a
+ld 0x1234
+#section code
+y:
+ld y
+; = 0x551234_55aa03_55aa06
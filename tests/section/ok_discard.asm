@@ -0,0 +1,18 @@
+#ruledef test
+{
+    ld {x: u16} => 0x55 @ x
+}
+
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a
+    debug => a, discard = true
+}
+
+#section debug
+#d "debug"
+#section code
+ld 0x1234
+; = 0x551234
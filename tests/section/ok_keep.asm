@@ -0,0 +1,27 @@
+#ruledef test
+{
+    ld {x: u16} => 0x55 @ x
+}
+
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a
+    unused => a, keep = false
+    called => a, keep = false
+    nested => a, keep = false
+}
+
+#section unused
+unused_fn:
+#d8 0x11
+#section nested
+nested_fn:
+#d8 0x22
+#section called
+called_fn:
+ld nested_fn
+#section code
+ld called_fn
+; = 0x55aa03_55aa06_22
@@ -0,0 +1,24 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    code => a
+    unused => a, keep = false
+    only_from_unused => a, keep = false
+    only_from_debug => a, keep = false
+    debug => a, discard = true
+}
+
+#section unused
+#d8 x
+#section only_from_unused
+x:
+#d8 0x22
+#section only_from_debug
+y:
+#d8 0x33
+#section debug
+#d8 y
+#section code
+#d8 0x11
+; = 0x11
@@ -0,0 +1,15 @@
+#bankdef a { #addr 0xaa00, #size 0x10, #outp 8 * 0x0 }
+
+#layout
+{
+    first => a
+    second => a
+}
+
+#section first
+#d8 1, 2, 3
+#section second
+label:
+#d16 $bankof(label).addr
+#d16 $bankof(label).size
+; = 0x010203_aa03_0004
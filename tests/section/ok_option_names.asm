@@ -0,0 +1,23 @@
+#ruledef test
+{
+    ld {x: u16} => 0x55 @ x
+}
+
+#bankdef align { #addr 0xaa00, #size 0x4, #outp 8 * 0x0 }
+#bankdef discard { #addr 0xbb00, #size 0x10, #outp 8 * 0x4 }
+
+#layout
+{
+    code => align, discard, align = 2
+    debug => discard, discard = 1 == 1
+}
+
+#bank align
+#d8 0xff
+
+#section code
+x:
+ld x
+#section debug
+#d "debug"
+; = 0xff_000000_55bb00
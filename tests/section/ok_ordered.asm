@@ -0,0 +1,26 @@
+#ruledef test
+{
+    ld {x: u16} => 0x55 @ x
+}
+
+#bankdef a { #addr 0xaa00, #size 0x4, #outp 8 * 0x0 }
+#bankdef b { #addr 0xbb00, #size 0x10, #outp 8 * 0x4 }
+
+#layout
+{
+    first => a, b
+    second => a, b
+    third => a, b, ordered = true
+}
+
+#section first
+x:
+ld x
+#section second
+y:
+ld y
+ld z
+#section third
+z:
+#d8 0x11
+; = 0x55aa00_00_55bb00_55bb06_11
@@ -0,0 +1 @@
+#struct Point { x: 8, x: 8 } ; error: duplicate symbol `x` / note:_:1: first declared here
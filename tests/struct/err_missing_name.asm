@@ -0,0 +1 @@
+#struct Point { x: 8, 8 } ; error: expected identifier
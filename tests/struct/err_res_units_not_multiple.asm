@@ -0,0 +1,3 @@
+#struct Flags { mode: 4, count: 8 }
+
+#res Flags.units ; error: struct size of 12 bits isn't a whole number of 8-bit address units
@@ -0,0 +1 @@
+#struct Buffer { data: 8, size: 8 } ; error: field name `size` is reserved for the size of the struct
@@ -0,0 +1 @@
+#struct Buffer { data: 8, units: 8 } ; error: field name `units` is reserved for the size of the struct
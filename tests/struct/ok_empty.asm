@@ -0,0 +1,3 @@
+#struct Empty {}
+
+#d8 $sizeof(Empty) ; = 0x00
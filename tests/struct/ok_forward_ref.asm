@@ -0,0 +1,12 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+ld Player.size ; = 0x5518
+ld Player.score ; = 0x5508
+
+#struct Player { lives: LIVES_BITS, score: 16 }
+
+LIVES_BITS = 8
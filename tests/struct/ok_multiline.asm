@@ -0,0 +1,19 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+WORD = 16
+
+#struct Header
+{
+    magic: 2 * WORD
+    length: WORD
+    kind: 8, flags: 8
+}
+
+ld Header.length ; = 0x5520
+ld Header.kind ; = 0x5530
+ld Header.flags ; = 0x5538
+ld Header.size / 8 ; = 0x5508
@@ -0,0 +1,13 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#struct Vec2 { x: 8, y: 8 }
+#struct Entity { id: 8, pos: Vec2.size, hp: 8 }
+
+ld Entity.pos ; = 0x5508
+ld Entity.pos + Vec2.y ; = 0x5510
+ld Entity.hp ; = 0x5518
+ld Entity.size ; = 0x5520
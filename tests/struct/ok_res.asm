@@ -0,0 +1,13 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#struct Sprite { x: 8, y: 8, tile: 8, attr: 8 }
+
+sprites:
+#res 2 * Sprite.units
+after:
+ld after ; = 0x00000000_00000000_5508
+ld sprites + 1 * Sprite.units + Sprite.tile / 8 ; = 0x5506
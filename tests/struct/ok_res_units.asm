@@ -0,0 +1,14 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#struct Sprite { x: 8, y: 8, tile: 8, attr: 8 }
+
+sprite:
+#res Sprite.units
+after:
+ld after ; = 0x00000000_5504
+#res Sprite.units + 0
+ld $ ; = 0x00000000_550a
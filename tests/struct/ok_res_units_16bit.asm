@@ -0,0 +1,12 @@
+#bankdef words
+{
+    bits = 16
+    addr = 0
+    outp = 0
+}
+
+#struct Point { x: 16, y: 16 }
+
+#res Point.units
+#res 1 * Point.units
+#d16 $ ; = 0x0000_0000_0000_0000_0004
@@ -0,0 +1,11 @@
+#bankdef bits
+{
+    bits = 1
+    addr = 0
+    outp = 0
+}
+
+#struct Flags { mode: 4, count: 8 }
+
+#res Flags.units
+#d4 0xf ; = 0x000f
@@ -0,0 +1,12 @@
+#struct Regs { ctrl: 8, status: 8, data: 16 }
+
+#ruledef test
+{
+    ldr {base: u8}.{field} => 0x55 @ (base + field / 8)`8
+    ldw {x: u8} => 0x66 @ x @ 0`(Regs.data.size - 8)
+}
+
+
+ldr 0x10.Regs.status ; = 0x5511
+ldr 0x10.Regs.data ; = 0x5512
+ldw 0xab ; = 0x66ab00
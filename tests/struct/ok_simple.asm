@@ -0,0 +1,14 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#struct Point { x: 16, y: 8, flags: 4 }
+
+ld Point.x ; = 0x5500
+ld Point.y ; = 0x5510
+ld Point.flags ; = 0x5518
+ld Point.y.size ; = 0x5508
+ld Point.size ; = 0x551c
+ld $sizeof(Point) ; = 0x551c
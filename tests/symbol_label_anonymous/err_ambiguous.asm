@@ -0,0 +1,2 @@
+#d8 :+-1 ; error: ambiguous anonymous label reference / note:_:1: put a space
+:
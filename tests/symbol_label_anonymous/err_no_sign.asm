@@ -0,0 +1 @@
+#d8 : ; error: expected `-` or `+`
@@ -0,0 +1,8 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+:
+    ld :-- ; error: failed / note:_:3: within / error: unmatched anonymous label reference
@@ -0,0 +1,11 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+:
+    ld :- ; = 0x5500
+:
+    ld :- ; = 0x5502
+    ld :-- ; = 0x5500
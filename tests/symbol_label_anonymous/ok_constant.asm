@@ -0,0 +1,10 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+x = :+
+    ld x ; = 0x5502
+:
+    ld x ; = 0x5502
@@ -0,0 +1,4 @@
+#d8 :+, :++ ; = 0x0203
+:
+#d8 :- ; = 0x02
+:
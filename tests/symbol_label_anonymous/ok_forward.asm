@@ -0,0 +1,11 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+    ld :+ ; = 0x5504
+    ld :++ ; = 0x5506
+:
+    ld :+ ; = 0x5506
+:
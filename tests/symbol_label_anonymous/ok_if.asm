@@ -0,0 +1,17 @@
+#ruledef test
+{
+    jmp {x} => 0x55 @ x`8
+}
+
+
+X = 1
+
+:
+    jmp :- ; = 0x5500
+#if X == 1
+{
+:
+    jmp :- ; = 0x5502
+}
+:
+    jmp :- ; = 0x5504
@@ -0,0 +1,14 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+global1:
+.local1:
+:
+    ld .local1 ; = 0x5500
+    ld :- ; = 0x5500
+    ld :- + 1 ; = 0x5501
+    ld :+ - 1 ; = 0x5507
+:
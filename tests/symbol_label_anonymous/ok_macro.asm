@@ -0,0 +1,14 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+#macro wait_loop()
+{
+    :
+        ld :-
+}
+
+wait_loop ; = 0x5500
+wait_loop ; = 0x5502
@@ -0,0 +1,15 @@
+#ruledef test
+{
+    jmp {x} => 0x55 @ x`8
+}
+
+
+:
+    jmp :+ ; = 0x5502
+#repeat 2
+{
+:
+    jmp :+
+} ; = 0x55045506
+:
+    jmp :- ; = 0x5506
@@ -0,0 +1,15 @@
+#ruledef test
+{
+    nop => 0x00
+}
+
+
+#repeat :++ - :+
+{
+    nop
+}
+:
+#d8 0xff
+#d8 0xff
+#d8 0xff
+: ; = 0x000000ffffff
@@ -0,0 +1,10 @@
+#ruledef test
+{
+    ld {x} => 0x55 @ x`8
+}
+
+
+:
+    ld true ? :+ : :- ; = 0x5504
+    ld false ? :+ : :- ; = 0x5500
+:
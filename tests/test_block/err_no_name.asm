@@ -0,0 +1,2 @@
+#test { ; error: expected string
+}
@@ -0,0 +1,13 @@
+#ruledef
+{
+    ld {x: i8} => 0x55 @ x
+}
+
+#test "skipped"
+{
+    ld 0x34
+    label:
+}
+
+ld 0x12 ; = 0x5512
+label: